@@ -0,0 +1,827 @@
+use std::collections::HashMap;
+use codespan::{ByteIndex, Span};
+use codespan_reporting::diagnostic::{Diagnostic, Severity};
+use common::codes::Code;
+use common::errors::{Reporting, Suggestion};
+use common::features::{Feature, Features};
+use common::files::FileId;
+use common::levels::{Level, LintLevels};
+use common::spelling::closest;
+use crate::{AstNode, Attribute, BinaryOperator, Type, TypedValue, UnaryOperator};
+use crate::constant::{coerce, evaluate, EvalError};
+use crate::parser::TYPE_NAMES;
+
+/// The name of the built-in function that prints a single value of any type.
+pub const PRINT: &str = "print";
+
+/// The name of the function that is called after the top-level statements ran, if it takes
+/// no parameters.
+pub const ENTRY_POINT: &str = "Main";
+
+/// Returns the type of a literal value, or `None` when the value is a reference whose type
+/// depends on the scope it is evaluated in.
+pub fn literal_type(value: &TypedValue) -> Option<Type> {
+    match value {
+        TypedValue::ReferenceVal(_) => None,
+        TypedValue::StringVal(_) => Some(Type::String),
+        TypedValue::IntVal(_) => Some(Type::Int),
+        TypedValue::FloatVal(_) => Some(Type::Float),
+        TypedValue::BoolVal(_) => Some(Type::Boolean),
+        TypedValue::NullVal => Some(Type::Null),
+    }
+}
+
+/// Returns whether a value of type `found` can be stored in a slot of type `expected`.
+///
+/// Integers widen to floats, and `null` can be stored in a `String`.
+pub fn is_assignable(expected: Type, found: Type) -> bool {
+    expected == found
+        || (expected == Type::Float && found == Type::Int)
+        || (expected == Type::String && found == Type::Null)
+}
+
+/// Returns the type both operands of a binary operator are converted to before it is
+/// applied, together with the type of its result.
+///
+/// # Returns
+///
+/// Returns `None` if the operator cannot be applied to operands of the given types.
+pub fn binary_types(op: BinaryOperator, lhs: Type, rhs: Type) -> Option<(Type, Type)> {
+    let numeric = match (lhs, rhs) {
+        (Type::Int, Type::Int) => Some(Type::Int),
+        (Type::Int, Type::Float) | (Type::Float, Type::Int) | (Type::Float, Type::Float) => Some(Type::Float),
+        _ => None,
+    };
+
+    match op {
+        BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul | BinaryOperator::Div => {
+            numeric.map(|operands| (operands, operands))
+        }
+        BinaryOperator::Less | BinaryOperator::Greater | BinaryOperator::LessEq | BinaryOperator::GreaterEq => {
+            numeric.map(|operands| (operands, Type::Boolean))
+        }
+        BinaryOperator::Eq | BinaryOperator::NotEq => match (lhs, rhs) {
+            (Type::Boolean, Type::Boolean) => Some((Type::Boolean, Type::Boolean)),
+            _ => numeric.map(|operands| (operands, Type::Boolean)),
+        },
+        BinaryOperator::And | BinaryOperator::Or => match (lhs, rhs) {
+            (Type::Boolean, Type::Boolean) => Some((Type::Boolean, Type::Boolean)),
+            _ => None,
+        },
+    }
+}
+
+/// Returns the type of the result of a unary operator, or `None` if the operator cannot be
+/// applied to an operand of the given type.
+pub fn unary_type(op: UnaryOperator, operand: Type) -> Option<Type> {
+    match (op, operand) {
+        (UnaryOperator::Neg, Type::Int | Type::Float) => Some(operand),
+        (UnaryOperator::Not, Type::Boolean) => Some(Type::Boolean),
+        _ => None,
+    }
+}
+
+/// A variable that is visible in a scope.
+#[derive(Clone)]
+struct Binding {
+    var_type: Type,
+    is_static: bool,
+    /// Whether the variable was declared with `mut`, so that it can be assigned to
+    is_mutable: bool,
+    /// Whether the binding is a parameter, which cannot be declared mutable
+    is_parameter: bool,
+    /// The compile-time value of a static variable, `None` if it could not be evaluated
+    constant: Option<TypedValue>,
+    /// The span of the declaration
+    span: Span,
+}
+
+impl Binding {
+    /// Creates the binding of a variable whose value is only known at run time.
+    fn variable(var_type: Type, is_mutable: bool, span: Span) -> Self {
+        Binding {
+            var_type,
+            is_static: false,
+            is_mutable,
+            is_parameter: false,
+            constant: None,
+            span,
+        }
+    }
+
+    /// Creates the binding of a parameter, the span is the span of its function.
+    fn parameter(var_type: Type, span: Span) -> Self {
+        Binding {
+            is_parameter: true,
+            ..Binding::variable(var_type, false, span)
+        }
+    }
+}
+
+/// A diagnostic found by the checker, together with the suggestions that fix it.
+pub type CheckError = (Diagnostic<FileId>, Vec<Suggestion>);
+
+/// The functions and global variables a checker knows about. It can be carried over from one
+/// check to the next, so that separately parsed inputs, such as the lines entered into a
+/// REPL, see each other's declarations.
+#[derive(Default, Clone)]
+pub struct Environment {
+    functions: HashMap<String, Vec<Type>>,
+    globals: HashMap<String, Binding>,
+}
+
+/// Struct representing the semantic checker, which validates names and types of a parsed
+/// document before it is handed to a backend.
+pub struct Checker<'a> {
+    reporter: Reporting<'a>,
+    /// The input that is checked, which all spans refer to
+    input: &'a str,
+    /// Parameter types of every function declared in the document
+    functions: HashMap<String, Vec<Type>>,
+    /// Stack of variable scopes, the innermost scope is last
+    scopes: Vec<HashMap<String, Binding>>,
+    /// The levels of the diagnostic codes where the checker is, which the attributes of the
+    /// function that is being checked are applied to
+    levels: LintLevels,
+    /// The incubating features the input may use
+    features: Features,
+    /// The errors and warnings found so far
+    errors: Vec<CheckError>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(name: &'a str, input: &'a str) -> Self {
+        Self::with_environment(name, input, Environment::default())
+    }
+
+    /// Constructs a checker that already knows the declarations of an earlier check.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the input that is checked.
+    /// * `input` - The input that is checked, which all spans refer to.
+    /// * `environment` - The declarations returned by `check_body`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Checker` instance.
+    pub fn with_environment(name: &'a str, input: &'a str, environment: Environment) -> Self {
+        Checker {
+            reporter: Reporting::new(name, input),
+            input,
+            functions: environment.functions,
+            scopes: vec![environment.globals],
+            levels: LintLevels::new(),
+            features: Features::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Stops the checker from emitting diagnostics, they are only returned.
+    pub fn quiet(mut self) -> Self {
+        self.reporter = self.reporter.quiet();
+        self
+    }
+
+    /// Reports through the given instance instead of one of its own, such as one for a file of
+    /// a multi-file database.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - The `Reporting` instance of the checked input.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Checker` instance.
+    pub fn with_reporting(mut self, reporter: Reporting<'a>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Reports warnings at the given levels instead of the default ones, which the attributes
+    /// of functions override within them.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - The levels set by the project config file and the command line.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Checker` instance.
+    pub fn with_levels(mut self, levels: LintLevels) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Allows the input to use incubating features, which are reported as errors otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `features` - The features set by the project config file and the command line.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Checker` instance.
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Checks the given document, emitting a diagnostic for every error and warning that is
+    /// found.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `AstNode::Document` produced by the parser.
+    ///
+    /// # Returns
+    ///
+    /// Returns the emitted diagnostics if the document is not well typed.
+    pub fn check(self, document: &AstNode) -> Result<(), Vec<Diagnostic<FileId>>> {
+        let diagnostics: Vec<Diagnostic<FileId>> = self.check_with_suggestions(document)
+            .into_iter()
+            .map(|(diagnostic, _)| diagnostic)
+            .collect();
+
+        if diagnostics.iter().any(|diagnostic| diagnostic.severity >= Severity::Error) {
+            return Err(diagnostics)
+        }
+
+        Ok(())
+    }
+
+    /// Checks the given document like `check`, keeping the suggestions that fix the problems
+    /// that are found.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `AstNode::Document` produced by the parser.
+    ///
+    /// # Returns
+    ///
+    /// Returns every emitted diagnostic together with its suggestions, which are empty if the
+    /// document is well typed and nothing was warned about.
+    pub fn check_with_suggestions(mut self, document: &AstNode) -> Vec<CheckError> {
+        if let AstNode::Document { body } = document {
+            if let AstNode::Block { body } = body.as_ref() {
+                self.check_declarations(body);
+            }
+        }
+
+        self.errors
+    }
+
+    /// Checks the top-level declarations and statements of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The nodes of the document's `AstNode::Block`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the declarations known after the check, which include the checker's initial
+    /// environment, or the emitted error diagnostics.
+    pub fn check_body(mut self, body: &[AstNode]) -> Result<Environment, Vec<Diagnostic<FileId>>> {
+        self.check_declarations(body);
+
+        if self.has_errors() {
+            return Err(self.errors.into_iter().map(|(diagnostic, _)| diagnostic).collect())
+        }
+
+        Ok(Environment {
+            functions: self.functions,
+            globals: self.scopes.swap_remove(0),
+        })
+    }
+
+    /// Checks the top-level declarations and statements of a document, collecting the errors.
+    fn check_declarations(&mut self, body: &[AstNode]) {
+        // functions can be called before they are declared
+        let mut declared: HashMap<&str, Span> = HashMap::new();
+        for node in body {
+            if let AstNode::FuncDeclaration { name, params, span, .. } = node {
+                if self.functions.contains_key(name) || name == PRINT {
+                    let mut error = self.reporter.error(
+                        format!("The function `{}` is defined multiple times", name),
+                        *span,
+                        Code::E004,
+                    );
+                    if let Some(first) = declared.get(name.as_str()) {
+                        error = error.secondary(*first, "first defined here");
+                    } else if name == PRINT {
+                        error = error.note("`print` is a built-in function");
+                    }
+                    self.errors.push(error.emit());
+                } else {
+                    let types = params.iter().map(|(_, param_type)| *param_type).collect();
+                    self.functions.insert(name.clone(), types);
+                    declared.insert(name, *span);
+                }
+            }
+        }
+
+        for node in body {
+            self.check_node(node);
+        }
+    }
+
+    /// Checks a single expression in the global scope.
+    ///
+    /// # Returns
+    ///
+    /// Returns the type of the expression, or the emitted error diagnostics.
+    pub fn check_expression(mut self, expression: &AstNode) -> Result<Type, Vec<Diagnostic<FileId>>> {
+        match self.expression_type(expression) {
+            Some(found) if !self.has_errors() => Ok(found),
+            _ => Err(self.errors.into_iter().map(|(diagnostic, _)| diagnostic).collect()),
+        }
+    }
+
+    fn check_node(&mut self, node: &AstNode) {
+        match node {
+            AstNode::Block { body } => {
+                for node in body {
+                    self.check_node(node);
+                }
+            }
+            AstNode::FuncDeclaration { attributes, name, params, body, span, .. } => {
+                let outer = self.levels.clone();
+                self.apply_attributes(attributes);
+                self.scopes.push(HashMap::new());
+
+                for (index, (param, param_type)) in params.iter().enumerate() {
+                    if *param_type == Type::Reference {
+                        let suggestions = self.param_type_start(span.start().to_usize(), index)
+                            .and_then(|start| self.misspelling("type", start, TYPE_NAMES))
+                            .into_iter()
+                            .collect();
+                        self.error_with_suggestions(
+                            format!("Cannot find the type of parameter `{}` in function `{}`", param, name),
+                            *span,
+                            Code::E007,
+                            suggestions,
+                        );
+                    }
+
+                    self.declare(param, Binding::parameter(*param_type, *span), *span);
+                }
+
+                self.check_node(body);
+                self.scopes.pop();
+                self.levels = outer;
+            }
+            AstNode::VarDeclaration { name, var_type, is_mutable, is_static, value, span } => {
+                let mut well_typed = false;
+
+                if *is_static {
+                    self.expect_feature(Feature::StaticDeclarations, "`static` declarations", *span);
+                }
+
+                if *var_type == Type::Reference {
+                    let suggestions = self.misspelling("type", self.type_start(*is_static || *is_mutable, *span), TYPE_NAMES)
+                        .into_iter()
+                        .collect();
+                    self.error_with_suggestions(
+                        format!("Cannot find the type of variable `{}`", name),
+                        *span,
+                        Code::E007,
+                        suggestions,
+                    );
+                } else if let Some(found) = self.expression_type(value) {
+                    well_typed = is_assignable(*var_type, found);
+
+                    if !well_typed {
+                        let suggestions = self.change_type(name, *is_static || *is_mutable, *span, found)
+                            .into_iter()
+                            .collect();
+                        self.error_with_suggestions(mismatched_types(*var_type, found), *span, Code::E003, suggestions);
+                    }
+                }
+
+                let constant = if *is_static && well_typed {
+                    self.evaluate_static(name, value)
+                        .map(|constant| coerce(constant, *var_type))
+                } else {
+                    None
+                };
+
+                let binding = if *is_static {
+                    Binding { is_static: true, constant, ..Binding::variable(*var_type, false, *span) }
+                } else {
+                    Binding::variable(*var_type, *is_mutable, *span)
+                };
+
+                self.declare(name, binding, *span);
+            }
+            AstNode::VarAssignment { name, new_value, span } => {
+                if let Some(binding) = self.lookup_binding(name) {
+                    let (expected, declared) = (binding.var_type, binding.span);
+
+                    if binding.is_static {
+                        let suggestion = self.replace_static(name, declared);
+                        self.error_with_suggestions(
+                            format!("Cannot assign to `{}` because it is static", name),
+                            *span,
+                            Code::E010,
+                            vec![suggestion],
+                        );
+                    } else if binding.is_parameter {
+                        let error = self.reporter.error(format!("Cannot assign to parameter `{}`", name), *span, Code::E016)
+                            .note("parameters cannot be assigned to, declare a mutable variable with their value instead");
+                        self.errors.push(error.emit());
+                    } else if !binding.is_mutable {
+                        let error = self.reporter.error(format!("Cannot assign to `{}` because it is not mutable", name), *span, Code::E016)
+                            .secondary(declared, "declared here")
+                            .suggestion(self.declare_mutable(name, declared));
+                        self.errors.push(error.emit());
+                    }
+
+                    if let Some(found) = self.expression_type(new_value) {
+                        self.expect_type(expected, found, *span);
+                    }
+                } else {
+                    let suggestions = self.misspelt_variable(span.start().to_usize()).into_iter().collect();
+                    self.error_with_suggestions(
+                        format!("Cannot find variable `{}` in this scope", name),
+                        *span,
+                        Code::E002,
+                        suggestions,
+                    );
+                }
+            }
+            // only function bodies push a scope onto the global one
+            AstNode::Return { span } if self.scopes.len() == 1 => {
+                self.error("`return` can only be used in a function body".to_string(), *span, Code::E015);
+            }
+            AstNode::FunctionCall { name, param_list, span } => {
+                let args: Vec<Option<Type>> = param_list.iter()
+                    .map(|arg| self.expression_type(arg))
+                    .collect();
+
+                let expected = if name == PRINT {
+                    // print accepts a single value of any type
+                    vec![args.first().copied().flatten().unwrap_or(Type::Void)]
+                } else if let Some(params) = self.functions.get(name) {
+                    params.clone()
+                } else {
+                    let functions = self.functions.keys().map(String::as_str).chain([PRINT]);
+                    let suggestions = self.misspelling("function", span.start().to_usize(), functions)
+                        .into_iter()
+                        .collect();
+                    self.error_with_suggestions(
+                        format!("Cannot find function `{}` in this scope", name),
+                        *span,
+                        Code::E006,
+                        suggestions,
+                    );
+                    return;
+                };
+
+                if expected.len() != args.len() {
+                    self.error(
+                        format!(
+                            "The function `{}` takes {} argument(s) but {} were supplied",
+                            name,
+                            expected.len(),
+                            args.len()
+                        ),
+                        *span,
+                        Code::E005,
+                    );
+                    return;
+                }
+
+                for (expected, found) in expected.into_iter().zip(args) {
+                    if let Some(found) = found {
+                        self.expect_type(expected, found, *span);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolves the type of an expression, reporting an error for every operator that is
+    /// applied to operands of the wrong type.
+    fn expression_type(&mut self, node: &AstNode) -> Option<Type> {
+        match node {
+            AstNode::Value { value, span } => self.value_type(value, *span),
+            AstNode::UnaryOp { op, operand, span } => {
+                let operand = self.expression_type(operand)?;
+                let found = unary_type(*op, operand);
+
+                if found.is_none() {
+                    self.error(
+                        format!("Cannot apply the operator `{}` to a value of type `{}`", op, operand),
+                        *span,
+                        Code::E008,
+                    );
+                }
+
+                found
+            }
+            AstNode::BinaryOp { op, lhs, rhs, span } => {
+                let lhs = self.expression_type(lhs);
+                let rhs = self.expression_type(rhs);
+                let (lhs, rhs) = (lhs?, rhs?);
+                let types = binary_types(*op, lhs, rhs);
+
+                match types {
+                    None => self.error(
+                        format!("Cannot apply the operator `{}` to values of type `{}` and `{}`", op, lhs, rhs),
+                        *span,
+                        Code::E008,
+                    ),
+                    Some((Type::Float, _)) if matches!(op, BinaryOperator::Eq | BinaryOperator::NotEq) => self.lint(
+                        format!("Floats are compared with `{}`, which rounding errors can make fail", op),
+                        *span,
+                        Code::W001,
+                    ),
+                    Some(_) => {}
+                }
+
+                types.map(|(_, result)| result)
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluates the initializer of a static variable, reporting an error if it cannot be
+    /// evaluated at compile time.
+    fn evaluate_static(&mut self, name: &str, value: &AstNode) -> Option<TypedValue> {
+        let result = evaluate(value, &|reference| {
+            self.lookup_binding(reference).and_then(|binding| binding.constant.clone())
+        });
+
+        match result {
+            Ok(constant) => return Some(constant),
+            Err(EvalError::RuntimeValue { name: reference, span }) => {
+                // statics that could not be evaluated have been reported already
+                if !self.lookup_binding(&reference).is_some_and(|binding| binding.is_static) {
+                    self.error(
+                        format!(
+                            "The static `{}` cannot be evaluated at compile time because it depends on the runtime value `{}`",
+                            name,
+                            reference
+                        ),
+                        span,
+                        Code::E009,
+                    );
+                }
+            }
+            Err(EvalError::Undefined { op, span }) => {
+                self.error(
+                    format!("The static `{}` cannot be evaluated at compile time because `{}` overflows or divides by zero", name, op),
+                    span,
+                    Code::E011,
+                );
+            }
+        }
+
+        None
+    }
+
+    /// Resolves the type of a value, reporting an error if it references an unknown variable.
+    fn value_type(&mut self, value: &TypedValue, span: Span) -> Option<Type> {
+        if let TypedValue::ReferenceVal(reference) = value {
+            let found = self.lookup(reference);
+
+            if found.is_none() {
+                let suggestions = self.misspelt_variable(span.start().to_usize()).into_iter().collect();
+                self.error_with_suggestions(
+                    format!("Cannot find variable `{}` in this scope", reference),
+                    span,
+                    Code::E002,
+                    suggestions,
+                );
+            }
+
+            return found
+        }
+
+        literal_type(value)
+    }
+
+    /// Reports an error if a value of type `found` cannot be stored in a slot of type
+    /// `expected`, returning whether it can.
+    fn expect_type(&mut self, expected: Type, found: Type, span: Span) -> bool {
+        if !is_assignable(expected, found) {
+            self.error(mismatched_types(expected, found), span, Code::E003);
+            return false
+        }
+
+        true
+    }
+
+    /// Reports an error at the first word of a node if it uses a feature that is not enabled.
+    /// The node is checked as usual either way.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The feature the node uses.
+    /// * `what` - What the node is, such as `` `static` declarations ``.
+    /// * `span` - The span of the node, which starts with the word that needs the feature.
+    fn expect_feature(&mut self, feature: Feature, what: &str, span: Span) {
+        if self.features.is_enabled(feature) {
+            return
+        }
+
+        let start = span.start().to_usize();
+        let word = Span::new(span.start(), ByteIndex(self.skip_word(start) as u32));
+        let error = self.reporter.error(format!("{} are an incubating feature that is not enabled", what), word, Code::E018)
+            .help(&format!("enable it with `--feature {}`, or in the `[features]` table of zxx.toml", feature));
+        self.errors.push(error.emit());
+    }
+
+    /// Suggests declaring a variable with the type of its initializer instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the variable.
+    /// * `has_keyword` - Whether the declaration starts with `static` or `mut`, which precedes
+    ///   the type.
+    /// * `span` - The span of the declaration.
+    /// * `found` - The type of the initializer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if no variable can have the type of the initializer.
+    fn change_type(&self, name: &str, has_keyword: bool, span: Span, found: Type) -> Option<Suggestion> {
+        if !matches!(found, Type::Int | Type::Float | Type::String | Type::Boolean) {
+            return None
+        }
+
+        let start = self.type_start(has_keyword, span);
+
+        Some(Suggestion {
+            message: format!("Change the type of `{}` to `{}`", name, found),
+            span: self.reporter.file_span(Span::new(ByteIndex(start as u32), ByteIndex(self.skip_word(start) as u32))),
+            replacement: found.to_string(),
+        })
+    }
+
+    /// Suggests replacing `static` with `mut` in the declaration of a variable, so that it can
+    /// be assigned to.
+    fn replace_static(&self, name: &str, declaration: Span) -> Suggestion {
+        let start = declaration.start().to_usize();
+
+        Suggestion {
+            message: format!("Replace `static` with `mut` so that `{}` can be assigned to", name),
+            span: self.reporter.file_span(Span::new(ByteIndex(start as u32), ByteIndex(self.skip_word(start) as u32))),
+            replacement: "mut".to_string(),
+        }
+    }
+
+    /// Suggests adding `mut` to the declaration of a variable, so that it can be assigned to.
+    fn declare_mutable(&self, name: &str, declaration: Span) -> Suggestion {
+        Suggestion {
+            message: format!("Declare `{}` as mutable", name),
+            span: self.reporter.file_span(Span::new(declaration.start(), declaration.start())),
+            replacement: "mut ".to_string(),
+        }
+    }
+
+    /// Suggests the name a misspelt name was probably meant to be.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the candidates are, such as `variable`.
+    /// * `start` - The offset of the misspelt name.
+    /// * `candidates` - The names that exist where the name is used.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if no candidate is similar enough to the name.
+    fn misspelling<'n>(&self, kind: &str, start: usize, candidates: impl IntoIterator<Item = &'n str>) -> Option<Suggestion> {
+        let end = self.skip_word(start);
+        let candidate = closest(&self.input[start..end], candidates)?;
+
+        Some(Suggestion {
+            message: format!("A {} with a similar name exists: `{}`", kind, candidate),
+            span: self.reporter.file_span(Span::new(ByteIndex(start as u32), ByteIndex(end as u32))),
+            replacement: candidate.to_string(),
+        })
+    }
+
+    /// Suggests the variable in scope that a misspelt variable name starting at `start` was
+    /// probably meant to be.
+    fn misspelt_variable(&self, start: usize) -> Option<Suggestion> {
+        let variables = self.scopes.iter().flat_map(|scope| scope.keys().map(String::as_str));
+        self.misspelling("variable", start, variables)
+    }
+
+    /// Returns the offset of the type in a variable declaration, which follows the `static` or
+    /// `mut` the declaration starts with, if it has one.
+    fn type_start(&self, has_keyword: bool, declaration: Span) -> usize {
+        let mut start = declaration.start().to_usize();
+        if has_keyword {
+            start = self.skip_word(start);
+            start += self.input[start..].len() - self.input[start..].trim_start().len();
+        }
+
+        start
+    }
+
+    /// Returns the offset of the type of a parameter in a function declaration.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The offset of the function declaration.
+    /// * `index` - The index of the parameter.
+    fn param_type_start(&self, start: usize, index: usize) -> Option<usize> {
+        let open = start + self.input[start..].find('(')? + 1;
+        let close = open + self.input[open..].find(')')?;
+
+        let mut offset = open;
+        for param in self.input[open..close].split(',').take(index) {
+            offset += param.len() + 1;
+        }
+        let param = self.input[offset..close].split(',').next()?;
+
+        Some(offset + param.len() - param.trim_start().len())
+    }
+
+    /// Returns the offset after the word of identifier characters starting at `start`.
+    fn skip_word(&self, start: usize) -> usize {
+        let rest = &self.input[start..];
+        start + rest.find(|char: char| !char.is_ascii_alphanumeric() && char != '_').unwrap_or(rest.len())
+    }
+
+    fn lookup(&self, name: &str) -> Option<Type> {
+        self.lookup_binding(name).map(|binding| binding.var_type)
+    }
+
+    fn lookup_binding(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+    }
+
+    fn declare(&mut self, name: &str, binding: Binding, span: Span) {
+        let scope = self.scopes.last_mut()
+            .expect("The global scope is never popped");
+
+        if let Some(first) = scope.insert(name.to_string(), binding) {
+            let error = self.reporter.error(
+                format!("The variable `{}` is defined multiple times", name),
+                span,
+                Code::E004,
+            );
+            self.errors.push(error.secondary(first.span, "first defined here").emit());
+        }
+    }
+
+    /// Sets the levels the attributes of a function declaration ask for, reporting the
+    /// attributes that are not valid.
+    fn apply_attributes(&mut self, attributes: &[Attribute]) {
+        for attribute in attributes {
+            let Ok(level) = attribute.name.parse::<Level>() else {
+                let error = self.reporter.error(format!("Unknown attribute `@{}`", attribute.name), attribute.span, Code::E014)
+                    .note("expected one of `@allow`, `@warn`, `@deny` and `@forbid`");
+                self.errors.push(error.emit());
+                continue
+            };
+
+            for (arg, span) in &attribute.args {
+                match arg.parse() {
+                    Ok(code) => if let Err(error) = self.levels.set(code, level) {
+                        self.error(error.to_string(), *span, Code::E014);
+                    },
+                    Err(()) => self.error(format!("Unknown diagnostic code `{}`", arg), *span, Code::E014),
+                }
+            }
+        }
+    }
+
+    /// Reports a warning at the level its code has where it is found, which may turn it into
+    /// an error or leave it out.
+    fn lint(&mut self, message: String, span: Span, code: Code) {
+        let diagnostic = match self.levels.severity(code) {
+            None => return,
+            Some(Severity::Warning) => self.reporter.warning(message, span, code),
+            Some(_) => self.reporter.error(message, span, code),
+        };
+
+        self.errors.push(diagnostic.emit());
+    }
+
+    /// Returns whether any errors were found, rather than only warnings.
+    fn has_errors(&self) -> bool {
+        self.errors.iter().any(|(diagnostic, _)| diagnostic.severity >= Severity::Error)
+    }
+
+    fn error(&mut self, message: String, span: Span, code: Code) {
+        self.error_with_suggestions(message, span, code, Vec::new());
+    }
+
+    /// Reports an error whose suggestions are shown on the code they change.
+    fn error_with_suggestions(&mut self, message: String, span: Span, code: Code, suggestions: Vec<Suggestion>) {
+        let error = suggestions.into_iter()
+            .fold(self.reporter.error(message, span, code), |error, suggestion| error.suggestion(suggestion));
+        self.errors.push(error.emit());
+    }
+}
+
+fn mismatched_types(expected: Type, found: Type) -> String {
+    format!("Mismatched types, expected `{}` but found `{}`", expected, found)
+}
@@ -0,0 +1,126 @@
+use codespan::Span;
+use crate::{AstNode, BinaryOperator, Type, TypedValue, UnaryOperator};
+
+/// The reason an expression could not be evaluated at compile time.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvalError {
+    /// The expression reads a variable whose value is only known at run time
+    RuntimeValue {
+        name: String,
+        span: Span,
+    },
+    /// The operation has no defined result, such as a division by zero
+    Undefined {
+        op: BinaryOperator,
+        span: Span,
+    },
+}
+
+/// Evaluates a well typed expression at compile time.
+///
+/// # Arguments
+///
+/// * `node` - The expression to evaluate.
+/// * `constant` - Returns the compile-time value of a variable, or `None` if the variable
+///   is only known at run time.
+///
+/// # Returns
+///
+/// Returns the value of the expression, or the reason it cannot be evaluated.
+pub fn evaluate(node: &AstNode, constant: &dyn Fn(&str) -> Option<TypedValue>) -> Result<TypedValue, EvalError> {
+    match node {
+        AstNode::Value { value: TypedValue::ReferenceVal(name), span } => {
+            constant(name).ok_or_else(|| EvalError::RuntimeValue {
+                name: name.clone(),
+                span: *span,
+            })
+        }
+        AstNode::Value { value, .. } => Ok(value.clone()),
+        AstNode::UnaryOp { op, operand, .. } => {
+            let operand = evaluate(operand, constant)?;
+            Ok(unary(*op, &operand).expect("the checker only accepts well typed operands"))
+        }
+        AstNode::BinaryOp { op, lhs, rhs, span } => {
+            let lhs = evaluate(lhs, constant)?;
+            let rhs = evaluate(rhs, constant)?;
+            binary(*op, &lhs, &rhs).ok_or(EvalError::Undefined { op: *op, span: *span })
+        }
+        _ => unreachable!("only expressions can be evaluated"),
+    }
+}
+
+/// Applies a unary operator to a constant.
+///
+/// # Returns
+///
+/// Returns `None` if the operator is not defined for the operand.
+pub fn unary(op: UnaryOperator, operand: &TypedValue) -> Option<TypedValue> {
+    match (op, operand) {
+        (UnaryOperator::Neg, TypedValue::IntVal(value)) => Some(TypedValue::IntVal(value.wrapping_neg())),
+        (UnaryOperator::Neg, TypedValue::FloatVal(value)) => Some(TypedValue::FloatVal(-value)),
+        (UnaryOperator::Not, TypedValue::BoolVal(value)) => Some(TypedValue::BoolVal(!value)),
+        _ => None,
+    }
+}
+
+/// Applies a binary operator to two constants. Integer arithmetic wraps on overflow, and an
+/// `int` operand is widened when the other operand is a `float`.
+///
+/// # Returns
+///
+/// Returns `None` if the operator is not defined for the operands, or if the result is
+/// undefined, which is the case for integer division by zero and `i32::MIN / -1`.
+pub fn binary(op: BinaryOperator, lhs: &TypedValue, rhs: &TypedValue) -> Option<TypedValue> {
+    use BinaryOperator::*;
+    use TypedValue::*;
+
+    let value = match (lhs, rhs) {
+        (IntVal(lhs), IntVal(rhs)) => match op {
+            Add => IntVal(lhs.wrapping_add(*rhs)),
+            Sub => IntVal(lhs.wrapping_sub(*rhs)),
+            Mul => IntVal(lhs.wrapping_mul(*rhs)),
+            Div => IntVal(lhs.checked_div(*rhs)?),
+            _ => BoolVal(compare(op, lhs, rhs)?),
+        },
+        (FloatVal(lhs), FloatVal(rhs)) => match op {
+            Add => FloatVal(lhs + rhs),
+            Sub => FloatVal(lhs - rhs),
+            Mul => FloatVal(lhs * rhs),
+            Div => FloatVal(lhs / rhs),
+            _ => BoolVal(compare(op, lhs, rhs)?),
+        },
+        (IntVal(lhs), FloatVal(_)) => return binary(op, &FloatVal(*lhs as f32), rhs),
+        (FloatVal(_), IntVal(rhs)) => return binary(op, lhs, &FloatVal(*rhs as f32)),
+        (BoolVal(lhs), BoolVal(rhs)) => BoolVal(match op {
+            And => *lhs && *rhs,
+            Or => *lhs || *rhs,
+            Eq => lhs == rhs,
+            NotEq => lhs != rhs,
+            _ => return None,
+        }),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn compare<T: PartialOrd>(op: BinaryOperator, lhs: &T, rhs: &T) -> Option<bool> {
+    match op {
+        BinaryOperator::Eq => Some(lhs == rhs),
+        BinaryOperator::NotEq => Some(lhs != rhs),
+        BinaryOperator::Less => Some(lhs < rhs),
+        BinaryOperator::Greater => Some(lhs > rhs),
+        BinaryOperator::LessEq => Some(lhs <= rhs),
+        BinaryOperator::GreaterEq => Some(lhs >= rhs),
+        _ => None,
+    }
+}
+
+/// Converts a constant to the representation of a slot of type `var_type`, widening
+/// integers stored in a `float`.
+pub fn coerce(value: TypedValue, var_type: Type) -> TypedValue {
+    match value {
+        TypedValue::IntVal(value) if var_type == Type::Float => TypedValue::FloatVal(value as f32),
+        value => value,
+    }
+}
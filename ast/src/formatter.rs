@@ -0,0 +1,315 @@
+use codespan_reporting::diagnostic::Diagnostic;
+use common::errors::Reporting;
+use common::files::FileId;
+use crate::AstNode;
+use crate::lexer::{Lexer, Token, TokenType};
+use crate::parser::Parser;
+use crate::syntax::{SyntaxNode, TokenKind};
+
+/// The whitespace a level of indentation is made of.
+const INDENT: &str = "    ";
+
+/// A piece of the source between two tokens, which the parser skips.
+#[derive(Debug, PartialEq, Clone)]
+enum Trivia {
+    /// One or more line breaks
+    Newlines(usize),
+    /// A `//` comment, without the line break that ends it
+    Comment(String),
+}
+
+/// Struct representing the formatter, which re-prints a document in the canonical style.
+///
+/// Every statement is put on its own line, function bodies are indented by four spaces with
+/// the opening brace on the line of the declaration, and tokens are separated by the spacing
+/// their `TokenType` calls for. Comments are kept, as is a single blank line wherever the
+/// input had one or more. Only whitespace is changed, so the tokens and therefore the AST of
+/// the document stay the same, and formatting formatted code changes nothing.
+pub struct Formatter<'a> {
+    name: &'a str,
+    input: &'a str,
+    /// Whether diagnostics are only returned, without emitting them
+    quiet: bool,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(name: &'a str, input: &'a str) -> Self {
+        Formatter { name, input, quiet: false }
+    }
+
+    /// Stops the formatter from emitting diagnostics, they are only returned.
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Formats the input.
+    ///
+    /// # Returns
+    ///
+    /// Returns the formatted document, or the emitted error diagnostics if the input cannot be
+    /// lexed or parsed, in which case it is not safe to move its tokens around.
+    pub fn format(&self) -> Result<String, Vec<Diagnostic<FileId>>> {
+        let mut lexer = Lexer::new(self.name, self.input);
+        let mut reporter = Reporting::new(self.name, self.input);
+        if self.quiet {
+            lexer = lexer.quiet();
+            reporter = reporter.quiet();
+        }
+
+        let tokens = lexer.try_tokenize()?;
+        let mut parser = Parser::new(tokens.clone());
+        let document = parser.parse();
+
+        let errors = parser.report_errors(&reporter);
+        if !errors.is_empty() {
+            return Err(errors)
+        }
+
+        let body = match document {
+            AstNode::Document { body } => match *body {
+                AstNode::Block { body } => body,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+
+        let mut printer = Printer {
+            input: self.input,
+            tokens: &tokens,
+            trivia: gaps(self.input, &parser.syntax_tree(self.input)),
+            out: String::new(),
+            depth: 0,
+        };
+        printer.print_block(&body);
+        printer.break_line(tokens.len(), true);
+
+        if !printer.out.is_empty() {
+            printer.out.push('\n');
+        }
+
+        Ok(printer.out)
+    }
+}
+
+struct Printer<'a> {
+    input: &'a str,
+    tokens: &'a [Token],
+    /// The trivia in front of every token, and at the end of the input
+    trivia: Vec<Vec<Trivia>>,
+    out: String,
+    /// The indentation level of the current line
+    depth: usize,
+}
+
+impl Printer<'_> {
+    /// Prints statements on lines of their own, keeping single blank lines between them.
+    fn print_block(&mut self, body: &[AstNode]) {
+        for (position, node) in body.iter().enumerate() {
+            let Some(span) = node.span() else {
+                continue
+            };
+            let first = self.tokens.partition_point(|token| token.span.start() < span.start());
+            let last = self.tokens.partition_point(|token| token.span.start() < span.end()) - 1;
+
+            // attributes are in front of the span of a function, on lines of their own
+            let attributes = match node {
+                AstNode::FuncDeclaration { attributes, .. } => attributes.as_slice(),
+                _ => &[],
+            };
+            for (index, attribute) in attributes.iter().enumerate() {
+                let start = self.tokens.partition_point(|token| token.span.start() < attribute.span.start());
+                let end = self.tokens.partition_point(|token| token.span.start() < attribute.span.end()) - 1;
+
+                self.break_line(start, index == 0 && position > 0);
+
+                // the names of codes are printed as they are written, without spaces around
+                // the minus signs in them, unless comments within the attribute keep the
+                // tokens on lines of their own
+                let has_comments = (start + 1..=end)
+                    .any(|index| self.trivia(index).iter().any(|item| matches!(item, Trivia::Comment(_))));
+                if has_comments {
+                    self.print_inline(start, end);
+                } else {
+                    let args: Vec<&str> = attribute.args.iter().map(|(arg, _)| arg.as_str()).collect();
+                    self.out.push_str(&format!("@{}({})", attribute.name, args.join(", ")));
+                }
+            }
+            self.break_line(first, attributes.is_empty() && position > 0);
+
+            match node {
+                AstNode::FuncDeclaration { body, .. } => {
+                    let open = (first..last)
+                        .find(|index| self.tokens[*index].token_type == TokenType::LBrace)
+                        .expect("function bodies start with a brace");
+                    self.print_inline(first, open);
+
+                    let statements = match body.as_ref() {
+                        AstNode::Block { body } => body.as_slice(),
+                        _ => &[],
+                    };
+
+                    // an empty body without comments stays on the line of the declaration
+                    if statements.is_empty() && self.trivia(last).iter().all(|item| !matches!(item, Trivia::Comment(_))) {
+                        self.out.push('}');
+                        continue
+                    }
+
+                    self.depth += 1;
+                    self.print_block(statements);
+                    self.break_line(last, false);
+                    self.depth -= 1;
+
+                    self.out.push('}');
+                }
+                _ => self.print_inline(first, last),
+            }
+        }
+    }
+
+    /// Prints the tokens from `first` to `last` on the current line. A comment between them
+    /// ends the line, and the rest of the tokens continue on the next line, indented by one
+    /// more level.
+    fn print_inline(&mut self, first: usize, last: usize) {
+        for index in first..=last {
+            if index > first {
+                let has_comments = self.trivia(index).iter().any(|item| matches!(item, Trivia::Comment(_)));
+
+                if has_comments {
+                    self.depth += 1;
+                    self.break_line(index, false);
+                    self.depth -= 1;
+                } else if self.space_before(index) {
+                    self.out.push(' ');
+                }
+            }
+
+            let span = self.tokens[index].span;
+            self.out.push_str(&self.input[span.start().to_usize()..span.end().to_usize()]);
+        }
+    }
+
+    /// Ends the current line and starts the line of the token at `index`, printing the comments
+    /// in front of the token on lines of their own. A comment that follows the previous token
+    /// on the same line stays there. A `}` is indented one level less than the comments in
+    /// front of it, which belong to the block it closes.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the token, or the number of tokens to end the document.
+    /// * `keep_blank` - Whether a blank line in front of the first of the comments and the token
+    ///   is kept. Blank lines are never kept in front of a `}`.
+    fn break_line(&mut self, index: usize, keep_blank: bool) {
+        let mut blank = false;
+        let mut keep_blank = keep_blank;
+
+        for (position, item) in self.trivia[index].clone().into_iter().enumerate() {
+            match item {
+                Trivia::Comment(comment) if position == 0 && !self.out.is_empty() => {
+                    self.out.push(' ');
+                    self.out.push_str(&comment);
+                }
+                Trivia::Comment(comment) => {
+                    self.start_line(blank && keep_blank, self.depth);
+                    self.out.push_str(&comment);
+                    blank = false;
+                    keep_blank = true;
+                }
+                Trivia::Newlines(count) => blank = count > 1,
+            }
+        }
+
+        if let Some(token) = self.tokens.get(index) {
+            if token.token_type == TokenType::RBrace {
+                self.start_line(false, self.depth - 1);
+            } else {
+                self.start_line(blank && keep_blank, self.depth);
+            }
+        }
+    }
+
+    fn start_line(&mut self, blank: bool, depth: usize) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+            if blank {
+                self.out.push('\n');
+            }
+        }
+
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// Returns the whitespace and comments in front of the token at `index`, or at the end of
+    /// the input if `index` is the number of tokens.
+    fn trivia(&self, index: usize) -> &[Trivia] {
+        &self.trivia[index]
+    }
+
+    /// Returns whether a space separates the token at `index` from the token before it.
+    fn space_before(&self, index: usize) -> bool {
+        let previous = &self.tokens[index - 1].token_type;
+        let before_previous = index.checked_sub(2).map(|before| &self.tokens[before].token_type);
+
+        if is_unary(before_previous, previous) {
+            return false
+        }
+
+        !matches!(
+            (previous, &self.tokens[index].token_type),
+            (_, TokenType::Comma | TokenType::RParen | TokenType::RBracket | TokenType::Semicolon | TokenType::Colon | TokenType::Dot)
+                | (TokenType::LParen | TokenType::LBracket | TokenType::Dot | TokenType::At, _)
+                | (TokenType::Identifier(_), TokenType::LParen | TokenType::LBracket)
+        )
+    }
+}
+
+/// Groups the trivia of a concrete syntax tree by the token it is in front of.
+///
+/// # Returns
+///
+/// Returns the trivia in front of every token, followed by the trivia at the end of the input.
+fn gaps(input: &str, tree: &SyntaxNode) -> Vec<Vec<Trivia>> {
+    let mut gaps = vec![Vec::new()];
+
+    for token in tree.tokens() {
+        let gap = gaps.last_mut().expect("there is always a gap");
+        match token.kind {
+            TokenKind::Token(_) => gaps.push(Vec::new()),
+            TokenKind::Whitespace => {
+                let newlines = token.text(input).matches('\n').count();
+                if newlines > 0 {
+                    gap.push(Trivia::Newlines(newlines));
+                }
+            }
+            TokenKind::Comment => gap.push(Trivia::Comment(token.text(input).trim_end().to_string())),
+            // the input cannot be formatted if it did not lex
+            TokenKind::Unknown => {}
+        }
+    }
+
+    gaps
+}
+
+/// Returns whether an operator is a prefix operator, which is the case for `!`, and for `-`
+/// when it does not follow an operand.
+fn is_unary(previous: Option<&TokenType>, operator: &TokenType) -> bool {
+    let follows_operand = previous.is_some_and(|previous| matches!(
+        previous,
+        TokenType::Identifier(_)
+            | TokenType::IntLiteral(_)
+            | TokenType::FloatLiteral(_)
+            | TokenType::BoolLiteral(_)
+            | TokenType::StringLiteral(_)
+            | TokenType::NullLiteral
+            | TokenType::RParen
+            | TokenType::RBracket
+    ));
+
+    match operator {
+        TokenType::Not => true,
+        TokenType::Minus => !follows_operand,
+        _ => false,
+    }
+}
@@ -0,0 +1,339 @@
+use std::cell::OnceCell;
+use std::ops::Range;
+use std::rc::Rc;
+use codespan::Span;
+use crate::AstNode;
+use crate::lexer::{lex_from, Token};
+use crate::parser::{Parser, SyntaxError};
+
+/// A part of a document that is lexed and parsed on its own: a top-level function or statement
+/// together with the trivia in front of it, or the trivia at the end of the document.
+///
+/// Spans within an item are relative to its start, so that an edit in front of the item only
+/// has to move its start.
+#[derive(Debug, Clone)]
+struct Item {
+    /// The byte offset the item starts at, which is where the last token of the previous item
+    /// ends
+    start: usize,
+    tokens: Vec<Token>,
+    /// The parsed function or statement, or `None` if it could not be parsed
+    node: Option<AstNode>,
+    syntax_errors: Vec<SyntaxError>,
+    /// The spans of the text that could not be lexed
+    lex_errors: Vec<Span>,
+}
+
+/// What was lexed and parsed again after an edit.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Reparse {
+    /// The number of tokens that were lexed again
+    pub relexed: usize,
+    /// The indices of the top-level items that were parsed again, which replaced the items
+    /// affected by the edit. The last item holds the trivia at the end of the document.
+    pub items: Range<usize>,
+}
+
+/// Struct representing a document that is kept lexed and parsed while it is being edited.
+///
+/// A document is a sequence of top-level functions and statements, which are parsed
+/// independently of each other. An edit relexes the text from the item it starts in up to the
+/// first token boundary after it that the old tokens share, and reparses from that item until
+/// the parser reaches the start of an item after the edit. The items after that are reused, so
+/// editing a function body only reparses that function, however long the document is.
+///
+/// The tokens and AST of the whole document are assembled from the items when they are first
+/// asked for after an edit, and shared by every caller until the next edit.
+pub struct Document {
+    text: String,
+    items: Vec<Item>,
+    tokens: OnceCell<Rc<Vec<Token>>>,
+    ast: OnceCell<Rc<AstNode>>,
+}
+
+impl Document {
+    /// Lexes and parses a whole document.
+    pub fn new(text: &str) -> Self {
+        let mut document = Document {
+            text: text.to_string(),
+            items: Vec::new(),
+            tokens: OnceCell::new(),
+            ast: OnceCell::new(),
+        };
+        document.reparse(0, 0, 0);
+        document
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces a byte range of the text, lexing and parsing only what the edit affects.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The byte range that is replaced, which must be within the text and on
+    ///   character boundaries.
+    /// * `replacement` - The text the range is replaced by.
+    ///
+    /// # Returns
+    ///
+    /// Returns what was lexed and parsed again.
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Reparse {
+        self.text.replace_range(range.clone(), replacement);
+        self.tokens.take();
+        self.ast.take();
+        let delta = replacement.len() as isize - range.len() as isize;
+
+        // the item containing the edit, where an edit right after an item's last token belongs
+        // to that item, since the token may be extended by it
+        let containing = self.items.partition_point(|item| item.start < range.start).saturating_sub(1);
+        let mut first = containing;
+
+        // the parser looks at up to two tokens after an item to decide where it ends, so items
+        // that end less than two tokens in front of the edit are parsed again as well
+        let item = &self.items[containing];
+        let mut lookahead = item.tokens
+            .partition_point(|token| item.start + token.span.end().to_usize() < range.start);
+        while first > 0 && lookahead < 2 {
+            first -= 1;
+            lookahead += self.items[first].tokens.len();
+        }
+
+        self.reparse(first, range.end, delta)
+    }
+
+    /// Relexes and reparses the items from `first` on, until lexing and parsing reach an item
+    /// after the edit, which is reused along with the items after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The index of the first item that is parsed again.
+    /// * `old_end` - The end of the edited range, before the edit.
+    /// * `delta` - How much the text after the edit moved.
+    fn reparse(&mut self, first: usize, old_end: usize, delta: isize) -> Reparse {
+        let text = self.text.as_str();
+        let start = self.items.get(first).map_or(0, |item| item.start);
+        let moved = |item: &Item| item.start.wrapping_add_signed(delta);
+
+        // relex until a token ends where an item after the edit starts, from where on the
+        // text and therefore the tokens are the same as before
+        let mut reused = first + 1;
+        let mut tokens = Vec::new();
+        let mut lex_errors = Vec::new();
+        let mut end = start;
+
+        loop {
+            while reused < self.items.len() && (self.items[reused].start < old_end || moved(&self.items[reused]) < end) {
+                reused += 1;
+            }
+
+            // the item is only reused if it starts after a token, so that the text in front of
+            // it belongs to the items that are parsed again
+            let after_token = end == start || tokens.last().is_some_and(|token: &Token| token.span.end().to_usize() == end);
+            if reused < self.items.len() && moved(&self.items[reused]) == end && after_token {
+                break
+            }
+
+            match lex_from(text, end).next() {
+                Some(Ok(token)) => {
+                    end = token.span.end().to_usize();
+                    tokens.push(token);
+                }
+                Some(Err(span)) => {
+                    end = span.end().to_usize();
+                    lex_errors.push(span);
+                }
+                None => {
+                    reused = self.items.len();
+                    break
+                }
+            }
+        }
+        let relexed = tokens.len();
+
+        // reparse until an item ends where an item after the edit starts. The tokens of the
+        // items after the edit are given to the parser when it needs to look at them, and the
+        // items whose tokens are parsed as part of a new item are replaced
+        let mut parser = Parser::new(tokens.clone());
+        // the index of the first token of the first item that has not been replaced
+        let mut boundary = tokens.len();
+        let mut replaced = reused;
+        // the first item whose tokens have not been given to the parser
+        let mut lookahead = reused;
+        let mut items = Vec::new();
+        let mut item_start = start;
+
+        while parser.position() < boundary {
+            let pos = parser.position();
+            let errors = parser.error_count();
+            let node = parser.parse_item();
+            let end = parser.position();
+
+            // the parser may have looked at the token after the next one to decide where the
+            // item ends, which has to be there unless the document ends
+            if tokens.len() < end + 2 && lookahead < self.items.len() {
+                parser.rewind(pos, errors);
+
+                let item = &self.items[lookahead];
+                let shifted: Vec<Token> = item.tokens.iter()
+                    .map(|token| shift_token(token, moved(item) as isize))
+                    .collect();
+                parser.push_tokens(shifted.iter().cloned());
+                tokens.extend(shifted);
+                lookahead += 1;
+                continue
+            }
+
+            while end > boundary {
+                let item = &self.items[replaced];
+                lex_errors.extend(item.lex_errors.iter().map(|span| shift_span(*span, moved(item) as isize)));
+                boundary += item.tokens.len();
+                replaced += 1;
+            }
+
+            let relative = -(item_start as isize);
+            items.push(Item {
+                start: item_start,
+                tokens: tokens[pos..end].iter().map(|token| shift_token(token, relative)).collect(),
+                node: node.map(|mut node| {
+                    shift_node(&mut node, relative);
+                    node
+                }),
+                syntax_errors: parser.errors()[errors..].iter()
+                    .map(|error| shift_error(error, relative))
+                    .collect(),
+                lex_errors: Vec::new(),
+            });
+            item_start = tokens[end - 1].span.end().to_usize();
+        }
+
+        // the trivia at the end of the document needs an item of its own if it was relexed
+        if replaced == self.items.len() {
+            items.push(Item {
+                start: item_start,
+                tokens: Vec::new(),
+                node: None,
+                syntax_errors: Vec::new(),
+                lex_errors: Vec::new(),
+            });
+        }
+
+        // the text that could not be lexed belongs to the item it is in
+        for span in lex_errors {
+            let index = items.partition_point(|item| item.start <= span.start().to_usize()) - 1;
+            let item = &mut items[index];
+            item.lex_errors.push(shift_span(span, -(item.start as isize)));
+        }
+
+        let parsed = items.len();
+        self.items.splice(first..replaced, items);
+        for item in &mut self.items[first + parsed..] {
+            item.start = item.start.wrapping_add_signed(delta);
+        }
+
+        Reparse { relexed, items: first..first + parsed }
+    }
+
+    /// Returns the tokens of the document.
+    pub fn tokens(&self) -> Rc<Vec<Token>> {
+        self.tokens.get_or_init(|| {
+            let tokens = self.items.iter()
+                .flat_map(|item| item.tokens.iter().map(|token| shift_token(token, item.start as isize)))
+                .collect();
+            Rc::new(tokens)
+        }).clone()
+    }
+
+    /// Returns the AST of the document, as `Parser::parse` would.
+    pub fn ast(&self) -> Rc<AstNode> {
+        self.ast.get_or_init(|| {
+            let body = self.items.iter()
+                .filter_map(|item| {
+                    let mut node = item.node.clone()?;
+                    shift_node(&mut node, item.start as isize);
+                    Some(node)
+                })
+                .collect();
+
+            Rc::new(AstNode::Document {
+                body: Box::new(AstNode::Block { body }),
+            })
+        }).clone()
+    }
+
+    /// Returns the syntax errors of the document, as `Parser::errors` would.
+    pub fn syntax_errors(&self) -> Vec<SyntaxError> {
+        self.items.iter()
+            .flat_map(|item| item.syntax_errors.iter().map(|error| shift_error(error, item.start as isize)))
+            .collect()
+    }
+
+    /// Returns the spans of the text that could not be lexed.
+    pub fn lex_errors(&self) -> Vec<Span> {
+        self.items.iter()
+            .flat_map(|item| item.lex_errors.iter().map(|span| shift_span(*span, item.start as isize)))
+            .collect()
+    }
+}
+
+fn shift_error(error: &SyntaxError, delta: isize) -> SyntaxError {
+    SyntaxError {
+        message: error.message.clone(),
+        span: shift_span(error.span, delta),
+        keyword: error.keyword.map(|(span, keyword)| (shift_span(span, delta), keyword)),
+        code: error.code,
+    }
+}
+
+fn shift_span(span: Span, delta: isize) -> Span {
+    Span::new(
+        span.start().to_usize().wrapping_add_signed(delta) as u32,
+        span.end().to_usize().wrapping_add_signed(delta) as u32,
+    )
+}
+
+fn shift_token(token: &Token, delta: isize) -> Token {
+    Token { token_type: token.token_type.clone(), span: shift_span(token.span, delta) }
+}
+
+/// Moves the spans of a node and the nodes within it.
+fn shift_node(node: &mut AstNode, delta: isize) {
+    match node {
+        AstNode::Empty => {}
+        AstNode::Document { body } => shift_node(body, delta),
+        AstNode::Block { body } => body.iter_mut().for_each(|node| shift_node(node, delta)),
+        AstNode::FunctionCall { param_list, span, .. } => {
+            param_list.iter_mut().for_each(|node| shift_node(node, delta));
+            *span = shift_span(*span, delta);
+        }
+        AstNode::FuncDeclaration { attributes, body, span, .. } => {
+            for attribute in attributes {
+                attribute.span = shift_span(attribute.span, delta);
+                attribute.args.iter_mut().for_each(|(_, span)| *span = shift_span(*span, delta));
+            }
+            shift_node(body, delta);
+            *span = shift_span(*span, delta);
+        }
+        AstNode::VarDeclaration { value, span, .. } => {
+            shift_node(value, delta);
+            *span = shift_span(*span, delta);
+        }
+        AstNode::VarAssignment { new_value, span, .. } => {
+            shift_node(new_value, delta);
+            *span = shift_span(*span, delta);
+        }
+        AstNode::BinaryOp { lhs, rhs, span, .. } => {
+            shift_node(lhs, delta);
+            shift_node(rhs, delta);
+            *span = shift_span(*span, delta);
+        }
+        AstNode::UnaryOp { operand, span, .. } => {
+            shift_node(operand, delta);
+            *span = shift_span(*span, delta);
+        }
+        AstNode::Identifier { span, .. } | AstNode::Value { span, .. } | AstNode::Return { span } => {
+            *span = shift_span(*span, delta)
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::rc::Rc;
+use codespan::Span;
+use crate::{AstNode, Type, TypedValue};
+use crate::checker::PRINT;
+use crate::constant::{binary, coerce, unary};
+
+/// The deepest the call stack can grow before the interpreter gives up. Z++ has no
+/// conditionals yet, so any recursive function recurses forever.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// An error that stopped the interpreter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// Formats a value the way `print` writes it.
+pub fn format_value(value: &TypedValue) -> String {
+    match value {
+        TypedValue::StringVal(value) => value.clone(),
+        TypedValue::IntVal(value) => value.to_string(),
+        TypedValue::FloatVal(value) => value.to_string(),
+        TypedValue::BoolVal(value) => value.to_string(),
+        TypedValue::NullVal => "null".to_string(),
+        TypedValue::ReferenceVal(_) => unreachable!("references are always resolved"),
+    }
+}
+
+struct Function {
+    params: Vec<(String, Type)>,
+    body: Vec<AstNode>,
+}
+
+/// A variable together with the type it was declared with, which values assigned to it are
+/// converted to.
+type Variable = (Type, TypedValue);
+
+/// The functions and global variables of an interpreter at some point, which it can be reset to.
+pub struct Snapshot {
+    functions: HashMap<String, Rc<Function>>,
+    globals: HashMap<String, Variable>,
+}
+
+/// Struct representing a tree-walking interpreter that executes checked AST nodes directly.
+///
+/// Its state persists between calls to `run`, so a document can be executed piece by piece.
+pub struct Interpreter<W: Write> {
+    /// Where `print` writes to
+    out: W,
+    functions: HashMap<String, Rc<Function>>,
+    globals: HashMap<String, Variable>,
+    /// The local variables of every active call, the innermost call is last
+    frames: Vec<HashMap<String, Variable>>,
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn new(out: W) -> Self {
+        Interpreter {
+            out,
+            functions: HashMap::new(),
+            globals: HashMap::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Returns the writer `print` writes to.
+    pub fn output(&self) -> &W {
+        &self.out
+    }
+
+    /// Returns the current value of a global variable.
+    pub fn global(&self, name: &str) -> Option<&TypedValue> {
+        self.globals.get(name).map(|(_, value)| value)
+    }
+
+    /// Captures the current functions and global variables, so that the effects of a body
+    /// that failed half way through can be undone with `restore`.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            functions: self.functions.clone(),
+            globals: self.globals.clone(),
+        }
+    }
+
+    /// Resets the functions and global variables to a snapshot. Output that was already
+    /// written is not taken back.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.functions = snapshot.functions;
+        self.globals = snapshot.globals;
+    }
+
+    /// Declares the functions and runs the top-level statements of a checked document.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The nodes of the document's `AstNode::Block`, which must have passed the
+    ///   checker together with every body that was run before.
+    ///
+    /// # Returns
+    ///
+    /// Returns the error that stopped execution, if any.
+    pub fn run(&mut self, body: &[AstNode]) -> Result<(), RuntimeError> {
+        for node in body {
+            if let AstNode::FuncDeclaration { name, params, body, .. } = node {
+                let body = match body.as_ref() {
+                    AstNode::Block { body } => body.clone(),
+                    _ => Vec::new(),
+                };
+
+                self.functions.insert(name.clone(), Rc::new(Function {
+                    params: params.clone(),
+                    body,
+                }));
+            }
+        }
+
+        for node in body {
+            self.execute(node)?;
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, node: &AstNode) -> Result<(), RuntimeError> {
+        match node {
+            AstNode::VarDeclaration { name, var_type, value, .. } => {
+                let value = coerce(self.evaluate(value)?, *var_type);
+                let scope = self.frames.last_mut().unwrap_or(&mut self.globals);
+                scope.insert(name.clone(), (*var_type, value));
+            }
+            AstNode::VarAssignment { name, new_value, .. } => {
+                let value = self.evaluate(new_value)?;
+                let (var_type, slot) = self.frames.last_mut()
+                    .and_then(|frame| frame.get_mut(name))
+                    .or_else(|| self.globals.get_mut(name))
+                    .expect("the checker only accepts declared variables");
+
+                *slot = coerce(value, *var_type);
+            }
+            AstNode::FunctionCall { name, param_list, .. } if name == PRINT => {
+                let value = self.evaluate(&param_list[0])?;
+                // like the compiled targets, a closed output does not stop the program
+                let _ = writeln!(self.out, "{}", format_value(&value));
+            }
+            AstNode::FunctionCall { name, param_list, span } => {
+                let function = self.functions[name].clone();
+
+                if self.frames.len() >= MAX_CALL_DEPTH {
+                    return Err(RuntimeError {
+                        message: format!("Calling `{}` exceeds the maximum call depth of {}", name, MAX_CALL_DEPTH),
+                        span: *span,
+                    })
+                }
+
+                let mut frame = HashMap::new();
+                for ((param, param_type), arg) in function.params.iter().zip(param_list) {
+                    let value = coerce(self.evaluate(arg)?, *param_type);
+                    frame.insert(param.clone(), (*param_type, value));
+                }
+
+                self.frames.push(frame);
+                // without conditionals, a `return` is always a statement of the body itself
+                let result = function.body.iter()
+                    .take_while(|node| !matches!(node, AstNode::Return { .. }))
+                    .try_for_each(|node| self.execute(node));
+                self.frames.pop();
+
+                result?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a checked expression in the scope of the innermost call, which is the
+    /// global scope when no function is running.
+    pub fn evaluate(&mut self, node: &AstNode) -> Result<TypedValue, RuntimeError> {
+        match node {
+            AstNode::Value { value: TypedValue::ReferenceVal(name), .. } => {
+                let (_, value) = self.frames.last()
+                    .and_then(|frame| frame.get(name))
+                    .or_else(|| self.globals.get(name))
+                    .expect("the checker only accepts declared variables");
+
+                Ok(value.clone())
+            }
+            AstNode::Value { value, .. } => Ok(value.clone()),
+            AstNode::UnaryOp { op, operand, .. } => {
+                let operand = self.evaluate(operand)?;
+                Ok(unary(*op, &operand).expect("the checker only accepts well typed operands"))
+            }
+            AstNode::BinaryOp { op, lhs, rhs, span } => {
+                let lhs = self.evaluate(lhs)?;
+                let rhs = self.evaluate(rhs)?;
+
+                binary(*op, &lhs, &rhs).ok_or_else(|| RuntimeError {
+                    message: format!("The result of `{}` is undefined, it overflows or divides by zero", op),
+                    span: *span,
+                })
+            }
+            _ => unreachable!("only expressions can be evaluated"),
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use std::process::exit;
 use codespan::{ByteIndex, Span};
+use codespan_reporting::diagnostic::Diagnostic;
 use logos::Logos;
-use common::errors::{create_span, Reporting};
+use common::codes::Code;
+use common::errors::Reporting;
+use common::files::FileId;
 
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(skip r"\s+")]
+#[logos(skip r"//[^\n]*")]
 pub enum TokenType {
     // Keywords
     #[token("func")]
@@ -19,10 +23,18 @@ pub enum TokenType {
     For,
     #[token("while")]
     While,
+    #[token("static")]
+    Static,
+    #[token("mut")]
+    Mut,
 
     // Literals
-    #[regex("[0-9]+(\\.[0-9]+)?", |lex| lex.slice().parse::<f32>().expect("Unknown error"))]
-    IntLiteral(f32),
+    // literals too large for an `i64` saturate, the parser rejects everything out of the range
+    // of an `int` anyway
+    #[regex("[0-9]+", |lex| lex.slice().parse::<i64>().unwrap_or(i64::MAX))]
+    IntLiteral(i64),
+    #[regex("[0-9]+\\.[0-9]+", |lex| lex.slice().parse::<f32>().expect("Unknown error"))]
+    FloatLiteral(f32),
     #[regex("true|false", |lex| lex.slice() == "true")]
     BoolLiteral(bool),
     #[regex(r#""[^"\\\r\n]*""#, |lex| lex.slice()[1..lex.slice().len()-1].to_string())]
@@ -85,7 +97,34 @@ pub enum TokenType {
     At,
 }
 
-#[derive(Debug, Clone)]
+/// Lexes the input from a byte offset on, which must not be within a token or comment.
+///
+/// # Returns
+///
+/// Returns the tokens, with spans relative to the start of the input, and the spans of the
+/// text that cannot be lexed as errors.
+pub(crate) fn lex_from(input: &str, offset: usize) -> impl Iterator<Item = Result<Token, Span>> + '_ {
+    let mut lexer = TokenType::lexer(&input[offset..]);
+
+    std::iter::from_fn(move || {
+        let result = lexer.next()?;
+        let span = lexer.span();
+        let span = Span::new(
+            ByteIndex::from((offset + span.start) as u32), // starting character position
+            ByteIndex::from((offset + span.end) as u32), // ending character position
+        );
+
+        Some(match result {
+            Ok(token_type) => Ok(Token { token_type, span }),
+            Err(_) => Err(span),
+        })
+    })
+}
+
+/// The words `TokenType` reserves as keywords or literals, which cannot be used as names.
+pub const KEYWORDS: [&str; 11] = ["func", "return", "if", "else", "for", "while", "static", "mut", "true", "false", "null"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
     pub span: Span,
@@ -103,40 +142,128 @@ impl<'a> Lexer<'a> {
             reporter: Reporting::new(name, input)
         }
     }
+    /// Stops the lexer from emitting diagnostics, they are only returned.
+    pub fn quiet(mut self) -> Self {
+        self.reporter = self.reporter.quiet();
+        self
+    }
+
+    /// Reports through the given instance instead of one of its own, such as one for a file of
+    /// a multi-file database.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - The `Reporting` instance of the lexed input.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Lexer` instance.
+    pub fn with_reporting(mut self, reporter: Reporting<'a>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Splits the input into tokens, exiting the process if it contains a token that cannot
+    /// be lexed.
     pub fn tokenize(&self) -> Vec<Token> {
+        match self.try_tokenize() {
+            Ok(tokens) => tokens,
+            Err(_) => exit(1),
+        }
+    }
+
+    /// Splits the input into tokens, emitting a diagnostic for every token that cannot be
+    /// lexed.
+    ///
+    /// # Returns
+    ///
+    /// Returns the tokens, or the emitted error diagnostics.
+    pub fn try_tokenize(&self) -> Result<Vec<Token>, Vec<Diagnostic<FileId>>> {
+        let (tokens, errors) = self.tokenize_with_errors();
+
+        if !errors.is_empty() {
+            return Err(errors)
+        }
+
+        Ok(tokens)
+    }
+
+    /// Splits the input into tokens, emitting a diagnostic for every token that cannot be
+    /// lexed and skipping over it, so that the rest of the input can still be parsed.
+    ///
+    /// # Returns
+    ///
+    /// Returns the tokens that could be lexed, together with the emitted error diagnostics.
+    pub fn tokenize_with_errors(&self) -> (Vec<Token>, Vec<Diagnostic<FileId>>) {
         let mut tokens = Vec::new();
         let mut errors = Vec::new();
-        let mut lexer = TokenType::lexer(self.input);
-
-        loop {
-            if let Some(res) = lexer.next() {
-                if let Ok(token) = res {
-                    tokens.push(Token {
-                        token_type: token,
-                        span: Span::new(
-                            ByteIndex::from(lexer.span().start as u32), // starting character position
-                            ByteIndex::from(lexer.span().end as u32), // ending character position
-                        )
-                    });
-                } else if let Err(_) = res {
-                    errors.push(self.reporter.emit_error(
-                        "An error occurred while lexing".to_string(),
-                        create_span(lexer.span()),
-                        vec![
-                            "You might be running an outdated version of the interpreter!".to_string()
-                        ],
-                        "E001".to_string()
-                    ))
-                }
-            } else {
-                break
+
+        for result in lex_from(self.input, 0) {
+            match result {
+                Ok(token) => tokens.push(token),
+                Err(span) => errors.push(self.report_error(span)),
             }
         }
 
-        if errors.len() > 0 {
-            exit(1)
+        (tokens, errors)
+    }
+
+    /// Emits the diagnostic for text that cannot be lexed.
+    pub fn report_error(&self, span: Span) -> Diagnostic<FileId> {
+        self.reporter.emit_error(
+            "An error occurred while lexing".to_string(),
+            span,
+            vec![
+                "You might be running an outdated version of the interpreter!".to_string()
+            ],
+            Code::E001
+        )
+    }
+
+    /// Returns how many `{` and `(` in the input are not closed yet, ignoring anything that
+    /// cannot be lexed. Nothing is reported, so this can be used on incomplete input.
+    pub fn open_delimiters(&self) -> usize {
+        let mut open: usize = 0;
+
+        for token in TokenType::lexer(self.input).flatten() {
+            match token {
+                TokenType::LBrace | TokenType::LParen => open += 1,
+                TokenType::RBrace | TokenType::RParen => open = open.saturating_sub(1),
+                _ => {}
+            }
         }
 
-        tokens
+        open
     }
+}
+
+/// Returns the doc comment written directly above the line that contains `offset`.
+///
+/// A doc comment is a run of lines starting with `///`, the marker and a single space after it
+/// are removed from every line.
+///
+/// # Arguments
+///
+/// * `input` - The input the offset refers to.
+/// * `offset` - A byte offset on the first line of a declaration.
+///
+/// # Returns
+///
+/// Returns the lines of the doc comment, or `None` if the declaration has none.
+pub fn doc_comment(input: &str, offset: usize) -> Option<String> {
+    let line_start = input.get(..offset)?.rfind('\n').map_or(0, |newline| newline + 1);
+
+    let mut lines: Vec<&str> = input[..line_start]
+        .lines()
+        .rev()
+        .map_while(|line| line.trim_start().strip_prefix("///"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+        .collect();
+
+    if lines.is_empty() {
+        return None
+    }
+
+    lines.reverse();
+    Some(lines.join("\n"))
 }
\ No newline at end of file
@@ -1,9 +1,18 @@
+pub mod checker;
+pub mod constant;
+pub mod formatter;
+pub mod incremental;
+pub mod interpreter;
 pub mod lexer;
+pub mod lints;
 pub mod parser;
+pub mod resolver;
+pub mod syntax;
 
-use std::collections::HashMap;
+use std::fmt;
+use codespan::Span;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Type {
     Reference,
     Void,
@@ -14,7 +23,23 @@ pub enum Type {
     Null,
 }
 
-#[derive(Debug, PartialEq)]
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Reference => "reference",
+            Type::Void => "void",
+            Type::String => "String",
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Boolean => "bool",
+            Type::Null => "null",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum TypedValue {
     ReferenceVal(String),
     StringVal(String),
@@ -24,10 +49,94 @@ pub enum TypedValue {
     NullVal,
 }
 
+/// An operator that combines two operands.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    NotEq,
+    Less,
+    Greater,
+    LessEq,
+    GreaterEq,
+    And,
+    Or,
+}
+
+impl BinaryOperator {
+    /// Returns how tightly the operator binds, operators with a higher precedence are
+    /// evaluated first.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Eq | BinaryOperator::NotEq => 3,
+            BinaryOperator::Less
+            | BinaryOperator::Greater
+            | BinaryOperator::LessEq
+            | BinaryOperator::GreaterEq => 4,
+            BinaryOperator::Add | BinaryOperator::Sub => 5,
+            BinaryOperator::Mul | BinaryOperator::Div => 6,
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+            BinaryOperator::Eq => "==",
+            BinaryOperator::NotEq => "!=",
+            BinaryOperator::Less => "<",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::LessEq => "<=",
+            BinaryOperator::GreaterEq => ">=",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Or => "||",
+        };
+
+        write!(f, "{}", symbol)
+    }
+}
+
+/// An operator that is applied to a single operand.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UnaryOperator {
+    /// Arithmetic negation, `-`
+    Neg,
+    /// Logical negation, `!`
+    Not,
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOperator::Neg => write!(f, "-"),
+            UnaryOperator::Not => write!(f, "!"),
+        }
+    }
+}
+
+/// An attribute in front of a function declaration, such as `@allow(W001)`, which sets the
+/// level of diagnostic codes within the function.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Attribute {
+    pub name: String,
+    /// The words between the parentheses, together with their spans
+    pub args: Vec<(String, Span)>,
+    pub span: Span,
+}
+
 /// Represents a node in the abstract syntax tree (AST). Each node is given a unique
 /// span that represents the starting position and ending position of the evaluated
 /// expression.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum AstNode {
     /// AST node type that represents an empty evaluation
     Empty,
@@ -45,29 +154,84 @@ pub enum AstNode {
     Identifier {
         /// This is the name of the reference or type identifier.
         name: String,
+        span: Span,
     },
     /// AST node type that represents a basic function call
     FunctionCall {
         name: String,
-        param_list: Vec<TypedValue>,
+        /// The argument expressions in call order.
+        param_list: Vec<AstNode>,
+        span: Span,
     },
     /// AST node type representing a function prototype
     FuncDeclaration {
+        /// The attributes in front of the declaration, which are not part of its span
+        attributes: Vec<Attribute>,
         name: String,
-        params: HashMap<String, Type>,
+        /// Parameters in declaration order, which is also the calling convention order.
+        params: Vec<(String, Type)>,
         returns: Type,
         body: Box<AstNode>,
+        span: Span,
     },
     /// AST node type representing a variable declaration
     VarDeclaration {
         name: String,
-        is_mutable: bool, // TODO add interpreter support for mutability
+        /// The type written in front of the variable name.
+        var_type: Type,
+        /// Whether the declaration starts with `mut`, which allows the variable to be assigned to.
+        is_mutable: bool,
         is_static: bool,
-        value: TypedValue,
+        /// The initializer expression. For static variables it is evaluated at compile time.
+        value: Box<AstNode>,
+        span: Span,
     },
     /// AST node type representing a variable assignment
     VarAssignment {
         name: String,
-        new_value: TypedValue // type will get checked upon evaluation
+        new_value: Box<AstNode>, // type will get checked upon evaluation
+        span: Span,
+    },
+    /// AST node type representing a `return` statement, which leaves the function it is in.
+    /// Functions do not return values yet, so it is not followed by one.
+    Return {
+        span: Span,
     },
+    /// AST node type representing a literal value or a reference to a variable
+    Value {
+        value: TypedValue,
+        span: Span,
+    },
+    /// AST node type representing an operator applied to two expressions
+    BinaryOp {
+        op: BinaryOperator,
+        lhs: Box<AstNode>,
+        rhs: Box<AstNode>,
+        span: Span,
+    },
+    /// AST node type representing an operator applied to a single expression
+    UnaryOp {
+        op: UnaryOperator,
+        operand: Box<AstNode>,
+        span: Span,
+    },
+}
+
+impl AstNode {
+    /// Returns the span of the node, or `None` for nodes that do not come from a single
+    /// piece of source text, such as documents and blocks.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            AstNode::Empty | AstNode::Document { .. } | AstNode::Block { .. } => None,
+            AstNode::Identifier { span, .. }
+            | AstNode::FunctionCall { span, .. }
+            | AstNode::FuncDeclaration { span, .. }
+            | AstNode::VarDeclaration { span, .. }
+            | AstNode::VarAssignment { span, .. }
+            | AstNode::Return { span }
+            | AstNode::Value { span, .. }
+            | AstNode::BinaryOp { span, .. }
+            | AstNode::UnaryOp { span, .. } => Some(*span),
+        }
+    }
 }
\ No newline at end of file
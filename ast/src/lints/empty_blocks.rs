@@ -0,0 +1,34 @@
+use common::codes::Code;
+use crate::resolver::DeclarationKind;
+use super::{Finding, Lint, LintContext};
+
+/// Reports function bodies without statements or comments. Function bodies are the only
+/// blocks of Z++ so far.
+pub struct EmptyBlocks;
+
+impl Lint for EmptyBlocks {
+    fn code(&self) -> Code {
+        Code::W008
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for declaration in &context.resolution.declarations {
+            let Some(body) = declaration.body.filter(|_| declaration.kind == DeclarationKind::Function) else {
+                continue
+            };
+            let Some(text) = context.input.get(body.start().to_usize()..body.end().to_usize()) else {
+                continue
+            };
+
+            // the text between the braces, where comments are kept
+            let inside = text.strip_prefix('{').and_then(|text| text.strip_suffix('}'));
+            if inside.is_some_and(|inside| inside.trim().is_empty()) {
+                findings.push(Finding::new(format!("The body of `{}` is empty", declaration.name), body));
+            }
+        }
+
+        findings
+    }
+}
@@ -0,0 +1,267 @@
+mod empty_blocks;
+mod never_reassigned;
+mod shadowing;
+mod unreachable;
+mod unused_functions;
+mod unused_parameters;
+mod unused_variables;
+
+pub use empty_blocks::EmptyBlocks;
+pub use never_reassigned::NeverReassigned;
+pub use shadowing::Shadowing;
+pub use unreachable::UnreachableCode;
+pub use unused_functions::UnusedFunctions;
+pub use unused_parameters::UnusedParameters;
+pub use unused_variables::UnusedVariables;
+
+use codespan::{ByteIndex, Span};
+use codespan_reporting::diagnostic::Severity;
+use common::codes::Code;
+use common::errors::{Reporting, Suggestion};
+use common::levels::{Level, LintLevels};
+use crate::{AstNode, Attribute};
+use crate::checker::CheckError;
+use crate::resolver::{DeclarationId, Resolution};
+
+/// What a lint is given to look at.
+pub struct LintContext<'a> {
+    /// The text of the document
+    pub input: &'a str,
+    /// The top-level nodes of the document
+    pub body: &'a [AstNode],
+    /// What the names in the document refer to
+    pub resolution: &'a Resolution,
+}
+
+impl LintContext<'_> {
+    /// Returns whether a declaration is read, or called if it is a function, anywhere in the
+    /// document.
+    pub fn is_used(&self, id: DeclarationId) -> bool {
+        self.resolution.references_to(id).any(|reference| !reference.is_assignment)
+    }
+
+    /// Returns whether a declaration is referred to at all, including by assignments.
+    pub fn is_referenced(&self, id: DeclarationId) -> bool {
+        self.resolution.references_to(id).next().is_some()
+    }
+}
+
+/// A problem a lint found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub message: String,
+    pub span: Span,
+    /// Secondary labels, which point at the code that is involved in the problem
+    pub labels: Vec<(Span, String)>,
+    /// The change that fixes the problem, if it can be fixed without knowing what was meant
+    pub fix: Option<Fix>,
+}
+
+impl Finding {
+    pub fn new(message: String, span: Span) -> Self {
+        Finding { message, span, labels: Vec::new(), fix: None }
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push((span, message.to_string()));
+        self
+    }
+
+    pub fn with_fix(mut self, message: String, span: Span, replacement: &str) -> Self {
+        self.fix = Some(Fix { message, span, replacement: replacement.to_string() });
+        self
+    }
+}
+
+/// A replacement of a part of the document, which becomes a `Suggestion` of the diagnostic a
+/// finding is reported with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// A check of a resolved, well typed document for code that is valid, but probably a mistake
+/// or more complicated than it needs to be.
+pub trait Lint {
+    /// Returns the code the findings of the lint are reported with, whose entry in the
+    /// registry documents the lint.
+    fn code(&self) -> Code;
+
+    /// Runs the lint, returning what it found.
+    fn check(&self, context: &LintContext) -> Vec<Finding>;
+}
+
+/// Returns every lint, in the order of their codes.
+pub fn lints() -> Vec<Box<dyn Lint>> {
+    vec![
+        Box::new(UnusedVariables),
+        Box::new(UnusedFunctions),
+        Box::new(UnusedParameters),
+        Box::new(Shadowing),
+        Box::new(NeverReassigned),
+        Box::new(UnreachableCode),
+        Box::new(EmptyBlocks),
+    ]
+}
+
+/// Struct representing the lint runner, which runs every lint over a document and reports the
+/// findings at the levels of their codes.
+pub struct Linter<'a> {
+    reporter: Reporting<'a>,
+    levels: LintLevels,
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(name: &'a str, input: &'a str) -> Self {
+        Linter {
+            reporter: Reporting::new(name, input),
+            levels: LintLevels::new(),
+        }
+    }
+
+    /// Stops the linter from emitting diagnostics, they are only returned.
+    pub fn quiet(mut self) -> Self {
+        self.reporter = self.reporter.quiet();
+        self
+    }
+
+    /// Reports through the given instance instead of one of its own, such as one for a file of
+    /// a multi-file database.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - The `Reporting` instance of the linted input.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Linter` instance.
+    pub fn with_reporting(mut self, reporter: Reporting<'a>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Reports findings at the given levels instead of the default ones, which the attributes
+    /// of functions override within them.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - The levels set by the project config file and the command line.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Linter` instance.
+    pub fn with_levels(mut self, levels: LintLevels) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Runs every lint over a document, emitting a diagnostic for every finding that is not
+    /// allowed where it was found.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `AstNode::Document` produced by the parser, which must have passed
+    ///   the checker.
+    /// * `resolution` - What the names in the document refer to.
+    ///
+    /// # Returns
+    ///
+    /// Returns the emitted diagnostics in the order of their spans, together with the fix of
+    /// each finding that has one.
+    pub fn lint(self, document: &AstNode, resolution: &Resolution) -> Vec<CheckError> {
+        let body = match document {
+            AstNode::Document { body } => match body.as_ref() {
+                AstNode::Block { body } => body.as_slice(),
+                _ => &[],
+            },
+            _ => &[],
+        };
+        let context = LintContext { input: self.reporter.source(), body, resolution };
+
+        let mut findings: Vec<(Code, Finding)> = lints().iter()
+            .flat_map(|lint| lint.check(&context).into_iter().map(|finding| (lint.code(), finding)))
+            .collect();
+        findings.sort_by_key(|(code, finding)| (finding.span.start(), *code));
+
+        findings.into_iter()
+            .filter_map(|(code, finding)| self.report(body, code, finding))
+            .collect()
+    }
+
+    /// Reports a finding at the level its code has in the function it was found in.
+    fn report(&self, body: &[AstNode], code: Code, finding: Finding) -> Option<CheckError> {
+        let attributes = body.iter()
+            .find_map(|node| match node {
+                AstNode::FuncDeclaration { attributes, span, .. } if span.start() <= finding.span.start() && finding.span.end() <= span.end() => {
+                    Some(attributes.as_slice())
+                }
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let mut diagnostic = match levels_within(&self.levels, attributes).severity(code)? {
+            Severity::Warning => self.reporter.warning(finding.message, finding.span, code),
+            _ => self.reporter.error(finding.message, finding.span, code),
+        };
+        for (span, message) in &finding.labels {
+            diagnostic = diagnostic.secondary(*span, message);
+        }
+        if let Some(fix) = finding.fix {
+            diagnostic = diagnostic.suggestion(Suggestion {
+                message: fix.message,
+                span: self.reporter.file_span(fix.span),
+                replacement: fix.replacement,
+            });
+        }
+
+        Some(diagnostic.emit())
+    }
+}
+
+/// Returns the levels within a function, which are the levels around it with its attributes
+/// applied. Attributes that are not valid were reported by the checker, and are skipped.
+fn levels_within(levels: &LintLevels, attributes: &[Attribute]) -> LintLevels {
+    let mut levels = levels.clone();
+
+    for attribute in attributes {
+        let Ok(level) = attribute.name.parse::<Level>() else {
+            continue
+        };
+
+        for (arg, _) in &attribute.args {
+            if let Ok(code) = arg.parse() {
+                let _ = levels.set(code, level);
+            }
+        }
+    }
+
+    levels
+}
+
+/// Returns whether a name is marked as intentionally unused by starting with an underscore.
+fn is_intentionally_unused(name: &str) -> bool {
+    name.starts_with('_')
+}
+
+/// Returns the span that removes a piece of code, which includes the line it is on if
+/// nothing else is on that line, so that removing it does not leave a blank line behind.
+/// Otherwise it includes the whitespace that would be left between the rest of the line and
+/// the code.
+fn removal(input: &str, span: Span) -> Span {
+    let (start, end) = (span.start().to_usize(), span.end().to_usize());
+    let line_start = input[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = input[end..].find('\n').map_or(input.len(), |newline| end + newline + 1);
+    let (before, after) = (&input[line_start..start], &input[end..line_end]);
+
+    let (start, end) = match (before.trim().is_empty(), after.trim().is_empty()) {
+        (true, true) => (line_start, line_end),
+        // what follows, such as a comment, moves to where the code started
+        (true, false) => (start, end + after.len() - after.trim_start().len()),
+        (false, true) => (start - before.len() + before.trim_end().len(), end),
+        (false, false) => (start, end),
+    };
+
+    Span::new(ByteIndex(start as u32), ByteIndex(end as u32))
+}
@@ -0,0 +1,62 @@
+use codespan::{ByteIndex, Span};
+use common::codes::Code;
+use crate::AstNode;
+use crate::resolver::DeclarationKind;
+use super::{Finding, Lint, LintContext};
+
+/// Reports variables that are declared with `mut`, but are never assigned to, so that they do
+/// not need to be mutable.
+///
+/// Variables that are never read are left to `UnusedVariables`.
+pub struct NeverReassigned;
+
+impl Lint for NeverReassigned {
+    fn code(&self) -> Code {
+        Code::W006
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for node in context.body {
+            match node {
+                AstNode::FuncDeclaration { body, .. } => {
+                    if let AstNode::Block { body } = body.as_ref() {
+                        for node in body {
+                            check_statement(context, node, &mut findings);
+                        }
+                    }
+                }
+                _ => check_statement(context, node, &mut findings),
+            }
+        }
+
+        findings
+    }
+}
+
+/// Reports the variable a statement declares if it is mutable, but never assigned to.
+fn check_statement(context: &LintContext, node: &AstNode, findings: &mut Vec<Finding>) {
+    let AstNode::VarDeclaration { name, is_mutable: true, span, .. } = node else {
+        return
+    };
+
+    let Some(id) = context.resolution.declarations.iter()
+        .position(|declaration| declaration.kind == DeclarationKind::Variable && declaration.node_span == *span)
+    else {
+        return
+    };
+    let is_reassigned = context.resolution.references_to(id).any(|reference| reference.is_assignment);
+
+    if context.is_used(id) && !is_reassigned {
+        // the declaration starts with `mut`, which is removed with the whitespace after it
+        let start = span.start().to_usize();
+        let rest = &context.input[start + "mut".len()..];
+        let end = start + "mut".len() + rest.len() - rest.trim_start().len();
+
+        findings.push(
+            Finding::new(format!("The variable `{}` is declared mutable, but never reassigned", name), context.resolution.declarations[id].span)
+                .with_fix(format!("Remove `mut` from the declaration of `{}`", name), Span::new(span.start(), ByteIndex(end as u32)), ""),
+        );
+    }
+}
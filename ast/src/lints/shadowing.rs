@@ -0,0 +1,46 @@
+use common::codes::Code;
+use crate::resolver::DeclarationKind;
+use super::{Finding, Lint, LintContext};
+
+/// Reports parameters and variables in function bodies that have the name of a global
+/// variable the function could otherwise use.
+pub struct Shadowing;
+
+impl Lint for Shadowing {
+    fn code(&self) -> Code {
+        Code::W005
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let declarations = &context.resolution.declarations;
+        let mut findings = Vec::new();
+
+        for declaration in declarations {
+            let Some(function) = declaration.parent.map(|parent| &declarations[parent]) else {
+                continue
+            };
+
+            // function bodies only see the globals declared before the function
+            let shadowed = declarations.iter().find(|global| {
+                global.kind == DeclarationKind::Variable
+                    && global.parent.is_none()
+                    && global.name == declaration.name
+                    && global.span.start() < function.node_span.start()
+            });
+
+            if let Some(global) = shadowed {
+                let kind = match declaration.kind {
+                    DeclarationKind::Parameter => "parameter",
+                    _ => "variable",
+                };
+
+                findings.push(
+                    Finding::new(format!("The {} `{}` shadows a global variable", kind, declaration.name), declaration.span)
+                        .with_label(global.span, "the global variable is declared here"),
+                );
+            }
+        }
+
+        findings
+    }
+}
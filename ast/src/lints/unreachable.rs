@@ -0,0 +1,44 @@
+use codespan::Span;
+use common::codes::Code;
+use crate::AstNode;
+use super::{removal, Finding, Lint, LintContext};
+
+/// Reports the statements after a `return`, which never run. The fix removes them.
+pub struct UnreachableCode;
+
+impl Lint for UnreachableCode {
+    fn code(&self) -> Code {
+        Code::W007
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for node in context.body {
+            let AstNode::FuncDeclaration { body, .. } = node else {
+                continue
+            };
+            let AstNode::Block { body } = body.as_ref() else {
+                continue
+            };
+
+            // without conditionals, a `return` is always a statement of the body itself
+            let Some(index) = body.iter().position(|node| matches!(node, AstNode::Return { .. })) else {
+                continue
+            };
+            let (Some(first), Some(last)) = (body.get(index + 1).and_then(AstNode::span), body.last().and_then(AstNode::span)) else {
+                continue
+            };
+            let returns = body[index].span().expect("returns have a span");
+
+            let span = Span::new(first.start(), last.end());
+            findings.push(
+                Finding::new("This code is never run".to_string(), span)
+                    .with_label(returns, "the function returns here")
+                    .with_fix("Remove the code that is never run".to_string(), removal(context.input, span), ""),
+            );
+        }
+
+        findings
+    }
+}
@@ -0,0 +1,35 @@
+use common::codes::Code;
+use crate::checker::ENTRY_POINT;
+use crate::resolver::DeclarationKind;
+use super::{is_intentionally_unused, Finding, Lint, LintContext};
+
+/// Reports functions that are never called, other than by themselves.
+pub struct UnusedFunctions;
+
+impl Lint for UnusedFunctions {
+    fn code(&self) -> Code {
+        Code::W003
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for (id, declaration) in context.resolution.declarations.iter().enumerate() {
+            let is_entry_point = declaration.name == ENTRY_POINT && declaration.params.is_empty();
+            if declaration.kind != DeclarationKind::Function || is_entry_point || is_intentionally_unused(&declaration.name) {
+                continue
+            }
+
+            // calls within the function itself, which recurse, do not count
+            let span = declaration.node_span;
+            let is_called = context.resolution.references_to(id)
+                .any(|reference| reference.span.start() < span.start() || reference.span.end() > span.end());
+
+            if !is_called {
+                findings.push(Finding::new(format!("The function `{}` is never called", declaration.name), declaration.span));
+            }
+        }
+
+        findings
+    }
+}
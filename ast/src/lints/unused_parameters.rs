@@ -0,0 +1,37 @@
+use common::codes::Code;
+use crate::resolver::DeclarationKind;
+use super::{is_intentionally_unused, Finding, Lint, LintContext};
+
+/// Reports parameters whose value is never read.
+///
+/// Parameters that are not assigned to either are renamed by the fix, to start with an
+/// underscore, since removing them would change the calls of the function as well.
+pub struct UnusedParameters;
+
+impl Lint for UnusedParameters {
+    fn code(&self) -> Code {
+        Code::W004
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for (id, declaration) in context.resolution.declarations.iter().enumerate() {
+            if declaration.kind != DeclarationKind::Parameter
+                || is_intentionally_unused(&declaration.name)
+                || context.is_used(id)
+            {
+                continue
+            }
+
+            let mut finding = Finding::new(format!("The parameter `{}` is never read", declaration.name), declaration.span);
+            if !context.is_referenced(id) {
+                let renamed = format!("_{}", declaration.name);
+                finding = finding.with_fix(format!("Rename `{}` to `{}` to show that it is unused", declaration.name, renamed), declaration.span, &renamed);
+            }
+            findings.push(finding);
+        }
+
+        findings
+    }
+}
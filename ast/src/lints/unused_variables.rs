@@ -0,0 +1,38 @@
+use common::codes::Code;
+use crate::resolver::DeclarationKind;
+use super::{is_intentionally_unused, removal, Finding, Lint, LintContext};
+
+/// Reports variables whose value is never read.
+///
+/// Variables that are not assigned to either are removed by the fix, which cannot change
+/// what the program does, since initializers cannot call functions.
+pub struct UnusedVariables;
+
+impl Lint for UnusedVariables {
+    fn code(&self) -> Code {
+        Code::W002
+    }
+
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for (id, declaration) in context.resolution.declarations.iter().enumerate() {
+            if declaration.kind != DeclarationKind::Variable
+                || is_intentionally_unused(&declaration.name)
+                || context.is_used(id)
+            {
+                continue
+            }
+
+            let finding = if context.is_referenced(id) {
+                Finding::new(format!("The variable `{}` is assigned to, but never read", declaration.name), declaration.span)
+            } else {
+                Finding::new(format!("The variable `{}` is never used", declaration.name), declaration.span)
+                    .with_fix(format!("Remove `{}`", declaration.name), removal(context.input, declaration.node_span), "")
+            };
+            findings.push(finding);
+        }
+
+        findings
+    }
+}
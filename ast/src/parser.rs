@@ -1,6 +1,46 @@
-use std::collections::HashMap;
-use crate::{AstNode, Type, TypedValue};
-use crate::lexer::{Token, TokenType};
+use codespan::Span;
+use codespan_reporting::diagnostic::Diagnostic;
+use common::codes::Code;
+use common::errors::{merge_span, Reporting, Suggestion};
+use common::files::FileId;
+use common::spelling::closest;
+use crate::{AstNode, Attribute, BinaryOperator, Type, TypedValue, UnaryOperator};
+use crate::lexer::{Token, TokenType, KEYWORDS};
+use crate::syntax::{build_tree, Marker, NodeKind, SyntaxNode};
+
+/// A part of the input that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub span: Span,
+    /// The span of the first word of the statement and the keyword it probably is a
+    /// misspelling of, such as `fucn` for `func`
+    pub keyword: Option<(Span, &'static str)>,
+    /// The code the error is reported with, which is `E012` unless the tokens are valid syntax
+    /// but cannot be a value, such as a literal out of range
+    pub code: Code,
+}
+
+impl SyntaxError {
+    /// Emits the diagnostic for the error.
+    ///
+    /// # Returns
+    ///
+    /// Returns the diagnostic, together with the suggestion to correct a misspelt keyword.
+    pub fn report(&self, reporter: &Reporting) -> (Diagnostic<FileId>, Vec<Suggestion>) {
+        let mut error = reporter.error(self.message.clone(), self.span, self.code);
+
+        if let Some((span, keyword)) = self.keyword {
+            error = error.suggestion(Suggestion {
+                message: format!("A keyword with a similar name exists: `{}`", keyword),
+                span: reporter.file_span(span),
+                replacement: keyword.to_string(),
+            });
+        }
+
+        error.emit()
+    }
+}
 
 /// Struct representing a parser that generates an Abstract Syntax Tree (AST)
 /// from a list of Tokens generated by the lexer
@@ -9,10 +49,19 @@ pub struct Parser {
     tokens: Vec<Token>,
     /// Represents the current position in the above token list
     pos: usize,
+    /// Every statement or declaration that could not be parsed
+    errors: Vec<SyntaxError>,
+    /// The nodes of the concrete syntax tree that were parsed
+    markers: Vec<Marker>,
 }
 
-fn ident_to_type(ident: String) -> Type {
-    return match ident.as_str() {
+/// The names of the built-in types.
+pub const TYPE_NAMES: [&str; 5] = ["bool", "int", "float", "String", "null"];
+
+/// Returns the type a type name refers to, which is `Type::Reference` for names that are not
+/// built-in types.
+pub fn ident_to_type(ident: String) -> Type {
+    match ident.as_str() {
         "bool" => Type::Boolean,
         "int" => Type::Int,
         "float" => Type::Float,
@@ -22,10 +71,22 @@ fn ident_to_type(ident: String) -> Type {
     }
 }
 
-fn can_convert_f32(value: &f32) -> bool {
-    // Check if the value is within the range of i64
-    value >= &(i32::MIN as f32) && value <= &(i64::MAX as f32) &&
-    value.fract() == 0.0 //Check if the value is an integer (has no fractional part)
+fn token_to_binary_operator(token_type: &TokenType) -> Option<BinaryOperator> {
+    match token_type {
+        TokenType::Plus => Some(BinaryOperator::Add),
+        TokenType::Minus => Some(BinaryOperator::Sub),
+        TokenType::Mul => Some(BinaryOperator::Mul),
+        TokenType::Slash => Some(BinaryOperator::Div),
+        TokenType::DoubleEq => Some(BinaryOperator::Eq),
+        TokenType::NotEq => Some(BinaryOperator::NotEq),
+        TokenType::Less => Some(BinaryOperator::Less),
+        TokenType::Greater => Some(BinaryOperator::Greater),
+        TokenType::LessEq => Some(BinaryOperator::LessEq),
+        TokenType::GreaterEq => Some(BinaryOperator::GreaterEq),
+        TokenType::And => Some(BinaryOperator::And),
+        TokenType::Or => Some(BinaryOperator::Or),
+        _ => None
+    }
 }
 
 /// Implementation for the Parser struct.
@@ -36,27 +97,50 @@ impl Parser {
         Parser {
             tokens,
             pos: 0,
+            errors: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
+    /// Returns the concrete syntax tree of the input parsed by the last call to `parse`, which
+    /// keeps every byte of the input, including whitespace, comments and statements that could
+    /// not be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The text the tokens were lexed from.
+    pub fn syntax_tree(&self, input: &str) -> SyntaxNode {
+        build_tree(input, &self.tokens, &self.markers)
+    }
+
+    /// Returns the syntax errors found by the last call to `parse`. Parts of the input that
+    /// could not be parsed are left out of the AST.
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    /// Emits a diagnostic for every syntax error found by the last call to `parse`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - Reports on the file the tokens were lexed from.
+    ///
+    /// # Returns
+    ///
+    /// Returns the emitted diagnostics, which are empty if the input was parsed successfully.
+    pub fn report_errors(&self, reporter: &Reporting) -> Vec<Diagnostic<FileId>> {
+        self.errors.iter()
+            .map(|error| error.report(reporter).0)
+            .collect()
+    }
+
     pub fn parse(&mut self) -> AstNode {
         let mut body = Vec::new();
+        self.markers.clear();
 
-        while let Some(token) = self.next() {
-            match &token.token_type {
-                TokenType::Func => {
-                    if let Some(func_node) = self.enter_func_declaration() {
-                        body.push(func_node);
-                    }
-                }
-                TokenType::Identifier(_) => {
-                    if let Some(expr) = self.parse_expr() {
-                        body.push(expr);
-                    }
-                }
-                _ => {
-                    panic!("Token type not implemented: {:?}", &token.token_type)
-                }
+        while self.pos < self.tokens.len() {
+            if let Some(node) = self.parse_item() {
+                body.push(node);
             }
         }
 
@@ -67,108 +151,420 @@ impl Parser {
         }
     }
 
-    fn parse_expr(&mut self) -> Option<AstNode> {
-        if let Some(token) = self.get() {
-            match &token.token_type {
-                TokenType::Identifier(_) => {
-                    if let Some(token) = self.next() {
-                        match &token.token_type {
-                            // variable declaration
-                            TokenType::Identifier(var_name) => {
-                                if let Some(token) = self.next() {
-                                    match &token.token_type {
-                                        TokenType::Eq => {
-                                            if let Some(val) = self.next() {
-                                                return match &val.token_type {
-                                                    // reference
-                                                    TokenType::Identifier(reference) => {
-                                                        Some(AstNode::VarDeclaration {
-                                                            name: var_name.clone(),
-                                                            is_mutable: false,
-                                                            is_static: false,
-                                                            value: TypedValue::ReferenceVal(reference.clone()),
-                                                        })
-                                                    },
-                                                    // strings
-                                                    TokenType::StringLiteral(lit) => {
-                                                        Some(AstNode::VarDeclaration {
-                                                            name: var_name.clone(),
-                                                            is_mutable: false,
-                                                            is_static: false,
-                                                            value: TypedValue::StringVal(lit.clone()),
-                                                        })
-                                                    },
-                                                    // booleans
-                                                    TokenType::BoolLiteral(lit) => {
-                                                        Some(AstNode::VarDeclaration {
-                                                            name: var_name.clone(),
-                                                            is_mutable: false,
-                                                            is_static: false,
-                                                            value: TypedValue::BoolVal(lit.clone()),
-                                                        })
-                                                    },
-                                                    // integers
-                                                    TokenType::IntLiteral(lit) => {
-                                                        if can_convert_f32(lit) {
-                                                            // integer
-                                                            Some(AstNode::VarDeclaration {
-                                                                name: var_name.clone(),
-                                                                is_mutable: false,
-                                                                is_static: false,
-                                                                value: TypedValue::IntVal(lit.clone() as i32),
-                                                            })
-                                                        } else {
-                                                            // float
-                                                            Some(AstNode::VarDeclaration {
-                                                                name: var_name.clone(),
-                                                                is_mutable: false,
-                                                                is_static: false,
-                                                                value: TypedValue::FloatVal(lit.clone()),
-                                                            })
-                                                        }
-                                                    },
-                                                    // null
-                                                    TokenType::NullLiteral => {
-                                                        Some(AstNode::VarDeclaration {
-                                                            name: var_name.clone(),
-                                                            is_mutable: false,
-                                                            is_static: false,
-                                                            value: TypedValue::NullVal,
-                                                        })
-                                                    },
-                                                    // unknown value, user error
-                                                    _ => None
-                                                }
-                                            }
-                                        },
-                                        // not a variable declaration
-                                        _ => return None
-                                    }
+    /// Parses the top-level function or statement at the current position, which is where the
+    /// previous one ended. Top-level items are parsed independently of each other, which
+    /// incremental reparsing relies on.
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed node, or `None` if the item could not be parsed and a syntax error
+    /// was recorded, or the input is exhausted.
+    pub(crate) fn parse_item(&mut self) -> Option<AstNode> {
+        let token = self.next()?;
+        let start = self.pos - 1;
+
+        match &token.token_type {
+            TokenType::Func => {
+                let func_node = self.enter_func_declaration(token);
+                if func_node.is_none() {
+                    self.recover("Expected a function declaration", start, false);
+                }
+                func_node
+            }
+            TokenType::At => {
+                let func_node = self.enter_attributes(token)
+                    .and_then(|attributes| self.enter_attributed_func(attributes));
+                if func_node.is_none() {
+                    self.recover("Expected attributes in front of a function declaration", start, false);
+                }
+                func_node
+            }
+            TokenType::Identifier(_) | TokenType::Static | TokenType::Mut | TokenType::Return => {
+                let expr = self.parse_statement(token);
+                if expr.is_none() {
+                    self.recover("Expected a variable declaration, assignment or function call", start, false);
+                }
+                expr
+            }
+            _ => {
+                self.recover("Expected a function, variable declaration, assignment or function call", start, false);
+                None
+            }
+        }
+    }
+
+    /// Returns the index of the next token to be parsed.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of syntax errors recorded so far.
+    pub(crate) fn error_count(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Appends tokens to the input, as if they had been lexed with the rest of it.
+    pub(crate) fn push_tokens(&mut self, tokens: impl IntoIterator<Item = Token>) {
+        self.tokens.extend(tokens);
+    }
+
+    /// Rewinds the parser to the token with index `pos`, forgetting the errors recorded after
+    /// the first `errors`.
+    pub(crate) fn rewind(&mut self, pos: usize, errors: usize) {
+        self.pos = pos;
+        self.errors.truncate(errors);
+        self.markers.retain(|marker| marker.start < pos);
+    }
+
+    /// Parses the whole input as a single expression, as it is entered into a REPL.
+    ///
+    /// # Returns
+    ///
+    /// Returns the expression, or `None` if the input is not exactly one expression.
+    pub fn parse_bare_expression(&mut self) -> Option<AstNode> {
+        let (expr, _) = self.parse_expression()?;
+
+        if self.pos < self.tokens.len() {
+            return None
+        }
+
+        Some(expr)
+    }
+
+    /// Records a syntax error for the statement starting at the token with index `start`, and
+    /// skips ahead to the next token that can start a statement, so that the statements after
+    /// it are still parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Describes what was expected.
+    /// * `start` - The index of the first token of the statement that could not be parsed.
+    /// * `in_body` - Whether the statement is in a function body, which a `}` closes.
+    fn recover(&mut self, message: &str, start: usize, in_body: bool) {
+        let tokens = &self.tokens;
+        // statements start with a keyword, or with the name in a declaration, assignment or call
+        let starts_statement = |index: usize| match tokens[index].token_type {
+            TokenType::Func | TokenType::Static | TokenType::Mut | TokenType::Return | TokenType::At => true,
+            TokenType::RBrace => in_body,
+            TokenType::Identifier(_) => tokens.get(index + 1).is_some_and(|next| matches!(
+                next.token_type,
+                TokenType::Identifier(_) | TokenType::Eq | TokenType::LParen
+            )),
+            _ => false,
+        };
+
+        // keywords cannot appear within a statement, so the one the statement failed at
+        // starts the next statement
+        if self.pos > start + 1 {
+            let last = self.pos - 1;
+            if !matches!(tokens[last].token_type, TokenType::Identifier(_)) && starts_statement(last) {
+                self.pos = last;
+            }
+        }
+
+        // braces are skipped in pairs, so that a function whose declaration is malformed is
+        // skipped together with its body
+        let mut depth: usize = 0;
+        while let Some(token) = tokens.get(self.pos) {
+            if depth == 0 && starts_statement(self.pos) {
+                break
+            }
+
+            match token.token_type {
+                TokenType::LBrace => depth += 1,
+                TokenType::RBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.pos += 1;
+        }
+
+        // the nodes of the statement are replaced by a single error node
+        self.markers.retain(|marker| marker.start < start);
+        self.mark(NodeKind::Error, start);
+
+        self.error(message, self.tokens[start].span);
+
+        // literals cannot start a statement, so only keywords are suggested
+        if let TokenType::Identifier(word) = &self.tokens[start].token_type {
+            let keywords = KEYWORDS.into_iter().filter(|keyword| !matches!(*keyword, "true" | "false" | "null"));
+            let keyword = closest(word, keywords).map(|keyword| (self.tokens[start].span, keyword));
+            if let Some(error) = self.errors.last_mut() {
+                error.keyword = keyword;
+            }
+        }
+    }
+
+    /// Records a node of the concrete syntax tree that starts at the token with index `start`
+    /// and ends with the last token that was consumed.
+    fn mark(&mut self, kind: NodeKind, start: usize) {
+        self.markers.push(Marker { kind, start, end: self.pos });
+    }
+
+    /// Records a syntax error spanning from `start` to the last token that was consumed.
+    fn error(&mut self, message: &str, start: Span) {
+        let end = self.tokens.get(self.pos.saturating_sub(1))
+            .map_or(start, |token| token.span);
+
+        self.errors.push(SyntaxError {
+            message: message.to_string(),
+            span: merge_span(&start, &end),
+            keyword: None,
+            code: Code::E012,
+        });
+    }
+
+    /// Parses a statement starting with the given token.
+    fn parse_statement(&mut self, first: Token) -> Option<AstNode> {
+        if matches!(first.token_type, TokenType::Static | TokenType::Mut) {
+            return self.enter_declaration_with_keyword(first)
+        }
+        if first.token_type == TokenType::Return {
+            self.mark(NodeKind::Return, self.pos - 1);
+            return Some(AstNode::Return { span: first.span })
+        }
+
+        self.parse_expr(first)
+    }
+
+    /// Parses a `static` or `mut` variable declaration, starting after the keyword.
+    fn enter_declaration_with_keyword(&mut self, first: Token) -> Option<AstNode> {
+        let start = self.pos - 1;
+        let token = self.next()?;
+        if let TokenType::Identifier(_) = &token.token_type {
+            if let Some(AstNode::VarDeclaration { name, var_type, value, span, .. }) = self.parse_expr(token) {
+                // the declaration was the last node to be recorded
+                if let Some(marker) = self.markers.last_mut() {
+                    marker.start = start;
+                }
+
+                return Some(AstNode::VarDeclaration {
+                    name,
+                    var_type,
+                    is_mutable: first.token_type == TokenType::Mut,
+                    is_static: first.token_type == TokenType::Static,
+                    value,
+                    span: merge_span(&first.span, &span),
+                })
+            }
+        }
+
+        None
+    }
+
+    fn parse_expr(&mut self, first: Token) -> Option<AstNode> {
+        let start = self.pos - 1;
+        if let TokenType::Identifier(ident) = &first.token_type {
+            if let Some(token) = self.next() {
+                return match &token.token_type {
+                    // variable declaration
+                    TokenType::Identifier(var_name) => {
+                        if let Some(token) = self.next() {
+                            if token.token_type == TokenType::Eq {
+                                if let Some((value, end)) = self.parse_expression() {
+                                    self.mark(NodeKind::VarDeclaration, start);
+                                    return Some(AstNode::VarDeclaration {
+                                        name: var_name.clone(),
+                                        var_type: ident_to_type(ident.clone()),
+                                        is_mutable: false,
+                                        is_static: false,
+                                        value: Box::new(value),
+                                        span: merge_span(&first.span, &end),
+                                    })
                                 }
-                            },
-                            // unrecognised
-                            _ => return None
+                            }
                         }
+
+                        None
+                    },
+                    // variable assignment
+                    TokenType::Eq => {
+                        if let Some((new_value, end)) = self.parse_expression() {
+                            self.mark(NodeKind::VarAssignment, start);
+                            return Some(AstNode::VarAssignment {
+                                name: ident.clone(),
+                                new_value: Box::new(new_value),
+                                span: merge_span(&first.span, &end),
+                            })
+                        }
+
+                        None
+                    },
+                    // function call
+                    TokenType::LParen => {
+                        if let Some((param_list, end)) = self.enter_call_args() {
+                            self.mark(NodeKind::FunctionCall, start);
+                            return Some(AstNode::FunctionCall {
+                                name: ident.clone(),
+                                param_list,
+                                span: merge_span(&first.span, &end),
+                            })
+                        }
+
+                        None
+                    },
+                    // unrecognised
+                    _ => None
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Parses an expression, returning it together with its span.
+    fn parse_expression(&mut self) -> Option<(AstNode, Span)> {
+        self.parse_binary(1)
+    }
+
+    /// Parses a chain of binary operators whose precedence is at least `min_precedence`,
+    /// operators of equal precedence associate to the left.
+    fn parse_binary(&mut self, min_precedence: u8) -> Option<(AstNode, Span)> {
+        let start = self.pos;
+        let (mut lhs, mut span) = self.parse_unary()?;
+
+        while let Some(op) = self.get().and_then(|token| token_to_binary_operator(&token.token_type)) {
+            if op.precedence() < min_precedence {
+                break
+            }
+
+            self.next();
+            let (rhs, end) = self.parse_binary(op.precedence() + 1)?;
+            self.mark(NodeKind::BinaryOp, start);
+            span = merge_span(&span, &end);
+            lhs = AstNode::BinaryOp {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span,
+            };
+        }
+
+        Some((lhs, span))
+    }
+
+    /// Parses a prefix operator applied to an operand, or a single operand.
+    fn parse_unary(&mut self) -> Option<(AstNode, Span)> {
+        let start = self.pos;
+        let token = self.get()?;
+        let op = match &token.token_type {
+            TokenType::Minus => UnaryOperator::Neg,
+            TokenType::Not => UnaryOperator::Not,
+            TokenType::LParen => {
+                self.next();
+                let (expr, _) = self.parse_expression()?;
+                let close = self.next()?;
+                if close.token_type != TokenType::RParen {
+                    return None
+                }
+
+                self.mark(NodeKind::Paren, start);
+                return Some((expr, merge_span(&token.span, &close.span)))
+            }
+            _ => {
+                let (value, span) = self.parse_value()?;
+                self.mark(NodeKind::Value, start);
+                return Some((AstNode::Value { value, span }, span))
+            }
+        };
+
+        self.next();
+        let (operand, end) = self.parse_unary()?;
+        self.mark(NodeKind::UnaryOp, start);
+        let span = merge_span(&token.span, &end);
+
+        Some((AstNode::UnaryOp {
+            op,
+            operand: Box::new(operand),
+            span,
+        }, span))
+    }
+
+    /// Parses a single value, returning it together with the span of the token it came from.
+    fn parse_value(&mut self) -> Option<(TypedValue, Span)> {
+        if let Some(val) = self.next() {
+            let value = match &val.token_type {
+                // reference
+                TokenType::Identifier(reference) => TypedValue::ReferenceVal(reference.clone()),
+                // strings
+                TokenType::StringLiteral(lit) => TypedValue::StringVal(lit.clone()),
+                // booleans
+                TokenType::BoolLiteral(lit) => TypedValue::BoolVal(*lit),
+                // integers
+                TokenType::IntLiteral(lit) => match i32::try_from(*lit) {
+                    Ok(lit) => TypedValue::IntVal(lit),
+                    Err(_) => {
+                        // the literal is still a value of the right type, so that nothing
+                        // else is reported about the statement
+                        self.errors.push(SyntaxError {
+                            message: format!("The literal is larger than the largest `int`, {}", i32::MAX),
+                            span: val.span,
+                            keyword: None,
+                            code: Code::E017,
+                        });
+                        TypedValue::IntVal(i32::MAX)
                     }
                 },
+                // floats
+                TokenType::FloatLiteral(lit) => TypedValue::FloatVal(*lit),
+                // null
+                TokenType::NullLiteral => TypedValue::NullVal,
+                // unknown value, user error
                 _ => return None
+            };
+
+            return Some((value, val.span))
+        }
+
+        None
+    }
+
+    /// Parses the comma separated arguments of a function call, starting after the opening
+    /// parenthesis. Returns the arguments and the span of the closing parenthesis.
+    fn enter_call_args(&mut self) -> Option<(Vec<AstNode>, Span)> {
+        let start = self.pos - 1;
+        let mut args = Vec::new();
+
+        if let Some(token) = self.get() {
+            if token.token_type == TokenType::RParen {
+                self.next();
+                self.mark(NodeKind::ArgList, start);
+                return Some((args, token.span))
+            }
+        }
+
+        while let Some((value, _)) = self.parse_expression() {
+            args.push(value);
+
+            if let Some(token) = self.next() {
+                match &token.token_type {
+                    TokenType::Comma => continue,
+                    TokenType::RParen => {
+                        self.mark(NodeKind::ArgList, start);
+                        return Some((args, token.span))
+                    }
+                    _ => return None
+                }
             }
         }
 
         None
     }
 
-    fn enter_func_declaration(&mut self) -> Option<AstNode> {
+    fn enter_func_declaration(&mut self, func: Token) -> Option<AstNode> {
+        let start = self.pos - 1;
         if let Some(token) = self.next() {
             match &token.token_type {
                 TokenType::Identifier(func_name) => {
                     if let Some(params) = self.enter_func_params() {
-                        if let Some(body) = self.enter_func_body() {
+                        if let Some((body, end)) = self.enter_func_body() {
+                            self.mark(NodeKind::FuncDeclaration, start);
                             return Some(AstNode::FuncDeclaration {
+                                attributes: Vec::new(),
                                 name: func_name.clone(),
                                 params,
                                 returns: Type::Void,
                                 body: Box::new(body),
+                                span: merge_span(&func.span, &end),
                             })
                         }
                     }
@@ -180,20 +576,105 @@ impl Parser {
         None
     }
 
-    fn enter_func_params(&mut self) -> Option<HashMap<String, Type>> {
-        let mut params = HashMap::new();
+    /// Parses the attributes in front of a function declaration, starting after the `@` of
+    /// the first one. Every attribute is a name followed by comma separated words in
+    /// parentheses, such as `@allow(W001, W002)`.
+    fn enter_attributes(&mut self, first: Token) -> Option<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        let mut at = first;
+
+        loop {
+            let start = self.pos - 1;
+            let TokenType::Identifier(name) = self.next()?.token_type else {
+                return None
+            };
+            if self.next()?.token_type != TokenType::LParen {
+                return None
+            }
+
+            let mut args = Vec::new();
+            let close = loop {
+                let token = self.next()?;
+                match token.token_type {
+                    TokenType::Identifier(arg) => args.push(self.enter_attribute_arg(arg, token.span)),
+                    TokenType::RParen => break token,
+                    _ => return None,
+                }
+
+                let token = self.next()?;
+                match token.token_type {
+                    TokenType::Comma => continue,
+                    TokenType::RParen => break token,
+                    _ => return None,
+                }
+            };
+
+            self.mark(NodeKind::Attribute, start);
+            attributes.push(Attribute { name, args, span: merge_span(&at.span, &close.span) });
+
+            match self.get() {
+                Some(token) if token.token_type == TokenType::At => at = self.next()?,
+                _ => return Some(attributes),
+            }
+        }
+    }
+
+    /// Parses the rest of an argument of an attribute, which is a name in kebab case such as
+    /// `float-comparison`, and is lexed as identifiers joined by minus signs.
+    fn enter_attribute_arg(&mut self, mut arg: String, mut span: Span) -> (String, Span) {
+        while let (Some(minus), Some(next)) = (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            let TokenType::Identifier(part) = &next.token_type else {
+                break
+            };
+            let adjacent = minus.span.start() == span.end() && next.span.start() == minus.span.end();
+            if minus.token_type != TokenType::Minus || !adjacent {
+                break
+            }
+
+            arg = format!("{}-{}", arg, part);
+            span = merge_span(&span, &next.span);
+            self.pos += 2;
+        }
+
+        (arg, span)
+    }
+
+    /// Parses the function declaration that follows its attributes.
+    fn enter_attributed_func(&mut self, attributes: Vec<Attribute>) -> Option<AstNode> {
+        let func = self.next()?;
+        if func.token_type != TokenType::Func {
+            return None
+        }
+
+        match self.enter_func_declaration(func)? {
+            AstNode::FuncDeclaration { name, params, returns, body, span, .. } => {
+                Some(AstNode::FuncDeclaration { attributes, name, params, returns, body, span })
+            }
+            _ => None,
+        }
+    }
+
+    fn enter_func_params(&mut self) -> Option<Vec<(String, Type)>> {
+        let mut params = Vec::new();
+        let start = self.pos;
 
         if let Some(token) = self.next() {
             match &token.token_type {
                 TokenType::LParen => {
                     while let Some(token) = self.next() {
                         match &token.token_type {
-                            TokenType::RParen => return Some(params),
+                            TokenType::RParen => {
+                                self.mark(NodeKind::ParamList, start);
+                                return Some(params)
+                            }
+                            // parameters are separated by commas
+                            TokenType::Comma if !params.is_empty() => continue,
                             TokenType::Identifier(type_ident) => {
                                 if let Some(token) = self.next() {
                                     match &token.token_type {
                                         TokenType::Identifier(param_ident) => {
-                                            params.insert(param_ident.clone(), ident_to_type(type_ident.clone()));
+                                            self.mark(NodeKind::Param, self.pos - 2);
+                                            params.push((param_ident.clone(), ident_to_type(type_ident.clone())));
                                         },
                                         _ => return None
                                     }
@@ -211,45 +692,53 @@ impl Parser {
         None
     }
 
-    fn enter_func_body(&mut self) -> Option<AstNode> {
+    /// Parses a function body, starting at its opening brace. Statements that cannot be parsed
+    /// are recorded as errors and left out, and a body that is never closed ends before the
+    /// next function, or at the end of the input.
+    fn enter_func_body(&mut self) -> Option<(AstNode, Span)> {
+        let start = self.pos;
+        let open = self.next()?;
+        if open.token_type != TokenType::LBrace {
+            return None
+        }
+
         let mut body = vec![];
+        let mut end = open.span;
 
-        if let Some(token) = self.next() {
-            match &token.token_type {
-                // open func body
-                TokenType::LBrace => {
-                    while let Some(token) = self.next() {
-                        match &token.token_type {
-                            // close func body
-                            TokenType::RBrace => {
-                                return Some(AstNode::Block {
-                                    body,
-                                })
-                            }
-                            // fill func body
-                            _ => {
-                                if let Some(expr) = self.parse_expr() {
-                                    body.push(expr);
-                                } else {
-                                    return None
-                                }
-                            }
-                        }
-                    }
+        loop {
+            match self.get() {
+                // close func body
+                Some(token) if token.token_type == TokenType::RBrace => {
+                    self.next();
+                    self.mark(NodeKind::Block, start);
+                    return Some((AstNode::Block { body }, token.span))
                 }
-                _ => return None
-            }
-        }
+                // functions cannot be nested, so the body is missing its `}`
+                Some(token) if token.token_type != TokenType::Func => {
+                    // fill func body
+                    self.next();
+                    let start = self.pos - 1;
 
-        None
-    }
+                    if let Some(expr) = self.parse_statement(token) {
+                        body.push(expr);
+                    } else {
+                        self.recover("Expected a variable declaration, assignment or function call", start, true);
+                    }
 
-    fn peek(&mut self) -> Option<Token> {
-        if let Some(token) = self.tokens.get(self.pos + 1) {
-            return Some(token.clone())
+                    end = self.tokens[self.pos - 1].span;
+                }
+                _ => {
+                    self.errors.push(SyntaxError {
+                        message: "Expected a `}` closing the function body".to_string(),
+                        span: open.span,
+                        keyword: None,
+                        code: Code::E012,
+                    });
+                    self.mark(NodeKind::Block, start);
+                    return Some((AstNode::Block { body }, end))
+                }
+            }
         }
-
-        None
     }
 
     fn get(&mut self) -> Option<Token> {
@@ -262,7 +751,7 @@ impl Parser {
 
     fn next(&mut self) -> Option<Token> {
         if self.pos == 0 {
-            if let Some(token) = self.tokens.get(0) {
+            if let Some(token) = self.tokens.first() {
                 self.pos += 1;
                 return Some(token.clone());
             }
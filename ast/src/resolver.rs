@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use codespan::{ByteIndex, Span};
+use crate::{AstNode, Type, TypedValue};
+use crate::lexer::{Token, TokenType};
+
+/// Identifies a declaration, it is the declaration's index in `Resolution::declarations`.
+pub type DeclarationId = usize;
+
+/// The offset scopes that last until the end of the input end at.
+const END_OF_INPUT: u32 = u32::MAX;
+
+/// What a declaration introduces.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DeclarationKind {
+    Function,
+    Parameter,
+    Variable,
+}
+
+/// A name introduced by a function, parameter or variable declaration.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Declaration {
+    pub name: String,
+    pub kind: DeclarationKind,
+    /// The type of a variable or parameter, or the return type of a function
+    pub var_type: Type,
+    /// The parameters of a function, empty for every other declaration
+    pub params: Vec<(String, Type)>,
+    pub is_static: bool,
+    /// Whether a variable was declared with `mut`
+    pub is_mutable: bool,
+    /// The span of the declared name
+    pub span: Span,
+    /// The span of the whole declaration, for parameters this is the span of their function
+    pub node_span: Span,
+    /// The part of the input the name can be used in, which may extend past the end of the
+    /// input. Functions can be used anywhere.
+    pub scope: Span,
+    /// The function a parameter or local variable belongs to
+    pub parent: Option<DeclarationId>,
+    /// The body of a function, from its opening brace to its closing brace or, if it is never
+    /// closed, to the next declaration
+    pub body: Option<Span>,
+}
+
+impl Declaration {
+    /// Returns the declaration as it is written in source, without a function body or the
+    /// initializer of a variable, such as `func add(int a, int b)` or `mut int count`.
+    pub fn signature(&self) -> String {
+        match self.kind {
+            DeclarationKind::Function => {
+                let params: Vec<String> = self.params.iter()
+                    .map(|(name, param_type)| format!("{} {}", param_type, name))
+                    .collect();
+
+                format!("func {}({})", self.name, params.join(", "))
+            }
+            DeclarationKind::Variable if self.is_static => format!("static {} {}", self.var_type, self.name),
+            DeclarationKind::Variable if self.is_mutable => format!("mut {} {}", self.var_type, self.name),
+            DeclarationKind::Parameter | DeclarationKind::Variable => format!("{} {}", self.var_type, self.name),
+        }
+    }
+}
+
+/// A use of a declared name, such as a variable read, an assignment or a function call.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Reference {
+    pub declaration: DeclarationId,
+    /// The span of the name at the use
+    pub span: Span,
+    /// Whether the use assigns a new value to a variable, rather than reading it or calling a
+    /// function
+    pub is_assignment: bool,
+}
+
+/// The declarations of a document, and what every name in it refers to.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Resolution {
+    /// Every declaration in the order it appears in
+    pub declarations: Vec<Declaration>,
+    /// Every use of a name that could be resolved, names that cannot be resolved are reported
+    /// by the checker
+    pub references: Vec<Reference>,
+}
+
+impl Resolution {
+    /// Returns the declaration whose name is at a byte offset, either where it is declared
+    /// or where it is used. An offset directly after a name also counts as on it.
+    pub fn declaration_at(&self, offset: usize) -> Option<DeclarationId> {
+        let contains = |span: &Span| span.start().to_usize() <= offset && offset <= span.end().to_usize();
+
+        self.declarations.iter()
+            .position(|declaration| contains(&declaration.span))
+            .or_else(|| {
+                self.references.iter()
+                    .find(|reference| contains(&reference.span))
+                    .map(|reference| reference.declaration)
+            })
+    }
+
+    /// Returns the declarations whose names can be used at a byte offset. Of declarations with
+    /// the same name, only the innermost is returned. Functions and variables are in separate
+    /// namespaces, so a variable does not hide a function with the same name.
+    pub fn visible_at(&self, offset: usize) -> Vec<DeclarationId> {
+        let contains = |span: &Span| span.start().to_usize() <= offset && offset <= span.end().to_usize();
+        let function = self.declarations.iter()
+            .find(|declaration| declaration.body.as_ref().is_some_and(contains));
+
+        let mut visible = HashMap::new();
+        for (id, declaration) in self.declarations.iter().enumerate() {
+            if !contains(&declaration.scope) {
+                continue
+            }
+
+            // function bodies only see the globals declared before the function
+            let is_global = declaration.kind == DeclarationKind::Variable && declaration.parent.is_none();
+            if is_global && function.is_some_and(|function| declaration.span.start() > function.node_span.start()) {
+                continue
+            }
+
+            // inner declarations come after the declarations they shadow
+            let is_function = declaration.kind == DeclarationKind::Function;
+            visible.insert((declaration.name.as_str(), is_function), id);
+        }
+
+        let mut visible: Vec<DeclarationId> = visible.into_values().collect();
+        visible.sort();
+        visible
+    }
+
+    /// Returns every use of a declaration in the order they appear in.
+    pub fn references_to(&self, id: DeclarationId) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(move |reference| reference.declaration == id)
+    }
+}
+
+/// Resolves every name in a parsed document to its declaration, following the scoping rules
+/// of the checker: functions are visible everywhere, variables from their declaration to the
+/// end of their scope, and function bodies see the globals declared before them.
+///
+/// # Arguments
+///
+/// * `document` - The `AstNode::Document` produced by the parser, which may be incomplete if
+///   the input has syntax errors.
+/// * `tokens` - The tokens the document was parsed from, which locate the declared names.
+///
+/// # Returns
+///
+/// Returns the `Resolution` of the document.
+pub fn resolve(document: &AstNode, tokens: &[Token]) -> Resolution {
+    let mut resolver = Resolver {
+        tokens,
+        function: None,
+        functions: HashMap::new(),
+        scopes: vec![HashMap::new()],
+        resolution: Resolution::default(),
+    };
+
+    if let AstNode::Document { body } = document {
+        if let AstNode::Block { body } = body.as_ref() {
+            resolver.resolve_body(body);
+        }
+    }
+
+    resolver.resolution
+}
+
+struct Resolver<'a> {
+    tokens: &'a [Token],
+    /// The function whose body is being resolved
+    function: Option<DeclarationId>,
+    /// The first declaration of every function, which is the one calls refer to
+    functions: HashMap<String, DeclarationId>,
+    /// Stack of variable scopes, the innermost scope is last
+    scopes: Vec<HashMap<String, DeclarationId>>,
+    resolution: Resolution,
+}
+
+impl Resolver<'_> {
+    fn resolve_body(&mut self, body: &[AstNode]) {
+        // functions can be called before they are declared
+        let mut functions = Vec::new();
+        for (index, node) in body.iter().enumerate() {
+            if let AstNode::FuncDeclaration { name, params, returns, span, .. } = node {
+                let name_span = self.identifiers(*span).next().map_or(*span, |token| token.span);
+                let next = body.get(index + 1).and_then(AstNode::span);
+
+                let id = self.declare(Declaration {
+                    name: name.clone(),
+                    kind: DeclarationKind::Function,
+                    var_type: *returns,
+                    params: params.clone(),
+                    is_static: false,
+                    is_mutable: false,
+                    span: name_span,
+                    node_span: *span,
+                    scope: Span::new(0, END_OF_INPUT),
+                    parent: None,
+                    body: Some(self.body_span(*span, next)),
+                });
+
+                self.functions.entry(name.clone()).or_insert(id);
+                functions.push(id);
+            }
+        }
+
+        let mut functions = functions.into_iter();
+        for node in body {
+            match node {
+                AstNode::FuncDeclaration { params, body, span, .. } => {
+                    let id = functions.next().expect("every function was declared");
+                    self.resolve_function(id, params, body, *span);
+                }
+                _ => self.resolve_statement(node),
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, id: DeclarationId, params: &[(String, Type)], body: &AstNode, span: Span) {
+        let body_span = self.resolution.declarations[id].body.unwrap_or(span);
+
+        // the parameter list is a sequence of type and name pairs between the parentheses
+        let names: Vec<Span> = self.tokens_in(span)
+            .skip_while(|token| token.token_type != TokenType::LParen)
+            .take_while(|token| token.token_type != TokenType::RParen)
+            .filter(|token| matches!(token.token_type, TokenType::Identifier(_)))
+            .skip(1)
+            .step_by(2)
+            .map(|token| token.span)
+            .collect();
+
+        self.function = Some(id);
+        self.scopes.push(HashMap::new());
+
+        for (index, (param, param_type)) in params.iter().enumerate() {
+            let param_id = self.declare(Declaration {
+                name: param.clone(),
+                kind: DeclarationKind::Parameter,
+                var_type: *param_type,
+                params: Vec::new(),
+                is_static: false,
+                is_mutable: false,
+                span: names.get(index).copied().unwrap_or(span),
+                node_span: span,
+                scope: body_span,
+                parent: Some(id),
+                body: None,
+            });
+            self.bind(param, param_id);
+        }
+
+        if let AstNode::Block { body } = body {
+            for node in body {
+                self.resolve_statement(node);
+            }
+        }
+
+        self.scopes.pop();
+        self.function = None;
+    }
+
+    fn resolve_statement(&mut self, node: &AstNode) {
+        match node {
+            AstNode::VarDeclaration { name, var_type, is_mutable, is_static, value, span } => {
+                // the initializer cannot see the variable it initializes
+                self.resolve_expression(value);
+
+                // the name follows the type, which is an identifier as well
+                let name_span = self.identifiers(*span).nth(1).map_or(*span, |token| token.span);
+                // variables can be used until the end of the body they are declared in
+                let scope_end = self.function
+                    .and_then(|function| self.resolution.declarations[function].body)
+                    .map_or(ByteIndex(END_OF_INPUT), |body| body.end());
+
+                let id = self.declare(Declaration {
+                    name: name.clone(),
+                    kind: DeclarationKind::Variable,
+                    var_type: *var_type,
+                    params: Vec::new(),
+                    is_static: *is_static,
+                    is_mutable: *is_mutable,
+                    span: name_span,
+                    node_span: *span,
+                    scope: Span::new(span.end(), scope_end),
+                    parent: self.function,
+                    body: None,
+                });
+                self.bind(name, id);
+            }
+            AstNode::VarAssignment { name, new_value, span } => {
+                if let Some(id) = self.lookup(name) {
+                    self.refer(id, self.first_token(*span), true);
+                }
+
+                self.resolve_expression(new_value);
+            }
+            AstNode::FunctionCall { name, param_list, span } => {
+                if let Some(id) = self.functions.get(name).copied() {
+                    self.refer(id, self.first_token(*span), false);
+                }
+
+                for arg in param_list {
+                    self.resolve_expression(arg);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn resolve_expression(&mut self, node: &AstNode) {
+        match node {
+            AstNode::Value { value: TypedValue::ReferenceVal(name), span } => {
+                if let Some(id) = self.lookup(name) {
+                    self.refer(id, *span, false);
+                }
+            }
+            AstNode::UnaryOp { operand, .. } => self.resolve_expression(operand),
+            AstNode::BinaryOp { lhs, rhs, .. } => {
+                self.resolve_expression(lhs);
+                self.resolve_expression(rhs);
+            }
+            _ => {}
+        }
+    }
+
+    fn declare(&mut self, declaration: Declaration) -> DeclarationId {
+        self.resolution.declarations.push(declaration);
+        self.resolution.declarations.len() - 1
+    }
+
+    fn bind(&mut self, name: &str, id: DeclarationId) {
+        self.scopes.last_mut()
+            .expect("The global scope is never popped")
+            .insert(name.to_string(), id);
+    }
+
+    fn refer(&mut self, declaration: DeclarationId, span: Span, is_assignment: bool) {
+        self.resolution.references.push(Reference { declaration, span, is_assignment });
+    }
+
+    fn lookup(&self, name: &str) -> Option<DeclarationId> {
+        self.scopes.iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    /// Returns the tokens that lie within a span.
+    fn tokens_in(&self, span: Span) -> impl Iterator<Item = &Token> {
+        let start = self.tokens.partition_point(|token| token.span.start() < span.start());
+
+        self.tokens[start..].iter()
+            .take_while(move |token| token.span.end() <= span.end())
+    }
+
+    /// Returns the identifier tokens that lie within a span.
+    fn identifiers(&self, span: Span) -> impl Iterator<Item = &Token> {
+        self.tokens_in(span)
+            .filter(|token| matches!(token.token_type, TokenType::Identifier(_)))
+    }
+
+    /// Returns the span of a function's body, which reaches until the next declaration if the
+    /// body is not closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The span of the function.
+    /// * `next` - The span of the node after the function, if there is one.
+    fn body_span(&self, span: Span, next: Option<Span>) -> Span {
+        let mut tokens = self.tokens_in(span).skip_while(|token| token.token_type != TokenType::LBrace);
+        let Some(open) = tokens.next() else {
+            return span
+        };
+
+        let end = match tokens.last() {
+            Some(close) if close.token_type == TokenType::RBrace => close.span.end(),
+            _ => next.map_or(ByteIndex(END_OF_INPUT), |next| next.start()),
+        };
+
+        Span::new(open.span.start(), end)
+    }
+
+    /// Returns the span of the first token of a node, which is the name of assignments and
+    /// function calls.
+    fn first_token(&self, span: Span) -> Span {
+        self.tokens_in(span).next().map_or(span, |token| token.span)
+    }
+}
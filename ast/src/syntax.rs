@@ -0,0 +1,286 @@
+use codespan::Span;
+use crate::AstNode;
+use crate::lexer::{Token, TokenType};
+
+/// The kind of a token in the concrete syntax tree.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TokenKind {
+    /// A run of spaces, tabs and line breaks
+    Whitespace,
+    /// A `//` comment, without the line break that ends it
+    Comment,
+    /// Text the lexer could not make a token of
+    Unknown,
+    /// A token the parser reads
+    Token(TokenType),
+}
+
+/// A token of the concrete syntax tree, which may be trivia that the parser skips.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxToken {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl SyntaxToken {
+    /// Returns whether the token is whitespace, a comment or unknown text, which the parser
+    /// never sees.
+    pub fn is_trivia(&self) -> bool {
+        !matches!(self.kind, TokenKind::Token(_))
+    }
+
+    /// Returns the text of the token.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.span.start().to_usize()..self.span.end().to_usize()]
+    }
+}
+
+/// The kind of a node in the concrete syntax tree.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NodeKind {
+    /// The whole input
+    Document,
+    FuncDeclaration,
+    /// The parenthesized parameters of a function declaration
+    ParamList,
+    /// A type and name in a parameter list
+    Param,
+    /// A function body, including its braces
+    Block,
+    VarDeclaration,
+    VarAssignment,
+    Return,
+    FunctionCall,
+    /// The parenthesized arguments of a function call
+    ArgList,
+    BinaryOp,
+    UnaryOp,
+    /// An expression in parentheses
+    Paren,
+    Value,
+    /// An attribute in front of a function declaration, such as `@allow(W001)`
+    Attribute,
+    /// A statement that could not be parsed
+    Error,
+}
+
+/// An element of the concrete syntax tree.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// Represents a node in the lossless concrete syntax tree (CST).
+///
+/// Unlike the AST, the CST keeps every byte of the input: every token, the whitespace and
+/// comments between them, and the text that could not be lexed or parsed. Trivia between the
+/// tokens of a node belongs to the node, while trivia in front of its first or after its last
+/// token belongs to its parent, so the `Document` node covers the whole input.
+///
+/// The AST is built alongside the CST rather than on top of it, so its nodes are separate
+/// values. `AstNode::syntax` finds the CST node an AST node was parsed from by its span.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub span: Span,
+    /// The nodes and tokens the node consists of, in source order
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    /// Returns the text of the node, including the trivia inside of it.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.span.start().to_usize()..self.span.end().to_usize()]
+    }
+
+    /// Returns the nodes among the children of the node.
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Returns every token within the node in source order, including trivia.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// Returns the innermost node of a kind that has exactly the given span.
+    pub fn find(&self, kind: NodeKind, span: Span) -> Option<&SyntaxNode> {
+        if self.span.start() > span.start() || self.span.end() < span.end() {
+            return None
+        }
+
+        self.child_nodes()
+            .find_map(|node| node.find(kind, span))
+            .or_else(|| (self.kind == kind && self.span == span).then_some(self))
+    }
+}
+
+impl AstNode {
+    /// Returns the node of the concrete syntax tree that the node was parsed from, which is
+    /// searched for as the innermost CST node of the matching kind with the same span.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The `Document` node of the CST the AST was parsed with.
+    ///
+    /// # Returns
+    ///
+    /// Returns the CST node, or `None` for nodes that have no span, which are blocks and
+    /// identifiers, or nodes that did not come from the tree.
+    pub fn syntax<'a>(&self, root: &'a SyntaxNode) -> Option<&'a SyntaxNode> {
+        let kind = match self {
+            AstNode::Empty | AstNode::Block { .. } | AstNode::Identifier { .. } => return None,
+            AstNode::Document { .. } => return Some(root),
+            AstNode::FunctionCall { .. } => NodeKind::FunctionCall,
+            AstNode::FuncDeclaration { .. } => NodeKind::FuncDeclaration,
+            AstNode::VarDeclaration { .. } => NodeKind::VarDeclaration,
+            AstNode::VarAssignment { .. } => NodeKind::VarAssignment,
+            AstNode::Return { .. } => NodeKind::Return,
+            AstNode::Value { .. } => NodeKind::Value,
+            AstNode::BinaryOp { .. } => NodeKind::BinaryOp,
+            AstNode::UnaryOp { .. } => NodeKind::UnaryOp,
+        };
+
+        root.find(kind, self.span()?)
+    }
+}
+
+/// A node recorded by the parser, as a range of the tokens it read.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Marker {
+    pub kind: NodeKind,
+    /// The index of the first token of the node
+    pub start: usize,
+    /// The index after the last token of the node
+    pub end: usize,
+}
+
+/// Splits the input into tokens without losing any of it, by filling the gaps between the
+/// tokens the parser reads with trivia.
+///
+/// # Arguments
+///
+/// * `input` - The text the tokens were lexed from.
+/// * `tokens` - The tokens the parser reads, in source order.
+///
+/// # Returns
+///
+/// Returns the tokens and trivia, whose spans cover the input without gaps.
+pub fn lossless_tokens(input: &str, tokens: &[Token]) -> Vec<SyntaxToken> {
+    let mut lossless = Vec::with_capacity(tokens.len() * 2);
+    let mut offset = 0;
+
+    for token in tokens {
+        split_trivia(input, offset, token.span.start().to_usize(), &mut lossless);
+        lossless.push(SyntaxToken { kind: TokenKind::Token(token.token_type.clone()), span: token.span });
+        offset = token.span.end().to_usize();
+    }
+    split_trivia(input, offset, input.len(), &mut lossless);
+
+    lossless
+}
+
+/// Splits the text between two tokens into whitespace, comments and unknown text.
+fn split_trivia(input: &str, start: usize, end: usize, out: &mut Vec<SyntaxToken>) {
+    let mut offset = start;
+
+    while offset < end {
+        let rest = &input[offset..end];
+        let (kind, length) = if rest.starts_with("//") {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with(char::is_whitespace) {
+            (TokenKind::Whitespace, rest.find(|char: char| !char.is_whitespace()).unwrap_or(rest.len()))
+        } else {
+            let length = rest.char_indices()
+                .find(|(index, char)| char.is_whitespace() || rest[*index..].starts_with("//"))
+                .map_or(rest.len(), |(index, _)| index);
+            (TokenKind::Unknown, length)
+        };
+
+        out.push(SyntaxToken { kind, span: Span::new(offset as u32, (offset + length) as u32) });
+        offset += length;
+    }
+}
+
+/// Assembles the concrete syntax tree from the nodes the parser recorded.
+///
+/// # Arguments
+///
+/// * `input` - The text the tokens were lexed from.
+/// * `tokens` - The tokens the parser read.
+/// * `markers` - The nodes the parser recorded, which nest within each other.
+pub(crate) fn build_tree(input: &str, tokens: &[Token], markers: &[Marker]) -> SyntaxNode {
+    let lossless = lossless_tokens(input, tokens);
+
+    // the index of every token the parser read among the lossless tokens
+    let positions: Vec<usize> = lossless.iter()
+        .enumerate()
+        .filter(|(_, token)| !token.is_trivia())
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut markers: Vec<Marker> = markers.iter()
+        .filter(|marker| marker.start < marker.end)
+        .map(|marker| Marker {
+            kind: marker.kind,
+            start: positions[marker.start],
+            end: positions[marker.end - 1] + 1,
+        })
+        .collect();
+    // parents come before their children
+    markers.sort_by_key(|marker| (marker.start, std::cmp::Reverse(marker.end)));
+
+    let mut builder = TreeBuilder { tokens: lossless, markers, next: 0 };
+    builder.build(NodeKind::Document, 0, builder.tokens.len())
+}
+
+struct TreeBuilder {
+    tokens: Vec<SyntaxToken>,
+    markers: Vec<Marker>,
+    /// The index of the next marker to build
+    next: usize,
+}
+
+impl TreeBuilder {
+    fn build(&mut self, kind: NodeKind, start: usize, end: usize) -> SyntaxNode {
+        let mut children = Vec::new();
+        let mut position = start;
+
+        while position < end {
+            match self.markers.get(self.next) {
+                Some(marker) if marker.start == position && marker.end <= end => {
+                    let marker = *marker;
+                    self.next += 1;
+                    children.push(SyntaxElement::Node(self.build(marker.kind, marker.start, marker.end)));
+                    position = marker.end;
+                }
+                _ => {
+                    children.push(SyntaxElement::Token(self.tokens[position].clone()));
+                    position += 1;
+                }
+            }
+        }
+
+        let span = match (self.tokens.get(start), end.checked_sub(1).and_then(|last| self.tokens.get(last))) {
+            (Some(first), Some(last)) if start < end => Span::new(first.span.start(), last.span.end()),
+            _ => Span::initial(),
+        };
+
+        SyntaxNode { kind, span, children }
+    }
+}
@@ -0,0 +1,75 @@
+use ast::checker::Checker;
+use ast::lexer::Lexer;
+use ast::parser::Parser;
+use common::features::Features;
+
+/// Checks a source, returning the code, message and the text of the primary span of every
+/// diagnostic.
+fn check(source: &str) -> Vec<(String, String, String)> {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let document = Parser::new(tokens).parse();
+
+    Checker::new("test.zpp", source).quiet()
+        .with_features(Features::all())
+        .check_with_suggestions(&document)
+        .into_iter()
+        .map(|(diagnostic, _)| {
+            let span = &diagnostic.labels[0].range;
+            (diagnostic.code.unwrap(), diagnostic.message, source[span.clone()].to_string())
+        })
+        .collect()
+}
+
+fn diagnostic(code: &str, message: &str, text: &str) -> (String, String, String) {
+    (code.to_string(), message.to_string(), text.to_string())
+}
+
+#[test]
+fn static_declarations_need_their_feature() {
+    let source = "static int SIZE = 1024\nfunc f() {\n    static float HALF = SIZE / 2\n}\n";
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let document = Parser::new(tokens).parse();
+
+    let errors = Checker::new("test.zpp", source).quiet()
+        .check_with_suggestions(&document);
+    let spans: Vec<(&str, &str)> = errors.iter()
+        .map(|(diagnostic, _)| (diagnostic.code.as_deref().unwrap(), &source[diagnostic.labels[0].range.clone()]))
+        .collect();
+    assert_eq!(spans, [("E018", "static"), ("E018", "static")]);
+
+    assert_eq!(check(source), []);
+}
+
+#[test]
+fn statics_cannot_depend_on_runtime_values() {
+    let source = "int count = 2\nstatic int DOUBLE = count * 2\nfunc f(int n) {\n    static int HALF = n / 2\n}\n";
+
+    assert_eq!(check(source), [
+        diagnostic("E009", "The static `DOUBLE` cannot be evaluated at compile time because it depends on the runtime value `count`", "count"),
+        diagnostic("E009", "The static `HALF` cannot be evaluated at compile time because it depends on the runtime value `n`", "n"),
+    ]);
+}
+
+#[test]
+fn statics_that_overflow_or_divide_by_zero_are_reported() {
+    let source = "static int BIG = 2147483647\nstatic int MIN = -BIG - 1\nstatic int QUOTIENT = MIN / -1\nstatic int WRAPPED = BIG + 1\n";
+
+    // integer arithmetic wraps, only the division is undefined
+    assert_eq!(check(source), [
+        diagnostic("E011", "The static `QUOTIENT` cannot be evaluated at compile time because `/` overflows or divides by zero", "MIN / -1"),
+    ]);
+}
+
+#[test]
+fn statics_used_as_a_divisor_of_zero_are_reported() {
+    let source = "static int ZERO = 0\nstatic int RATIO = 10 / ZERO\nstatic int LATER = RATIO + 1\nfunc f() {\n    static int SCALED = 5 / (ZERO * 3)\n}\n";
+
+    // `LATER` depends on `RATIO`, which has no value, but is only reported once
+    assert_eq!(check(source), [
+        diagnostic("E011", "The static `RATIO` cannot be evaluated at compile time because `/` overflows or divides by zero", "10 / ZERO"),
+        diagnostic("E011", "The static `SCALED` cannot be evaluated at compile time because `/` overflows or divides by zero", "5 / (ZERO * 3)"),
+    ]);
+
+    // floats can be divided by zero
+    assert_eq!(check("static float ZERO = 0.0\nstatic float INFINITE = 1.0 / ZERO\n"), []);
+}
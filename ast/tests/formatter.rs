@@ -0,0 +1,78 @@
+use ast::formatter::Formatter;
+use ast::lexer::{Lexer, TokenType};
+
+fn format(source: &str) -> String {
+    Formatter::new("test.zpp", source).quiet().format().unwrap()
+}
+
+fn token_types(source: &str) -> Vec<TokenType> {
+    Lexer::new("test.zpp", source).tokenize().into_iter().map(|token| token.token_type).collect()
+}
+
+const MESSY: &str = "// sums two numbers
+func   add(int a,int b)
+{
+int sum=a+b // the sum
+
+
+
+}
+mut  int   x=( 1 +-2 )*  -3
+add( x ,-2 )
+static bool   flag =!true&&x>=2
+
+
+func empty( ) {  }
+func commented() {
+// nothing yet
+}
+print(x  ) // done
+";
+
+#[test]
+fn code_is_formatted_in_the_canonical_style() {
+    assert_eq!(format(MESSY), "// sums two numbers
+func add(int a, int b) {
+    int sum = a + b // the sum
+}
+mut int x = (1 + -2) * -3
+add(x, -2)
+static bool flag = !true && x >= 2
+
+func empty() {}
+func commented() {
+    // nothing yet
+}
+print(x) // done
+");
+}
+
+#[test]
+fn formatting_is_idempotent_and_keeps_the_tokens() {
+    let formatted = format(MESSY);
+
+    assert_eq!(format(&formatted), formatted);
+    assert_eq!(token_types(&formatted), token_types(MESSY));
+}
+
+#[test]
+fn comments_within_statements_continue_on_the_next_line() {
+    let source = "int x = 1 + // one\n2\nfunc f(int a, // first\n  int b) {\n  // c\n\n  int c = a}";
+
+    assert_eq!(format(source), "int x = 1 + // one\n    2\nfunc f(int a, // first\n    int b) {\n    // c\n\n    int c = a\n}\n");
+}
+
+#[test]
+fn code_with_syntax_errors_is_not_formatted() {
+    assert!(Formatter::new("test.zpp", "int x = = 1").quiet().format().is_err());
+    assert!(Formatter::new("test.zpp", "func f() {").quiet().format().is_err());
+}
+
+#[test]
+fn attributes_are_put_on_lines_of_their_own() {
+    let source = "@allow( W001 )  @deny(float-comparison,W001) func f(float a) {\n  bool b = a == 1.0\n}\n";
+    let formatted = format(source);
+
+    assert_eq!(formatted, "@allow(W001)\n@deny(float-comparison, W001)\nfunc f(float a) {\n    bool b = a == 1.0\n}\n");
+    assert_eq!(format(&formatted), formatted);
+}
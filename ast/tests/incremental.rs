@@ -0,0 +1,113 @@
+use std::rc::Rc;
+use ast::incremental::Document;
+use ast::lexer::{Lexer, TokenType};
+use ast::parser::Parser;
+
+const SOURCE: &str = "\
+// counts things
+static int LIMIT = 10
+int count = 0
+
+func bump(int step) {
+    count = count + step * LIMIT
+    print(\"bumped\")
+}
+
+func reset() {
+    count = 0
+}
+bump(2)
+reset()
+";
+
+/// Asserts that a document was lexed and parsed to the same result as a full parse would.
+fn assert_matches_full_parse(document: &Document) {
+    let text = document.text();
+    let (tokens, lex_errors) = Lexer::new("test.zpp", text).quiet().tokenize_with_errors();
+    let mut parser = Parser::new(tokens.clone());
+    let ast = parser.parse();
+
+    let token_spans = |tokens: &[ast::lexer::Token]| -> Vec<(TokenType, u32, u32)> {
+        tokens.iter().map(|token| (token.token_type.clone(), token.span.start().0, token.span.end().0)).collect()
+    };
+    assert_eq!(token_spans(&document.tokens()), token_spans(&tokens), "tokens of {:?}", text);
+    assert_eq!(*document.ast(), ast, "AST of {:?}", text);
+    assert_eq!(document.syntax_errors(), parser.errors(), "syntax errors of {:?}", text);
+    assert_eq!(document.lex_errors().len(), lex_errors.len(), "lex errors of {:?}", text);
+}
+
+#[test]
+fn edits_in_a_function_body_only_reparse_that_function() {
+    let mut document = Document::new(SOURCE);
+
+    let offset = SOURCE.find("count = 0\n}").unwrap() + "count = ".len();
+    let reparse = document.edit(offset..offset + 1, "count + 1");
+
+    assert_eq!(document.text(), SOURCE.replace("count = 0\n}", "count = count + 1\n}"));
+    // the items are the static, `count`, `bump`, `reset`, the two calls and the trailing trivia
+    assert_eq!(reparse.items, 3..4);
+    // `func reset() { count = count + 1 }`
+    assert_eq!(reparse.relexed, 11);
+    assert_matches_full_parse(&document);
+}
+
+#[test]
+fn edits_that_change_the_structure_reparse_until_it_is_restored() {
+    let mut document = Document::new(SOURCE);
+
+    // without its `}`, `bump` takes the statements up to the next function
+    let close = SOURCE.find("}\n\nfunc reset").unwrap();
+    document.edit(close..close + 1, "");
+    assert_matches_full_parse(&document);
+
+    document.edit(close..close, "}");
+    assert_eq!(document.text(), SOURCE);
+    assert_matches_full_parse(&document);
+
+    // an unterminated string swallows the rest of the line
+    let quote = SOURCE.find("\"bumped\"").unwrap();
+    document.edit(quote..quote + 1, "");
+    assert_matches_full_parse(&document);
+}
+
+#[test]
+fn tokens_and_ast_are_assembled_once_per_edit() {
+    let mut document = Document::new(SOURCE);
+    let (tokens, ast) = (document.tokens(), document.ast());
+    assert!(Rc::ptr_eq(&tokens, &document.tokens()));
+    assert!(Rc::ptr_eq(&ast, &document.ast()));
+
+    let offset = SOURCE.find("bump(2)").unwrap() + "bump(".len();
+    document.edit(offset..offset + 1, "3");
+    assert!(!Rc::ptr_eq(&tokens, &document.tokens()));
+    assert_ne!(ast, document.ast());
+    assert_matches_full_parse(&document);
+}
+
+#[test]
+fn any_edit_gives_the_result_of_a_full_parse() {
+    let insertions = ["", "x", " ", "\n", "}", "{", "(", ")", "func ", "= ", "// note\n", "\"", "$", "int y = 1\n", "+ 2"];
+    let mut document = Document::new(SOURCE);
+    let mut seed: u64 = 7;
+    let mut random = |bound: usize| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) as usize % bound.max(1)
+    };
+
+    for _ in 0..2000 {
+        let length = document.text().len();
+        let start = random(length + 1);
+        let end = (start + random(4)).min(length);
+        let insertion = insertions[random(insertions.len())];
+
+        document.edit(start..end, insertion);
+        assert_matches_full_parse(&document);
+
+        // keep the document from drifting too far from valid code
+        if document.text().len() > 2 * SOURCE.len() || random(20) == 0 {
+            let length = document.text().len();
+            document.edit(0..length, SOURCE);
+            assert_matches_full_parse(&document);
+        }
+    }
+}
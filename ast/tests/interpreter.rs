@@ -0,0 +1,134 @@
+use ast::{AstNode, Type, TypedValue};
+use ast::checker::{Checker, Environment};
+use ast::interpreter::Interpreter;
+use ast::lexer::Lexer;
+use ast::parser::Parser;
+use common::features::Features;
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let mut parser = Parser::new(tokens);
+
+    match parser.parse() {
+        AstNode::Document { body } => match *body {
+            AstNode::Block { body } => body,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn printed(interpreter: &Interpreter<Vec<u8>>) -> Vec<String> {
+    String::from_utf8(interpreter.output().clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn functions_statics_and_operators() {
+    let source = r#"
+        static int SIZE = 4 * 1024
+        float ratio = 4
+        func show(int n, float f) {
+            static int OFFSET = SIZE * 2 + 1
+            print(n * 3 - OFFSET)
+            print(f / ratio)
+            print(-n)
+        }
+        int big = 2147483647
+        show(SIZE - 1, 2.5)
+        print(big + 1)
+        print(!(1 < 2) || SIZE == 4096)
+    "#;
+
+    let body = parse(source);
+    Checker::new("test.zpp", source)
+        .with_features(Features::all())
+        .check_body(&body)
+        .expect("the test program should pass the checker");
+
+    let mut interpreter = Interpreter::new(Vec::new());
+    interpreter.run(&body).unwrap();
+
+    assert_eq!(printed(&interpreter), ["4092", "0.625", "-4095", "-2147483648", "true"]);
+}
+
+#[test]
+fn state_persists_between_inputs() {
+    let mut environment = Environment::default();
+    let mut interpreter = Interpreter::new(Vec::new());
+
+    for input in ["mut int count = 2", "func bump(int by) {\n count = count + by\n}", "bump(40)"] {
+        let body = parse(input);
+        environment = Checker::with_environment("test.zpp", input, environment)
+            .check_body(&body)
+            .expect("every input should pass the checker");
+        interpreter.run(&body).unwrap();
+    }
+
+    let tokens = Lexer::new("test.zpp", "count / 4").tokenize();
+    let expression = Parser::new(tokens).parse_bare_expression().unwrap();
+    let found = Checker::with_environment("test.zpp", "count / 4", environment)
+        .check_expression(&expression)
+        .unwrap();
+
+    assert_eq!(found, Type::Int);
+    assert_eq!(interpreter.evaluate(&expression), Ok(TypedValue::IntVal(10)));
+}
+
+#[test]
+fn division_by_zero_is_a_runtime_error() {
+    let source = "int zero = 0\nint result = 1 / zero";
+    let mut interpreter = Interpreter::new(Vec::new());
+
+    let err = interpreter.run(&parse(source)).unwrap_err();
+    assert_eq!(err.span.start().0 as usize, source.find("1 / zero").unwrap());
+}
+
+#[test]
+fn return_leaves_the_function() {
+    let source = "func greet(int n) {\n    print(n)\n    return\n    print(n + 1)\n}\ngreet(1)\ngreet(2)";
+    let body = parse(source);
+    Checker::new("test.zpp", source)
+        .check_body(&body)
+        .expect("the test program should pass the checker");
+
+    let mut interpreter = Interpreter::new(Vec::new());
+    interpreter.run(&body).unwrap();
+
+    assert_eq!(printed(&interpreter), ["1", "2"]);
+    assert!(Checker::new("test.zpp", "return").quiet().check_body(&parse("return")).is_err());
+}
+
+#[test]
+fn restoring_a_snapshot_undoes_a_failed_run() {
+    let mut interpreter = Interpreter::new(Vec::new());
+    interpreter.run(&parse("mut int count = 1")).unwrap();
+
+    let snapshot = interpreter.snapshot();
+    let failing = parse("func reset() {\n count = 0\n}\ncount = 5\nint zero = 0\nint result = 1 / zero");
+    assert!(interpreter.run(&failing).is_err());
+    assert_eq!(interpreter.global("count"), Some(&TypedValue::IntVal(5)));
+
+    interpreter.restore(snapshot);
+    assert_eq!(interpreter.global("count"), Some(&TypedValue::IntVal(1)));
+    assert_eq!(interpreter.global("zero"), None);
+    assert_eq!(interpreter.global("result"), None);
+}
+
+#[test]
+fn literals_keep_their_type() {
+    let source = "print(7 / 2.0)\nfloat third = 1 / 3.0\nprint(third)\nint exact = 16777217\nprint(exact + 1)\nprint(2147483647)";
+    let body = parse(source);
+    Checker::new("test.zpp", source)
+        .check_body(&body)
+        .expect("the test program should pass the checker");
+
+    let mut interpreter = Interpreter::new(Vec::new());
+    interpreter.run(&body).unwrap();
+
+    // `2.0` is a float although it has no fraction, and integers are not rounded to a float
+    assert_eq!(printed(&interpreter), ["3.5", "0.33333334", "16777218", "2147483647"]);
+}
@@ -0,0 +1,113 @@
+use ast::lexer::Lexer;
+use ast::lints::{lints, Linter};
+use ast::parser::Parser;
+use ast::resolver::resolve;
+use common::codes::Code;
+use common::fix::apply_suggestions;
+use common::levels::{Level, LintLevels};
+
+/// Lints a source at the given levels, returning the codes and messages of the findings.
+fn lint_at(source: &str, levels: LintLevels) -> Vec<(String, String)> {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let document = Parser::new(tokens.clone()).parse();
+    let resolution = resolve(&document, &tokens);
+
+    Linter::new("test.zpp", source).quiet()
+        .with_levels(levels)
+        .lint(&document, &resolution)
+        .into_iter()
+        .map(|(diagnostic, _)| (diagnostic.code.unwrap(), diagnostic.message))
+        .collect()
+}
+
+fn lint(source: &str) -> Vec<(String, String)> {
+    lint_at(source, LintLevels::new())
+}
+
+/// Applies the fixes of the findings of a source once.
+fn fix(source: &str) -> String {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let document = Parser::new(tokens.clone()).parse();
+    let resolution = resolve(&document, &tokens);
+
+    let suggestions: Vec<_> = Linter::new("test.zpp", source).quiet()
+        .lint(&document, &resolution)
+        .into_iter()
+        .flat_map(|(_, suggestions)| suggestions)
+        .collect();
+
+    apply_suggestions(source, &suggestions).0
+}
+
+fn finding(code: &str, message: &str) -> (String, String) {
+    (code.to_string(), message.to_string())
+}
+
+#[test]
+fn every_lint_has_a_documented_warning_code() {
+    for lint in lints() {
+        let info = lint.code().info();
+        assert!(info.code.as_str().starts_with('W'), "{} is not a warning", info.code);
+        assert!(info.explanation.contains("zxx lint"), "{} does not mention `zxx lint`", info.code);
+    }
+}
+
+#[test]
+fn unused_declarations_are_found() {
+    let source = "mut int total = 0\nint spare = 1\ntotal = 2\nfunc add(int a, int b, int _c) {\n    int sum = 1\n    print(a)\n}\nfunc rec(int n) {\n    rec(n)\n}\nfunc Main() {\n    add(1, 2, 3)\n}\n";
+
+    assert_eq!(lint(source), [
+        finding("W002", "The variable `total` is assigned to, but never read"),
+        finding("W002", "The variable `spare` is never used"),
+        finding("W004", "The parameter `b` is never read"),
+        finding("W002", "The variable `sum` is never used"),
+        finding("W003", "The function `rec` is never called"),
+    ]);
+}
+
+#[test]
+fn shadowing_only_counts_globals_declared_before_the_function() {
+    let source = "int x = 1\nprint(x)\nfunc f(int x) {\n    print(x)\n}\nfunc g() {\n    mut int y = 2\n    y = 3\n    print(y)\n}\nmut int y = 4\ny = 5\nprint(y)\nf(1)\ng()\n";
+
+    assert_eq!(lint(source), [finding("W005", "The parameter `x` shadows a global variable")]);
+}
+
+#[test]
+fn mutable_variables_must_be_reassigned() {
+    let source = "func f(int a) {\n    mut int b = a + 1\n    mut int c = 2\n    c = c * 2\n    mut int d = 3\n    print(b + c)\n}\nf(1)\n";
+
+    assert_eq!(lint(source), [
+        finding("W006", "The variable `b` is declared mutable, but never reassigned"),
+        finding("W002", "The variable `d` is never used"),
+    ]);
+}
+
+#[test]
+fn unreachable_code_and_empty_blocks_are_found() {
+    let source = "func f() {\n    return\n    print(1)\n    print(2)\n}\nfunc g() {}\nfunc h() {\n    // nothing yet\n}\nf()\ng()\nh()\n";
+
+    assert_eq!(lint(source), [
+        finding("W007", "This code is never run"),
+        finding("W008", "The body of `g` is empty"),
+    ]);
+}
+
+#[test]
+fn findings_are_reported_at_their_levels() {
+    let source = "@allow(unused-parameter)\nfunc f(int a) {\n    print(1)\n}\nfunc g(int b) {\n    print(2)\n}\nf(1)\ng(2)\n";
+    assert_eq!(lint(source), [finding("W004", "The parameter `b` is never read")]);
+
+    let mut levels = LintLevels::new();
+    levels.set(Code::W004, Level::Allow).unwrap();
+    assert_eq!(lint_at(source, levels), []);
+}
+
+#[test]
+fn fixes_remove_rename_and_make_immutable() {
+    let source = "int unused = 1\nmut  int limit = 2\nprint(limit)\nfunc f(int a) {\n    print(1)\n    return\n    print(2) // gone\n}\nf(1)\n";
+
+    assert_eq!(
+        fix(source),
+        "int limit = 2\nprint(limit)\nfunc f(int _a) {\n    print(1)\n    return\n    // gone\n}\nf(1)\n",
+    );
+}
@@ -0,0 +1,93 @@
+use ast::Type;
+use ast::lexer::{doc_comment, Lexer, TokenType, KEYWORDS};
+use ast::parser::Parser;
+use ast::resolver::{resolve, DeclarationKind, Resolution};
+
+fn resolve_source(source: &str) -> Resolution {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let document = Parser::new(tokens.clone()).parse();
+
+    resolve(&document, &tokens)
+}
+
+/// Returns the byte range of the `nth` occurrence of `name` in `source`.
+fn find(source: &str, name: &str, nth: usize) -> (usize, usize) {
+    let start = source.match_indices(name).nth(nth).unwrap().0;
+    (start, start + name.len())
+}
+
+fn range_of(span: codespan::Span) -> (usize, usize) {
+    (span.start().to_usize(), span.end().to_usize())
+}
+
+#[test]
+fn names_resolve_to_the_innermost_declaration() {
+    let source = "int count = 1\nfunc add(int count, float step) {\n    count = count + 1\n}\nadd(count, 2)";
+    let resolution = resolve_source(source);
+
+    let kinds: Vec<(&str, DeclarationKind)> = resolution.declarations.iter()
+        .map(|declaration| (declaration.name.as_str(), declaration.kind))
+        .collect();
+    assert_eq!(kinds, vec![
+        ("add", DeclarationKind::Function),
+        ("count", DeclarationKind::Variable),
+        ("count", DeclarationKind::Parameter),
+        ("step", DeclarationKind::Parameter),
+    ]);
+
+    let parameter = &resolution.declarations[2];
+    assert_eq!(range_of(parameter.span), find(source, "count", 1));
+    assert_eq!(parameter.var_type, Type::Int);
+    assert_eq!(range_of(resolution.declarations[3].span), find(source, "step", 0));
+
+    // the body refers to the parameter, the call to the global
+    let uses: Vec<(usize, usize)> = resolution.references_to(2).map(|reference| range_of(reference.span)).collect();
+    assert_eq!(uses, vec![find(source, "count", 2), find(source, "count", 3)]);
+    assert_eq!(resolution.declaration_at(find(source, "count", 4).0), Some(1));
+
+    // calls resolve to functions declared after them as well
+    let (start, end) = find(source, "add", 1);
+    assert_eq!(resolution.declaration_at(end), Some(0));
+    assert_eq!(resolution.declaration_at(start), Some(0));
+}
+
+#[test]
+fn doc_comments_are_read_from_the_lines_above() {
+    let source = "// not a doc comment\n/// Counts things.\n///\n///   Indented.\nint count = 1 // trailing\nint other = count";
+    let resolution = resolve_source(source);
+
+    let count = &resolution.declarations[0];
+    assert_eq!(doc_comment(source, count.node_span.start().to_usize()).as_deref(), Some("Counts things.\n\n  Indented."));
+
+    let other = &resolution.declarations[1];
+    assert_eq!(doc_comment(source, other.node_span.start().to_usize()), None);
+}
+
+#[test]
+fn visibility_follows_scopes_in_incomplete_code() {
+    let source = "int before = 1\nfunc first(int step) {\n    int local = step\n    loc\nfunc second() {\n}\nint after = 2";
+    let resolution = resolve_source(source);
+
+    let names_at = |offset: usize| -> Vec<&str> {
+        resolution.visible_at(offset).into_iter()
+            .map(|id| resolution.declarations[id].name.as_str())
+            .collect()
+    };
+
+    let (_, typing) = find(source, "loc", 1);
+    assert_eq!(names_at(typing), vec!["first", "second", "before", "step", "local"]);
+
+    // the body of `first` is never closed, so it ends where `second` begins, and globals
+    // declared after a function are not visible in its body
+    let (_, open) = find(source, "second() {", 0);
+    assert_eq!(names_at(open), vec!["first", "second", "before"]);
+    assert_eq!(names_at(source.len()), vec!["first", "second", "before", "after"]);
+}
+
+#[test]
+fn keywords_are_not_identifiers() {
+    for keyword in KEYWORDS {
+        let tokens = Lexer::new("test.zpp", keyword).tokenize();
+        assert!(!matches!(tokens[0].token_type, TokenType::Identifier(_)), "`{}` is lexed as a name", keyword);
+    }
+}
@@ -0,0 +1,121 @@
+use ast::AstNode;
+use ast::lexer::Lexer;
+use ast::parser::Parser;
+use ast::syntax::{NodeKind, SyntaxElement, SyntaxNode, TokenKind};
+
+fn parse(source: &str) -> (AstNode, SyntaxNode) {
+    let (tokens, _) = Lexer::new("test.zpp", source).quiet().tokenize_with_errors();
+    let mut parser = Parser::new(tokens);
+    let document = parser.parse();
+
+    (document, parser.syntax_tree(source))
+}
+
+/// Prints the kinds of the nodes of a tree, indented by their depth.
+fn outline(node: &SyntaxNode, depth: usize, out: &mut String) {
+    out.push_str(&format!("{}{:?}\n", "  ".repeat(depth), node.kind));
+    for child in node.child_nodes() {
+        outline(child, depth + 1, out);
+    }
+}
+
+#[test]
+fn the_tree_reproduces_the_input_exactly() {
+    let sources = [
+        "",
+        "  \n// only a comment\n",
+        "// header\nstatic int   SIZE = (2 + 3) * -4 // trailing\nfunc  add(int a,  int b) {\n\tb = a+b\n}\n\nadd( SIZE , 1 )",
+        "int x = = 1\nfunc f( {\n  y = $ 2\n}\nprint(\"unterminated)\n",
+        "func open() {\n  int a = 1\n",
+    ];
+
+    for source in sources {
+        let (_, tree) = parse(source);
+        let text: String = tree.tokens().iter().map(|token| token.text(source)).collect();
+
+        assert_eq!(text, source);
+        assert_eq!(tree.text(source), source);
+    }
+}
+
+#[test]
+fn nodes_nest_like_the_grammar() {
+    let source = "static int SIZE = (2 + 3) * -4\nfunc add(int a, int b) {\n    b = a\n}\nadd(SIZE, 1)";
+    let (_, tree) = parse(source);
+
+    let mut out = String::new();
+    outline(&tree, 0, &mut out);
+    assert_eq!(out, "\
+Document
+  VarDeclaration
+    BinaryOp
+      Paren
+        BinaryOp
+          Value
+          Value
+      UnaryOp
+        Value
+  FuncDeclaration
+    ParamList
+      Param
+      Param
+    Block
+      VarAssignment
+        Value
+  FunctionCall
+    ArgList
+      Value
+      Value
+");
+
+    let declaration = tree.child_nodes().next().unwrap();
+    assert_eq!(declaration.text(source), "static int SIZE = (2 + 3) * -4");
+    assert!(matches!(declaration.children[1], SyntaxElement::Token(ref token) if token.kind == TokenKind::Whitespace));
+}
+
+#[test]
+fn trivia_and_errors_are_kept_in_the_tree() {
+    let source = "int x = = 1 // broken\nint y = 2";
+    let (_, tree) = parse(source);
+
+    let kinds: Vec<NodeKind> = tree.child_nodes().map(|node| node.kind).collect();
+    assert_eq!(kinds, vec![NodeKind::Error, NodeKind::VarDeclaration]);
+    assert_eq!(tree.child_nodes().next().unwrap().text(source), "int x = = 1");
+
+    let comment = tree.tokens().into_iter().find(|token| token.kind == TokenKind::Comment).unwrap();
+    assert_eq!(comment.text(source), "// broken");
+}
+
+#[test]
+fn ast_nodes_are_found_in_the_syntax_tree() {
+    let source = "func twice(int a) {\n    int b = (a) * 2 // doubled\n}";
+    let (document, tree) = parse(source);
+
+    let AstNode::Document { body } = &document else { panic!() };
+    let AstNode::Block { body } = body.as_ref() else { panic!() };
+    let AstNode::FuncDeclaration { body, .. } = &body[0] else { panic!() };
+    let AstNode::Block { body } = body.as_ref() else { panic!() };
+    let AstNode::VarDeclaration { value, .. } = &body[0] else { panic!() };
+
+    assert_eq!(document.syntax(&tree), Some(&tree));
+    let declaration = body[0].syntax(&tree).unwrap();
+    assert_eq!(declaration.kind, NodeKind::VarDeclaration);
+    assert_eq!(declaration.text(source), "int b = (a) * 2");
+    assert_eq!(value.syntax(&tree).unwrap().text(source), "(a) * 2");
+}
+
+#[test]
+fn integer_literals_out_of_range_are_errors() {
+    let source = "int big = 2147483647\nint bigger = 3000000000\nint huge = 99999999999999999999\nfloat fine = 3000000000.0";
+    let (tokens, _) = Lexer::new("test.zpp", source).quiet().tokenize_with_errors();
+    let mut parser = Parser::new(tokens);
+    let AstNode::Document { body } = parser.parse() else { panic!() };
+    let AstNode::Block { body } = *body else { panic!() };
+
+    // the declarations are still parsed, so that nothing else is reported about them
+    assert_eq!(body.len(), 4);
+    let errors: Vec<(String, &str)> = parser.errors().iter()
+        .map(|error| (error.code.to_string(), &source[error.span.start().to_usize()..error.span.end().to_usize()]))
+        .collect();
+    assert_eq!(errors, [("E017".to_string(), "3000000000"), ("E017".to_string(), "99999999999999999999")]);
+}
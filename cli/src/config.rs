@@ -0,0 +1,77 @@
+use std::process::exit;
+use clap::ArgMatches;
+use common::codes::Code;
+use common::config::Config;
+use common::features::{Feature, Features};
+use common::levels::{Level, LintLevels};
+
+/// The command line flags that set levels, together with the level each sets.
+pub const LEVEL_FLAGS: [(&str, Level); 4] = [
+    ("allow", Level::Allow),
+    ("warn", Level::Warn),
+    ("deny", Level::Deny),
+    ("forbid", Level::Forbid),
+];
+
+/// Returns the levels diagnostic codes are reported at, which are set by the `[lints]` table of
+/// the project config file and then by the command line flags, in the order they are given.
+/// Exits with an error if a level cannot be set.
+pub fn levels(args: &ArgMatches) -> LintLevels {
+    let mut levels = project_config().levels;
+
+    let mut flags: Vec<(usize, Level, &String)> = Vec::new();
+    for (id, level) in LEVEL_FLAGS {
+        if let (Some(indices), Some(values)) = (args.indices_of(id), args.get_many::<String>(id)) {
+            flags.extend(indices.zip(values).map(|(index, value)| (index, level, value)));
+        }
+    }
+    flags.sort_by_key(|(index, ..)| *index);
+
+    for (_, level, value) in flags {
+        let result = value.parse::<Code>()
+            .map_err(|()| format!("`{}` is not a diagnostic code of zxx", value))
+            .and_then(|code| levels.set(code, level).map_err(|error| error.to_string()));
+
+        if let Err(message) = result {
+            eprintln!("Cannot set `--{} {}`: {}", level, value, message);
+            exit(1)
+        }
+    }
+
+    if args.get_flag("deny-warnings") {
+        levels.set_deny_warnings(true);
+    }
+
+    levels
+}
+
+/// Returns the incubating features that are enabled by the `[features]` table of the project
+/// config file and by the `--feature` flags. Exits with an error if a feature does not exist.
+pub fn features(args: &ArgMatches) -> Features {
+    let mut features = project_config().features;
+
+    for name in args.get_many::<String>("feature").into_iter().flatten() {
+        match name.parse::<Feature>() {
+            Ok(feature) => features.enable(feature),
+            Err(()) => {
+                eprintln!("Cannot enable `--feature {}`: it is not a feature of zxx", name);
+                exit(1)
+            }
+        }
+    }
+
+    features
+}
+
+/// Returns the config file of the project the working directory belongs to, or the default
+/// config if there is none. Exits with an error if the file is invalid.
+fn project_config() -> Config {
+    let Some(path) = std::env::current_dir().ok().and_then(|directory| Config::find(&directory)) else {
+        return Config::default()
+    };
+
+    Config::read(&path).unwrap_or_else(|message| {
+        eprintln!("Invalid {}: {}", path.display(), message);
+        exit(1)
+    })
+}
@@ -1,37 +1,574 @@
-use std::fs::File;
-use clap::Arg;
-use std::io::Read;
+mod config;
+mod repl;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::io::{stdin, Read};
 use std::path::Path;
-use ast::lexer::Lexer;
-use ast::parser::Parser;
+use std::process::exit;
+use std::rc::Rc;
+use ast::checker::CheckError;
+use ast::formatter::Formatter;
+use ast::lints::lints;
+use common::codes::Code;
+use common::errors::{ErrorFormat, Reporting, Suggestion};
+use common::fix::{apply_suggestions, unified_diff};
+use common::sarif::SarifLog;
+use common::sink::{DiagnosticSink, Target};
+use codespan_reporting::diagnostic::Severity;
+use codespan_reporting::term::termcolor::ColorChoice;
+use codegen::native::RunError;
+use database::Database;
+use ir::passes::MAX_OPT_LEVEL;
 
+/// Reads a file, exiting with an error if it does not exist or cannot be read as UTF-8.
 fn read_file(path: &str) -> String {
-    let path = Path::new(path);
-    let mut file = File::open(path).unwrap();
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("Cannot read file `{}`: {}", path, err);
+            exit(1)
+        }
+    }
+}
+
+/// The name diagnostics refer to the input read from stdin by.
+const STDIN_NAME: &str = "<stdin>";
+
+/// Reads a file into the database, or stdin if the path is `-`.
+///
+/// # Returns
+///
+/// Returns the name of the file in the database.
+fn load(db: &mut Database, path: &str) -> String {
+    if path != "-" {
+        db.set_source(path, &read_file(path));
+        return path.to_string()
+    }
 
     let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Error reading file.");
+    if let Err(err) = stdin().read_to_string(&mut contents) {
+        eprintln!("Cannot read stdin: {}", err);
+        exit(1)
+    }
+    db.set_virtual_source(STDIN_NAME, &contents);
+
+    STDIN_NAME.to_string()
+}
+
+/// Returns a sink that writes diagnostics to stderr in the format given by `--error-format`,
+/// colored as `--color` asks for.
+fn sink(args: &ArgMatches) -> DiagnosticSink {
+    let format = match args.get_one::<String>("error-format").map(String::as_str) {
+        Some("json") => ErrorFormat::Json,
+        _ => ErrorFormat::Human,
+    };
+    let color = match args.get_one::<String>("color").map(String::as_str) {
+        Some("always") => ColorChoice::Always,
+        Some("never") => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    };
+
+    DiagnosticSink::new(Target::Stderr).with_format(format).with_color(color)
+}
+
+/// Emits the diagnostics of a file that was added to the database to a sink.
+///
+/// # Returns
+///
+/// Returns whether the file has any errors, rather than only warnings.
+fn report(sink: &DiagnosticSink, db: &Database, name: &str) -> bool {
+    emit(sink, db, name, &db.check(name))
+}
 
-    contents
+/// Emits diagnostics of a file that was added to the database to a sink, such as the
+/// findings of the lints.
+///
+/// # Returns
+///
+/// Returns whether any of the diagnostics is an error.
+fn emit(sink: &DiagnosticSink, db: &Database, name: &str, diagnostics: &[CheckError]) -> bool {
+    let file = db.file_id(name).expect("reported files were set");
+    let reporter = Reporting::for_file(db.files(), file).with_sink(sink);
+
+    for (diagnostic, _) in diagnostics.iter() {
+        reporter.emit_diagnostic(diagnostic);
+    }
+
+    diagnostics.iter().any(|(diagnostic, _)| diagnostic.severity >= Severity::Error)
+}
+
+/// Returns a database that reports diagnostics at the levels, and allows the features, the
+/// project config file and the command line set.
+fn database(args: &ArgMatches) -> Database {
+    let mut db = Database::new();
+    db.set_levels(config::levels(args));
+    db.set_features(config::features(args));
+    db
+}
+
+/// Reads a file into the database, and lexes, parses and checks it, exiting if any errors
+/// were reported. The number of errors and warnings is printed after the diagnostics.
+fn front_end(args: &ArgMatches, db: &mut Database, name: &str) {
+    db.set_source(name, &read_file(name));
+
+    let sink = sink(args);
+    let failed = report(&sink, db, name);
+    sink.summarize();
+
+    if failed {
+        exit(1)
+    }
+}
+
+/// Lowers a checked file into IR and optimizes it at the level given by `-O`.
+///
+/// When `dump` is set, the IR is printed to stdout before and after every pass.
+fn middle_end(args: &ArgMatches, db: &Database, name: &str, dump: bool) -> ir::Module {
+    let opt_level = *args.get_one::<u8>("opt-level")
+        .expect("No optimization level provided");
+
+    let mut module = Option::clone(&db.lower(name))
+        .expect("checked files lower");
+
+    if dump && opt_level == 0 {
+        println!(";; IR without optimizations\n{}", module);
+    }
+
+    ir::passes::optimize(&mut module, opt_level, |pass, after, module| {
+        if dump {
+            let when = if after { "after" } else { "before" };
+            println!(";; IR {} {}\n{}", when, pass, module);
+        }
+    });
+
+    module
+}
+
+fn build(args: &ArgMatches) {
+    let name = args.get_one::<String>("file")
+        .expect("No file argument provided");
+    let target = args.get_one::<String>("target")
+        .expect("No target provided");
+
+    let mut db = database(args);
+    front_end(args, &mut db, name);
+
+    let emit_ir = args.get_one::<String>("emit")
+        .is_some_and(|emit| emit == "ir");
+    let module = middle_end(args, &db, name, emit_ir);
+
+    if emit_ir {
+        return
+    }
+
+    let output_path = |extension: &str| match args.get_one::<String>("output") {
+        Some(path) => Path::new(path).with_extension(extension),
+        None => Path::new(name).with_extension(extension),
+    };
+
+    match target.as_str() {
+        "c" => {
+            let output = codegen::c::emit(name, &module);
+            write_file(&output_path("c"), output.as_bytes());
+        }
+        "wasm32" => {
+            let module = codegen::wasm::emit(name, &module);
+            write_file(&output_path("wasm"), &module.binary);
+            write_file(&output_path("wat"), module.text.as_bytes());
+        }
+        _ => unreachable!("clap only accepts known targets"),
+    }
+}
+
+fn run(args: &ArgMatches) {
+    let name = args.get_one::<String>("file")
+        .expect("No file argument provided");
+    let backend = args.get_one::<String>("backend")
+        .expect("No backend provided");
+
+    let Some(mut backend) = codegen::native::backend(backend) else {
+        eprintln!("zxx was built without the `{}` backend, rebuild it with `--features {}`", backend, backend);
+        exit(1)
+    };
+
+    let mut db = database(args);
+    front_end(args, &mut db, name);
+    let module = middle_end(args, &db, name, false);
+
+    match backend.run(&module) {
+        Ok(()) => {}
+        Err(RunError::Backend(err)) => {
+            eprintln!("The {} backend failed: {}", backend.name(), err);
+            exit(1)
+        }
+        Err(RunError::Runtime { message, span }) => {
+            let sink = sink(args);
+            let file = db.file_id(name).expect("run files were set");
+            Reporting::for_file(db.files(), file)
+                .with_sink(&sink)
+                .emit_error(message, span, vec![], Code::E013);
+            sink.summarize();
+            exit(1)
+        }
+    }
+}
+
+/// Checks files without compiling them, printing the diagnostics of every file followed by
+/// the number of errors and warnings, or with `--format sarif` a SARIF log of all of them.
+/// Exits with an error if any of the files has errors.
+fn check(args: &ArgMatches) {
+    let sarif = args.get_one::<String>("format").is_some_and(|format| format == "sarif");
+    let mut db = database(args);
+    let mut log = SarifLog::new();
+    let sink = sink(args).buffered();
+    let mut failed = false;
+
+    for path in args.get_many::<String>("files").expect("No file arguments provided") {
+        let name = load(&mut db, path);
+
+        if !sarif {
+            failed |= report(&sink, &db, &name);
+            continue
+        }
+
+        let file = db.file_id(&name).expect("loaded files were set");
+        let reporter = Reporting::for_file(db.files(), file);
+        for (diagnostic, suggestions) in db.check(&name).iter() {
+            log.add(&reporter, diagnostic, suggestions);
+            failed |= diagnostic.severity >= Severity::Error;
+        }
+    }
+
+    sink.flush();
+    sink.summarize();
+    if sarif {
+        println!("{:#}", log.to_json());
+    }
+
+    if failed {
+        exit(1)
+    }
+}
+
+/// How often `zxx fix` and `zxx lint --fix` check a file again to apply the suggestions their
+/// fixes uncovered.
+const MAX_FIX_PASSES: usize = 8;
+
+/// Applies the suggestions that are the single fix of their diagnostic to a file in the
+/// database, and of two conflicting ones only the first. The file is checked again and the
+/// suggestions that uncovers are applied as well, up to `MAX_FIX_PASSES` times.
+///
+/// # Arguments
+///
+/// * `db` - The database the file was set in, whose text of the file is replaced.
+/// * `name` - The name of the file.
+/// * `diagnostics` - Returns the diagnostics whose suggestions are applied, such as
+///   `Database::check`.
+///
+/// # Returns
+///
+/// Returns the number of suggestions that were applied.
+fn apply_fixes(db: &mut Database, name: &str, diagnostics: impl Fn(&Database, &str) -> Rc<Vec<CheckError>>) -> usize {
+    let mut applied = 0;
+
+    for _ in 0..MAX_FIX_PASSES {
+        let suggestions: Vec<Suggestion> = diagnostics(db, name).iter()
+            .filter(|(_, suggestions)| suggestions.len() == 1)
+            .map(|(_, suggestions)| suggestions[0].clone())
+            .collect();
+        let text = db.text(name).expect("fixed files were set");
+        let (fixed, count) = apply_suggestions(text, &suggestions);
+        if count == 0 {
+            break
+        }
+
+        db.set_source(name, &fixed);
+        applied += count;
+    }
+
+    applied
+}
+
+fn print_applied(applied: usize, name: &str) {
+    println!("Applied {} fix{} to {}", applied, if applied == 1 { "" } else { "es" }, name);
+}
+
+/// Applies the suggestions of files in place, or with `--dry-run` prints them as a unified
+/// diff. Only suggestions that are the single fix of their diagnostic are applied, and of two
+/// conflicting ones only the first. The fixed files are checked again, and their remaining
+/// diagnostics are printed. Exits with an error if any of the files still has errors.
+fn fix(args: &ArgMatches) {
+    let dry_run = args.get_flag("dry-run");
+    let mut db = database(args);
+    let sink = sink(args).buffered();
+    let mut failed = false;
+
+    for name in args.get_many::<String>("files").expect("No file arguments provided") {
+        let original = read_file(name);
+        db.set_source(name, &original);
+        let applied = apply_fixes(&mut db, name, |db, name| db.check(name));
+
+        let fixed = db.text(name).expect("fixed files were set");
+        if dry_run {
+            print!("{}", unified_diff(name, &original, fixed));
+        } else if applied > 0 {
+            write_file(Path::new(name), fixed.as_bytes());
+            print_applied(applied, name);
+        }
+
+        failed |= report(&sink, &db, name);
+    }
+
+    sink.flush();
+    sink.summarize();
+    if failed {
+        exit(1)
+    }
+}
+
+/// Runs the lints over files, printing the diagnostics of every file followed by the number of
+/// errors and warnings, or with `--list` lists the lints instead. With `--fix` the fixes of the
+/// lints are applied in place first, and the fixed code read from stdin is printed to stdout.
+/// Exits with an error if any of the files has errors, or a finding of a denied lint.
+fn lint(args: &ArgMatches) {
+    if args.get_flag("list") {
+        for lint in lints() {
+            let info = lint.code().info();
+            println!("{}  {:<18}  {}", info.code, info.name, info.title);
+        }
+        return
+    }
+
+    let fix = args.get_flag("fix");
+    let mut db = database(args);
+    let sink = sink(args).buffered();
+    let mut failed = false;
+
+    for path in args.get_many::<String>("files").expect("No file arguments provided") {
+        let name = load(&mut db, path);
+
+        if fix {
+            let applied = apply_fixes(&mut db, &name, |db, name| db.lint(name));
+            let fixed = db.text(&name).expect("fixed files were set");
+
+            if name == STDIN_NAME {
+                print!("{}", fixed);
+            } else if applied > 0 {
+                write_file(Path::new(&name), fixed.as_bytes());
+                print_applied(applied, &name);
+            }
+        }
+
+        failed |= report(&sink, &db, &name);
+        failed |= emit(&sink, &db, &name, &db.lint(&name));
+    }
+
+    sink.flush();
+    sink.summarize();
+    if failed {
+        exit(1)
+    }
+}
+
+/// Formats files in place, or with `--check` lists the files that are not formatted. Exits
+/// with an error if a file is not formatted with `--check`, or cannot be parsed.
+fn fmt(args: &ArgMatches) {
+    let check = args.get_flag("check");
+    let sink = sink(args);
+    let mut failed = false;
+
+    for name in args.get_many::<String>("files").expect("No file arguments provided") {
+        let contents = read_file(name);
+        let formatted = match Formatter::new(name, &contents).quiet().format() {
+            Ok(formatted) => formatted,
+            Err(diagnostics) => {
+                let reporter = Reporting::new(name, &contents).with_sink(&sink);
+                diagnostics.iter().for_each(|diagnostic| reporter.emit_diagnostic(diagnostic));
+                failed = true;
+                continue
+            }
+        };
+
+        if formatted == contents {
+            continue
+        }
+
+        if check {
+            println!("{} is not formatted", name);
+            failed = true;
+        } else {
+            write_file(Path::new(name), formatted.as_bytes());
+        }
+    }
+
+    if failed {
+        exit(1)
+    }
+}
+
+/// Prints the explanation of a diagnostic code, exiting with an error if it is not registered.
+fn explain(args: &ArgMatches) {
+    let code = args.get_one::<String>("code")
+        .expect("No code argument provided");
+
+    let Ok(code) = code.parse::<Code>() else {
+        eprintln!("`{}` is not a diagnostic code of zxx", code);
+        exit(1)
+    };
+
+    let info = code.info();
+    print!("{}: {}\n\n{}", code, info.title, info.explanation);
+}
+
+/// Writes a file, exiting with an error if it cannot be written.
+fn write_file(path: &Path, contents: &[u8]) {
+    if let Err(err) = std::fs::write(path, contents) {
+        eprintln!("Cannot write file `{}`: {}", path.display(), err);
+        exit(1)
+    }
+}
+
+fn opt_level_arg() -> Arg {
+    Arg::new("opt-level")
+        .short('O')
+        .help("Optimization level")
+        .value_parser(clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))
+        .default_value("0")
 }
 
 fn main() {
-    let cmd = clap::Command::new("zxx")
+    let cmd = Command::new("zxx")
         .bin_name("zxx")
         .arg(Arg::new("file")
-            .index(1)
-            .required(true))
-            .arg_required_else_help(true)
+            .index(1))
+        .arg(Arg::new("error-format")
+            .long("error-format")
+            .help("Prints diagnostics as colored text, or as one JSON object per line")
+            .value_parser(["human", "json"])
+            .default_value("human")
+            .global(true))
+        .arg(Arg::new("color")
+            .long("color")
+            .help("Whether diagnostics are colored, which by default they are on terminals")
+            .value_parser(["auto", "always", "never"])
+            .default_value("auto")
+            .global(true))
+        .args(config::LEVEL_FLAGS.map(|(id, level)| Arg::new(id)
+            .short(id.chars().next().expect("flags have names").to_ascii_uppercase())
+            .long(id)
+            .value_name("CODE")
+            .help(format!("Sets the level of a diagnostic code or its name to `{}`", level))
+            .action(ArgAction::Append)
+            .global(true)))
+        .arg(Arg::new("deny-warnings")
+            .long("deny-warnings")
+            .help("Reports warnings as errors, which makes them fail the build")
+            .action(ArgAction::SetTrue)
+            .global(true))
+        .arg(Arg::new("feature")
+            .long("feature")
+            .value_name("NAME")
+            .help("Enables an incubating language feature, named after its RFC")
+            .action(ArgAction::Append)
+            .global(true))
+        .subcommand(Command::new("build")
+            .about("Compiles a file ahead of time for the given target")
+            .arg(Arg::new("file")
+                .index(1)
+                .required(true))
+            .arg(Arg::new("target")
+                .long("target")
+                .value_parser(["c", "wasm32"])
+                .default_value("c"))
+            .arg(Arg::new("output")
+                .short('o')
+                .long("output"))
+            .arg(Arg::new("emit")
+                .long("emit")
+                .help("Prints the IR before and after every optimization pass instead of building")
+                .value_parser(["ir"]))
+            .arg(opt_level_arg()))
+        .subcommand(Command::new("run")
+            .about("Compiles a file to native code and runs it")
+            .arg(Arg::new("file")
+                .index(1)
+                .required(true))
+            .arg(Arg::new("backend")
+                .long("backend")
+                .value_parser(codegen::native::BACKENDS.to_vec())
+                .default_value("cranelift"))
+            .arg(opt_level_arg()))
+        .subcommand(Command::new("check")
+            .about("Reports the errors in files without compiling them")
+            .arg(Arg::new("files")
+                .help("The files to check, where `-` reads a file from stdin")
+                .index(1)
+                .num_args(1..)
+                .required(true))
+            .arg(Arg::new("format")
+                .long("format")
+                .help("Prints the diagnostics as they are emitted, or as a single SARIF 2.1.0 log")
+                .value_parser(["text", "sarif"])
+                .default_value("text")))
+        .subcommand(Command::new("fix")
+            .about("Applies the suggested fixes of files in place")
+            .arg(Arg::new("files")
+                .index(1)
+                .num_args(1..)
+                .required(true))
+            .arg(Arg::new("dry-run")
+                .long("dry-run")
+                .help("Prints the fixes as a unified diff instead of applying them")
+                .action(ArgAction::SetTrue)))
+        .subcommand(Command::new("lint")
+            .about("Reports code that is valid but probably a mistake, such as unused variables")
+            .arg(Arg::new("files")
+                .help("The files to lint, where `-` reads a file from stdin")
+                .index(1)
+                .num_args(1..)
+                .required_unless_present("list"))
+            .arg(Arg::new("fix")
+                .long("fix")
+                .help("Applies the fixes of the lints in place before reporting what remains")
+                .action(ArgAction::SetTrue))
+            .arg(Arg::new("list")
+                .long("list")
+                .help("Lists the lints with their codes instead of running them")
+                .action(ArgAction::SetTrue)))
+        .subcommand(Command::new("fmt")
+            .about("Formats files in place")
+            .arg(Arg::new("files")
+                .index(1)
+                .num_args(1..)
+                .required(true))
+            .arg(Arg::new("check")
+                .long("check")
+                .help("Lists the files that are not formatted instead of formatting them, failing if there are any")
+                .action(ArgAction::SetTrue)))
+        .subcommand(Command::new("explain")
+            .about("Explains a diagnostic code, such as E003")
+            .arg(Arg::new("code")
+                .index(1)
+                .required(true)))
+        .subcommand(Command::new("repl")
+            .about("Starts an interactive session"))
         .get_matches();
 
-    let name = cmd.get_one::<String>("file")
-        .expect("No file argument provided");
+    match cmd.subcommand() {
+        Some(("build", args)) => return build(args),
+        Some(("run", args)) => return run(args),
+        Some(("check", args)) => return check(args),
+        Some(("fix", args)) => return fix(args),
+        Some(("lint", args)) => return lint(args),
+        Some(("fmt", args)) => return fmt(args),
+        Some(("explain", args)) => return explain(args),
+        Some(("repl", args)) => return repl::run(config::features(args)),
+        _ => {}
+    }
 
-    let contents = read_file(name);
-    let lexer = Lexer::new(name, &contents);
-    let tokens = lexer.tokenize();
-    let mut parser = Parser::new(tokens);
-
-    let ast = parser.parse();
-}
\ No newline at end of file
+    // without a file or a subcommand, `zxx` starts a session like `zxx repl`
+    match cmd.get_one::<String>("file") {
+        Some(name) => front_end(&cmd, &mut database(&cmd), name),
+        None => repl::run(config::features(&cmd)),
+    }
+}
@@ -0,0 +1,184 @@
+use std::io::{stdout, Stdout};
+use std::process::exit;
+use ast::{AstNode, TypedValue};
+use ast::checker::{Checker, Environment};
+use ast::interpreter::{format_value, Interpreter, RuntimeError};
+use ast::lexer::{Lexer, Token};
+use ast::parser::Parser;
+use common::codes::Code;
+use common::errors::Reporting;
+use common::features::Features;
+use common::files::{FileId, Files};
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+
+/// The name diagnostics refer to the REPL's input buffer by.
+const BUFFER_NAME: &str = "<repl>";
+
+/// Starts an interactive session that reads inputs from the terminal until it is closed.
+///
+/// Inputs can be declarations, statements or bare expressions, whose value and type are
+/// printed. An input continues on the next line while it has an unclosed `{` or `(`.
+///
+/// # Arguments
+///
+/// * `features` - The incubating features the inputs may use.
+pub fn run(features: Features) {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            eprintln!("Could not open the terminal: {}", err);
+            exit(1)
+        }
+    };
+
+    let mut files = Files::new();
+    let file = files.add_virtual(BUFFER_NAME, "");
+    let mut session = Session {
+        buffer: String::new(),
+        files,
+        file,
+        environment: Environment::default(),
+        features,
+        interpreter: Interpreter::new(stdout()),
+    };
+    let mut pending = String::new();
+
+    loop {
+        let prompt = if pending.is_empty() { ">> " } else { ".. " };
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                pending.push_str(&line);
+                pending.push('\n');
+
+                if Lexer::new(BUFFER_NAME, &pending).open_delimiters() > 0 {
+                    continue
+                }
+
+                let input = std::mem::take(&mut pending);
+                if input.trim().is_empty() {
+                    continue
+                }
+
+                let _ = editor.add_history_entry(input.trim_end());
+                session.evaluate(&input);
+            }
+            // Ctrl-C discards the input that is being typed
+            Err(ReadlineError::Interrupted) => pending.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                eprintln!("Could not read from the terminal: {}", err);
+                exit(1)
+            }
+        }
+    }
+}
+
+/// The state that persists between the inputs of a session.
+struct Session {
+    /// Every input that was accepted so far. New inputs are appended to it before they are
+    /// lexed, so that diagnostics show where in the session they occurred.
+    buffer: String,
+    /// The files diagnostics point at, whose only file is a virtual file holding the buffer
+    files: Files,
+    file: FileId,
+    /// The declarations of every accepted input
+    environment: Environment,
+    features: Features,
+    interpreter: Interpreter<Stdout>,
+}
+
+impl Session {
+    /// Evaluates a complete input, discarding it if it contains an error. An input that
+    /// fails at runtime is undone completely, including the globals it already assigned.
+    fn evaluate(&mut self, input: &str) {
+        let snapshot = self.interpreter.snapshot();
+        let start = self.buffer.len();
+        self.buffer.push_str(input);
+        self.files.set_source(self.file, &self.buffer);
+
+        if self.evaluate_from(start).is_err() {
+            self.interpreter.restore(snapshot);
+            self.buffer.truncate(start);
+            self.files.set_source(self.file, &self.buffer);
+        }
+    }
+
+    /// Evaluates the part of the buffer after `start`. Diagnostics are emitted as soon as
+    /// they are found.
+    fn evaluate_from(&mut self, start: usize) -> Result<(), ()> {
+        let tokens: Vec<Token> = Lexer::new(BUFFER_NAME, &self.buffer)
+            .with_reporting(self.reporter())
+            .try_tokenize()
+            .map_err(drop)?
+            .into_iter()
+            .filter(|token| token.span.start().0 as usize >= start)
+            .collect();
+
+        if let Some(expression) = Parser::new(tokens.clone()).parse_bare_expression() {
+            let found = Checker::with_environment(BUFFER_NAME, &self.buffer, self.environment.clone())
+                .with_reporting(self.reporter())
+                .with_features(self.features.clone())
+                .check_expression(&expression)
+                .map_err(drop)?;
+            let value = self.interpreter.evaluate(&expression)
+                .map_err(|err| self.report(err))?;
+
+            println!("{}: {}", format_result(&value), found);
+            return Ok(())
+        }
+
+        let mut parser = Parser::new(tokens);
+        let document = parser.parse();
+        if !parser.report_errors(&self.reporter()).is_empty() {
+            return Err(())
+        }
+
+        let body = match document {
+            AstNode::Document { body } => match *body {
+                AstNode::Block { body } => body,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+
+        let environment = Checker::with_environment(BUFFER_NAME, &self.buffer, self.environment.clone())
+            .with_reporting(self.reporter())
+            .with_features(self.features.clone())
+            .check_body(&body)
+            .map_err(drop)?;
+        self.interpreter.run(&body)
+            .map_err(|err| self.report(err))?;
+        self.environment = environment;
+
+        for node in &body {
+            if let AstNode::VarDeclaration { name, var_type, .. } = node {
+                if let Some(value) = self.interpreter.global(name) {
+                    println!("{}: {} = {}", name, var_type, format_result(value));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn report(&self, err: RuntimeError) {
+        self.reporter()
+            .emit_error(err.message, err.span, vec![], Code::E013);
+    }
+
+    /// Returns the `Reporting` instance of the buffer.
+    fn reporter(&self) -> Reporting<'_> {
+        Reporting::for_file(&self.files, self.file)
+    }
+}
+
+/// Formats a value for display as the result of an input, quoting strings so they can be
+/// told apart from other values.
+fn format_result(value: &TypedValue) -> String {
+    match value {
+        TypedValue::StringVal(value) => format!("{:?}", value),
+        _ => format_value(value),
+    }
+}
@@ -0,0 +1,15 @@
+use std::process::Command;
+
+#[test]
+fn missing_files_are_reported_instead_of_panicking() {
+    let path = std::env::temp_dir().join("zpp-missing").join("missing.zpp");
+    let path = path.to_str().unwrap();
+
+    for args in [&[path][..], &["check", path], &["lint", path], &["fix", path], &["fmt", path], &["build", path]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_zxx")).args(args).output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(output.status.code(), Some(1), "{:?}: {}", args, stderr);
+        assert!(stderr.starts_with(&format!("Cannot read file `{}`: ", path)), "{:?}: {}", args, stderr);
+    }
+}
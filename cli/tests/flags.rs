@@ -0,0 +1,85 @@
+use std::process::{Command, Output};
+use common::temp::TempFile;
+
+/// Runs `zxx` on a program with the given arguments, in which `{}` stands for the program's path.
+///
+/// # Arguments
+///
+/// * `source` - The program.
+/// * `args` - The arguments passed to `zxx`.
+fn zxx(source: &str, args: &[&str]) -> Output {
+    let file = TempFile::new("zpp", source).unwrap();
+    let path = file.path().to_str().unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_zxx"))
+        .args(args.iter().map(|arg| if *arg == "{}" { path } else { arg }))
+        .output()
+        .unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+const MISSING: &str = "int x = missing\n";
+
+#[test]
+fn global_flags_can_come_before_the_subcommand() {
+    for args in [&["--color", "never", "check", "{}"][..], &["check", "--color", "never", "{}"], &["--color", "never", "{}"]] {
+        let output = zxx(MISSING, args);
+
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(stderr(&output).starts_with("error[E002]: Cannot find variable `missing` in this scope\n"), "{:?}: {}", args, stderr(&output));
+    }
+
+    let output = zxx(MISSING, &["--error-format", "json", "check", "{}"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with(r#"{"code":"E002","#), "{}", stderr(&output));
+
+    let output = zxx("print(1)\n", &["--color", "never", "build", "--emit", "ir", "{}"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with(";; IR without optimizations\n"));
+}
+
+#[test]
+fn level_flags_can_come_before_the_subcommand() {
+    const COMPARISON: &str = "float total = 0.1\nbool exact = total == 0.3\n";
+
+    let output = zxx(COMPARISON, &["--color", "never", "check", "{}"]);
+    assert!(output.status.success());
+    assert!(stderr(&output).starts_with("warning[W001]"), "{}", stderr(&output));
+
+    for args in [&["--deny-warnings", "--color", "never", "check", "{}"][..], &["-D", "W001", "--color", "never", "check", "{}"]] {
+        let output = zxx(COMPARISON, args);
+
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(stderr(&output).starts_with("error[W001]"), "{:?}: {}", args, stderr(&output));
+    }
+
+    for args in [&["-A", "W001", "check", "{}"][..], &["--allow", "float-comparison", "check", "{}"], &["-A", "W001", "--deny-warnings", "{}"]] {
+        let output = zxx(COMPARISON, args);
+
+        assert!(output.status.success(), "{:?}: {}", args, stderr(&output));
+        assert_eq!(stderr(&output), "", "{:?}", args);
+    }
+}
+
+#[test]
+fn incubating_features_are_enabled_by_flags() {
+    const STATIC: &str = "static int SIZE = 4 * 1024\nprint(SIZE)\n";
+
+    let output = zxx(STATIC, &["--color", "never", "check", "{}"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error[E018]: `static` declarations are an incubating feature that is not enabled\n"), "{}", stderr(&output));
+
+    for args in [&["--feature", "static-declarations", "check", "{}"][..], &["check", "{}", "--feature", "static-declarations"]] {
+        let output = zxx(STATIC, args);
+
+        assert!(output.status.success(), "{:?}: {}", args, stderr(&output));
+        assert_eq!(stderr(&output), "", "{:?}", args);
+    }
+
+    let output = zxx(STATIC, &["--feature", "statics", "check", "{}"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), "Cannot enable `--feature statics`: it is not a feature of zxx\n");
+}
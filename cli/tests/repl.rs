@@ -0,0 +1,47 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Starts `zxx` with the given arguments and types the inputs into its session.
+fn session(args: &[&str], inputs: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_zxx"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(inputs.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn lines(output: &Output) -> Vec<&str> {
+    std::str::from_utf8(&output.stdout).unwrap().lines().collect()
+}
+
+#[test]
+fn zxx_without_arguments_starts_a_session() {
+    for args in [&[][..], &["repl"]] {
+        let output = session(args, "1 + 2\n");
+
+        assert!(output.status.success(), "{:?}", args);
+        assert_eq!(lines(&output), ["3: int"], "{:?}", args);
+    }
+}
+
+#[test]
+fn inputs_that_fail_at_runtime_are_undone() {
+    let inputs = "mut int count = 1\nint zero = 0\nfunc reset() {\n    count = 7\n    print(1 / zero)\n}\nreset()\ncount\n";
+    let output = session(&[], inputs);
+
+    // `reset` assigned `count` before it failed, which is undone with the rest of the input
+    assert_eq!(lines(&output), ["count: int = 1", "zero: int = 0", "1: int"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error[E013]"));
+}
+
+#[test]
+fn literals_with_a_fraction_are_floats() {
+    let output = session(&[], "7 / 2.0\n2.0\n");
+
+    assert_eq!(lines(&output), ["3.5: float", "2: float"]);
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "cranelift")]
+
+use std::process::{Command, Output};
+use common::temp::TempFile;
+
+/// Runs a program with `zxx run --backend cranelift` at an optimization level, allowing
+/// `static` declarations.
+///
+/// # Arguments
+///
+/// * `source` - The program.
+/// * `opt_level` - The level passed to `-O`.
+fn run(source: &str, opt_level: u8) -> Output {
+    let file = TempFile::new("zpp", source).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_zxx"))
+        .args(["--color", "never", "--feature", "static-declarations", "run", "--backend", "cranelift"])
+        .arg(format!("-O{}", opt_level))
+        .arg(file.path())
+        .output()
+        .unwrap()
+}
+
+fn lines(output: &Output) -> Vec<&str> {
+    std::str::from_utf8(&output.stdout).unwrap().lines().collect()
+}
+
+#[test]
+fn values_of_every_type_are_printed() {
+    let source = r#"
+static int SIZE = 4 * 1024
+mut int count = SIZE - 1
+String name = "Z++"
+func show(int n, float f) {
+    print(n * 3 - SIZE)
+    print(f / 4)
+    print(-n)
+    print(n / 7)
+}
+func Main() {
+    show(count, 2.5)
+    count = 2147483647
+    print(count + 1)
+    print(name)
+    print(null)
+    print(count > 5)
+}
+"#;
+
+    for opt_level in 0..=3 {
+        let output = run(source, opt_level);
+
+        assert!(output.status.success(), "at -O{}: {}", opt_level, String::from_utf8_lossy(&output.stderr));
+        assert_eq!(
+            lines(&output),
+            ["8189", "0.625", "-4095", "585", "-2147483648", "Z++", "null", "true"],
+            "at -O{}",
+            opt_level
+        );
+    }
+}
+
+#[test]
+fn literals_keep_their_type() {
+    let source = r#"
+func Main() {
+    print(7 / 2.0)
+    float third = 1 / 3.0
+    print(third)
+    int exact = 16777217
+    print(exact + 1)
+    print(2147483647)
+}
+"#;
+
+    for opt_level in 0..=3 {
+        let output = run(source, opt_level);
+
+        assert!(output.status.success(), "at -O{}: {}", opt_level, String::from_utf8_lossy(&output.stderr));
+        assert_eq!(lines(&output), ["3.5", "0.33333334", "16777218", "2147483647"], "at -O{}", opt_level);
+    }
+}
+
+#[test]
+fn undefined_divisions_are_runtime_errors() {
+    let source = r#"
+mut int divisor = 0
+func divide(int n) {
+    print(n / divisor)
+    print(3)
+}
+func Main() {
+    print(1)
+    divide(7)
+    print(2)
+}
+"#;
+    let overflow = source.replace("mut int divisor = 0", "mut int divisor = -1")
+        .replace("divide(7)", "divide(-2147483647 - 1)");
+
+    for opt_level in 0..=3 {
+        for source in [source, &overflow] {
+            let output = run(source, opt_level);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+
+            // the program stops where it failed, rather than being killed by a trap
+            assert_eq!(output.status.code(), Some(1), "at -O{}: {}", opt_level, stderr);
+            assert_eq!(lines(&output), ["1"], "at -O{}", opt_level);
+            assert!(stderr.contains("error[E013]: The result of `/` is undefined"), "at -O{}: {}", opt_level, stderr);
+            assert!(stderr.contains(":4:11"), "at -O{}: {}", opt_level, stderr);
+        }
+    }
+}
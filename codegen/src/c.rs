@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use ir::{BinaryOperator, Constant, FuncId, Function, InstKind, Module, Terminator, Type, UnaryOperator, Value};
+
+/// The runtime prelude that is copied to the top of every generated translation unit.
+const RUNTIME: &str = include_str!("c/runtime.h");
+
+/// Translates an IR module into a single, self-contained C99 translation unit.
+///
+/// Every Z++ name is prefixed with `zpp_` so it can never collide with a C keyword, the C
+/// standard library or the runtime, whose names are prefixed with `zrt_`. Top-level
+/// statements run in `zrt_init`, and unless `ZPP_NO_MAIN` is defined, a `main` function
+/// calls it before calling `Main`. Defining `ZPP_NO_MAIN` allows the generated code to be
+/// embedded into an existing C program.
+///
+/// # Arguments
+///
+/// * `source_name` - The name of the file the module was lowered from.
+/// * `module` - The module to translate.
+///
+/// # Returns
+///
+/// Returns the generated C source code.
+pub fn emit(source_name: &str, module: &Module) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "/* Generated by zxx from {}. */", source_name.replace("*/", "* /")).unwrap();
+    out.push_str(RUNTIME);
+    out.push('\n');
+
+    for global in &module.globals {
+        writeln!(out, "static {} zpp_{};", c_type(global.var_type), global.name).unwrap();
+    }
+
+    // prototypes, so functions can be called before they are defined
+    for (index, function) in module.functions.iter().enumerate() {
+        writeln!(out, "{};", signature(module, FuncId(index as u32), function)).unwrap();
+    }
+    out.push('\n');
+
+    for (index, function) in module.functions.iter().enumerate() {
+        emit_function(&mut out, module, FuncId(index as u32), function);
+    }
+
+    out.push_str("#ifndef ZPP_NO_MAIN\n");
+    out.push_str("int main(void) {\n");
+    writeln!(out, "    {}();", function_name(module, module.init)).unwrap();
+    if let Some(entry) = module.entry {
+        writeln!(out, "    {}();", function_name(module, entry)).unwrap();
+    }
+    out.push_str("    return 0;\n");
+    out.push_str("}\n");
+    out.push_str("#endif\n");
+
+    out
+}
+
+/// Maps a Z++ type to the C type that represents it.
+fn c_type(value_type: Type) -> &'static str {
+    match value_type {
+        Type::Void => "void",
+        Type::String => "zrt_string",
+        Type::Int => "int32_t",
+        Type::Float => "float",
+        Type::Boolean => "bool",
+        Type::Null => "void *",
+        Type::Reference => unreachable!("unresolved types are rejected by the checker"),
+    }
+}
+
+/// Escapes a string literal so it can be embedded between double quotes in C source.
+fn c_string(value: &str) -> String {
+    let mut escaped = String::new();
+
+    for byte in value.bytes() {
+        match byte {
+            b'"' => escaped.push_str("\\\""),
+            b'\\' => escaped.push_str("\\\\"),
+            // avoids accidental trigraphs
+            b'?' => escaped.push_str("\\?"),
+            0x20..=0x7e => escaped.push(byte as char),
+            _ => write!(escaped, "\\{:03o}", byte).unwrap(),
+        }
+    }
+
+    escaped
+}
+
+fn function_name(module: &Module, id: FuncId) -> String {
+    if id == module.init {
+        "zrt_init".to_string()
+    } else {
+        format!("zpp_{}", module.function(id).name)
+    }
+}
+
+fn signature(module: &Module, id: FuncId, function: &Function) -> String {
+    let params = if function.params.is_empty() {
+        "void".to_string()
+    } else {
+        function.params.iter()
+            .map(|(name, value)| format!("{} zpp_{}", c_type(function.value_type(*value)), name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    format!("{} {}({})", c_type(function.returns), function_name(module, id), params)
+}
+
+fn emit_function(out: &mut String, module: &Module, id: FuncId, function: &Function) {
+    writeln!(out, "{} {{", signature(module, id, function)).unwrap();
+
+    // parameters keep their Z++ names, every other value is a C local named after its number
+    let names: HashMap<Value, String> = function.params.iter()
+        .map(|(name, value)| (*value, format!("zpp_{}", name)))
+        .collect();
+    let name = |value: &Value| names.get(value)
+        .cloned()
+        .unwrap_or_else(|| format!("v{}", value.0));
+
+    for block in &function.blocks {
+        for inst in &block.insts {
+            let expr = match &inst.kind {
+                InstKind::Const(constant) => {
+                    let result = inst.result.expect("constants define a value");
+                    match constant {
+                        Constant::Int(value) => value.to_string(),
+                        Constant::Float(value) => format!("{:?}f", value),
+                        Constant::Bool(value) => value.to_string(),
+                        Constant::String(value) => {
+                            format!("zrt_string_new(\"{}\", {})", c_string(value), value.len())
+                        }
+                        Constant::Null if function.value_type(result) == Type::String => {
+                            "zrt_string_null()".to_string()
+                        }
+                        Constant::Null => "NULL".to_string(),
+                    }
+                }
+                InstKind::Copy(value) => name(value),
+                InstKind::IntToFloat(value) => format!("(float) {}", name(value)),
+                InstKind::Binary(op, lhs, rhs) => {
+                    let (lhs_name, rhs_name) = (name(lhs), name(rhs));
+                    match op {
+                        // signed overflow is undefined in C, Z++ integers wrap instead
+                        BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul
+                            if function.value_type(*lhs) == Type::Int => {
+                            format!("(int32_t) ((uint32_t) {} {} (uint32_t) {})", lhs_name, op, rhs_name)
+                        }
+                        // so is dividing by zero, and dividing the smallest integer by -1
+                        BinaryOperator::Div if function.value_type(*lhs) == Type::Int => {
+                            format!("zrt_div_int({}, {})", lhs_name, rhs_name)
+                        }
+                        _ => format!("{} {} {}", lhs_name, op, rhs_name),
+                    }
+                }
+                InstKind::Unary(UnaryOperator::Neg, value) if function.value_type(*value) == Type::Int => {
+                    format!("(int32_t) (0u - (uint32_t) {})", name(value))
+                }
+                InstKind::Unary(op, value) => format!("{}{}", op, name(value)),
+                InstKind::Load(global) => format!("zpp_{}", module.global(*global).name),
+                InstKind::Store(global, value) => {
+                    format!("zpp_{} = {}", module.global(*global).name, name(value))
+                }
+                InstKind::Call(callee, args) => {
+                    let args = args.iter().map(&name).collect::<Vec<_>>().join(", ");
+                    format!("{}({})", function_name(module, *callee), args)
+                }
+                InstKind::Print(value) => match function.value_type(*value) {
+                    Type::String => format!("zrt_print_string({})", name(value)),
+                    Type::Int => format!("zrt_print_int({})", name(value)),
+                    Type::Float => format!("zrt_print_float({})", name(value)),
+                    Type::Boolean => format!("zrt_print_bool({})", name(value)),
+                    _ => "zrt_print_null()".to_string(),
+                },
+            };
+
+            match inst.result {
+                Some(result) => {
+                    let result_type = c_type(function.value_type(result));
+                    writeln!(out, "    {} {} = {};", result_type, name(&result), expr).unwrap();
+                }
+                None => writeln!(out, "    {};", expr).unwrap(),
+            }
+        }
+
+        match &block.terminator {
+            Terminator::Return(Some(value)) => writeln!(out, "    return {};", name(value)).unwrap(),
+            // the implicit return at the end of a void function
+            Terminator::Return(None) => {}
+        }
+    }
+
+    out.push_str("}\n\n");
+}
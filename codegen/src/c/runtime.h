@@ -0,0 +1,136 @@
+/*
+ * The Z++ C runtime. Every translation unit produced by the C backend starts with
+ * this prelude, so the generated code does not depend on anything but a C99 compiler
+ * and its standard library.
+ */
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Strings are immutable byte slices, a NULL data pointer represents `null`. */
+typedef struct zrt_string {
+    const char *data;
+    size_t len;
+} zrt_string;
+
+static inline zrt_string zrt_string_new(const char *data, size_t len) {
+    zrt_string string;
+    string.data = data;
+    string.len = len;
+    return string;
+}
+
+static inline zrt_string zrt_string_null(void) {
+    return zrt_string_new(NULL, 0);
+}
+
+static inline bool zrt_string_is_null(zrt_string string) {
+    return string.data == NULL;
+}
+
+/*
+ * Integer division is undefined in C when the divisor is zero or the result overflows,
+ * so both are checked, and end the program with the runtime error the interpreter reports.
+ */
+static inline int32_t zrt_div_int(int32_t lhs, int32_t rhs) {
+    if (rhs == 0 || (lhs == INT32_MIN && rhs == -1)) {
+        fflush(stdout);
+        fputs("zpp: error[E013]: The result of `/` is undefined, it overflows or divides by zero\n", stderr);
+        exit(1);
+    }
+
+    return lhs / rhs;
+}
+
+static inline void zrt_print_int(int32_t value) {
+    printf("%" PRId32 "\n", value);
+}
+
+/*
+ * Floats are printed like the interpreter prints them: with the fewest significant digits that
+ * read back as the same float, and never in scientific notation, so `1e7` prints `10000000`.
+ */
+static inline void zrt_print_float(float value) {
+    char digits[16], exact[128];
+    const char *rest;
+    int precision, exponent;
+
+    if (value != value) {
+        puts("NaN");
+        return;
+    }
+    if (value == 0.0f || value * 0.5f == value) {
+        /* zero and the infinities, which keep their sign */
+        printf("%s%s\n", signbit(value) ? "-" : "", value == 0.0f ? "0" : "inf");
+        return;
+    }
+
+    for (precision = 1; precision < 9; precision++) {
+        snprintf(digits, sizeof digits, "%.*e", precision - 1, (double) value);
+        if (strtof(digits, NULL) == value) {
+            break;
+        }
+    }
+    snprintf(digits, sizeof digits, "%.*e", precision - 1, (double) (value < 0.0f ? -value : value));
+    /* every float has at most 105 significant digits, so this is its exact value */
+    snprintf(exact, sizeof exact, "%.110e", (double) (value < 0.0f ? -value : value));
+
+    /* `digits` holds `d.ddde+x`, which is rewritten without the point and the exponent */
+    exponent = atoi(strchr(digits, 'e') + 1);
+    if (precision > 1) {
+        memmove(digits + 1, digits + 2, precision - 1);
+    }
+    digits[precision] = '\0';
+
+    /*
+     * printf rounds a value exactly halfway between two candidates to the even one, the
+     * interpreter rounds it up. The kept digit is then even, so adding one never carries.
+     */
+    rest = exact + precision + 2;
+    if (exact[precision + 1] == '5' && strspn(rest, "0") == (size_t) (strchr(rest, 'e') - rest)
+        && digits[precision - 1] == exact[precision == 1 ? 0 : precision]) {
+        digits[precision - 1]++;
+    }
+
+    if (value < 0.0f) {
+        putchar('-');
+    }
+    if (exponent < 0) {
+        fputs("0.", stdout);
+        for (; exponent < -1; exponent++) {
+            putchar('0');
+        }
+        puts(digits);
+    } else if (exponent < precision - 1) {
+        printf("%.*s.%s\n", exponent + 1, digits, digits + exponent + 1);
+    } else {
+        fputs(digits, stdout);
+        for (; exponent > precision - 1; exponent--) {
+            putchar('0');
+        }
+        putchar('\n');
+    }
+}
+
+static inline void zrt_print_bool(bool value) {
+    puts(value ? "true" : "false");
+}
+
+static inline void zrt_print_null(void) {
+    puts("null");
+}
+
+static inline void zrt_print_string(zrt_string value) {
+    if (zrt_string_is_null(value)) {
+        zrt_print_null();
+        return;
+    }
+
+    fwrite(value.data, 1, value.len, stdout);
+    putchar('\n');
+}
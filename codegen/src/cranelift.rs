@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use std::io::Write;
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{types, AbiParam, InstBuilder, MemFlags, Value};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, DataDescription, DataId, FuncId, Linkage, Module, ModuleError};
+use codespan::Span;
+use ir::{BinaryOperator, Constant, InstKind, Terminator, Type, UnaryOperator};
+use crate::native::{NativeBackend, RunError};
+
+/// A native backend that JIT compiles documents with Cranelift.
+///
+/// Values of type `String` are pointers to a little endian `u32` byte length followed by the
+/// string's UTF-8 bytes, the same layout the WebAssembly backend uses. The null pointer
+/// represents `null`.
+///
+/// Operations that would trap, such as an integer division by zero, are checked before they
+/// are executed instead. A failing check records which one failed and returns from its
+/// function, as does every function after a call that failed, until the program is left.
+pub struct CraneliftBackend;
+
+impl NativeBackend for CraneliftBackend {
+    fn name(&self) -> &'static str {
+        "cranelift"
+    }
+
+    fn run(&mut self, module: &ir::Module) -> Result<(), RunError> {
+        let mut flags = settings::builder();
+        flags.set("use_colocated_libcalls", "false").map_err(backend_error)?;
+        flags.set("is_pic", "false").map_err(backend_error)?;
+
+        let isa = cranelift_native::builder()
+            .map_err(backend_error)?
+            .finish(settings::Flags::new(flags))
+            .map_err(backend_error)?;
+
+        let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+        builder.symbol("zrt_print_int", zrt_print_int as *const u8);
+        builder.symbol("zrt_print_float", zrt_print_float as *const u8);
+        builder.symbol("zrt_print_bool", zrt_print_bool as *const u8);
+        builder.symbol("zrt_print_string", zrt_print_string as *const u8);
+        builder.symbol("zrt_print_null", zrt_print_null as *const u8);
+
+        let mut module_builder = JITModule::new(builder);
+        let failure = module_builder.declare_data("zrt_failure", Linkage::Local, true, false)
+            .map_err(backend_error)?;
+
+        let mut lowering = Lowering {
+            module: module_builder,
+            functions: Vec::new(),
+            globals: Vec::new(),
+            strings: HashMap::new(),
+            prints: HashMap::new(),
+            failure,
+            failures: Vec::new(),
+        };
+
+        lowering.lower_module(module).map_err(backend_error)?;
+        lowering.module.finalize_definitions().map_err(backend_error)?;
+
+        let init = lowering.module.get_finalized_function(lowering.functions[module.init.0 as usize]);
+        let main = module.entry.map(|main| lowering.module.get_finalized_function(lowering.functions[main.0 as usize]));
+        let (failure, _) = lowering.module.get_finalized_data(lowering.failure);
+
+        // SAFETY: both functions were declared without parameters or results, and every
+        // value they touch lives in memory owned by the module, as does the failure, which
+        // is an aligned `i32`.
+        let failed = unsafe {
+            let failed = || std::ptr::read_volatile(failure as *const i32);
+
+            let init: extern "C" fn() = std::mem::transmute(init);
+            init();
+
+            if let Some(main) = main.filter(|_| failed() == 0) {
+                let main: extern "C" fn() = std::mem::transmute(main);
+                main();
+            }
+
+            failed()
+        };
+
+        let _ = std::io::stdout().flush();
+
+        match failed {
+            0 => Ok(()),
+            failed => Err(RunError::Runtime {
+                message: format!("The result of `{}` is undefined, it overflows or divides by zero", BinaryOperator::Div),
+                span: lowering.failures[failed as usize - 1],
+            }),
+        }
+    }
+}
+
+fn backend_error(err: impl ToString) -> RunError {
+    RunError::Backend(err.to_string())
+}
+
+/// Writes a line to stdout. Host functions must not unwind into generated code, so write
+/// errors such as a closed pipe are ignored.
+fn print_line(line: &[u8]) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(line).and_then(|_| stdout.write_all(b"\n"));
+}
+
+extern "C" fn zrt_print_int(value: i32) {
+    print_line(value.to_string().as_bytes());
+}
+
+extern "C" fn zrt_print_float(value: f32) {
+    print_line(value.to_string().as_bytes());
+}
+
+extern "C" fn zrt_print_bool(value: i32) {
+    print_line((value != 0).to_string().as_bytes());
+}
+
+extern "C" fn zrt_print_null() {
+    print_line(b"null");
+}
+
+/// # Safety
+///
+/// `value` has to be null or point to a length prefixed string created by the backend.
+unsafe extern "C" fn zrt_print_string(value: *const u8) {
+    if value.is_null() {
+        zrt_print_null();
+        return;
+    }
+
+    let len = u32::from_le_bytes(std::ptr::read_unaligned(value as *const [u8; 4]));
+    print_line(std::slice::from_raw_parts(value.add(4), len as usize));
+}
+
+struct Lowering {
+    module: JITModule,
+    /// Ids of the functions of the IR module, in the same order
+    functions: Vec<FuncId>,
+    /// Data objects of the globals of the IR module, in the same order
+    globals: Vec<DataId>,
+    /// Data objects of string literals that were already placed in memory
+    strings: HashMap<String, DataId>,
+    /// Ids of the host functions used for printing
+    prints: HashMap<&'static str, FuncId>,
+    /// The data object the number of the check that failed is stored in, counting from one,
+    /// which stays zero while no check fails
+    failure: DataId,
+    /// The spans of the checked divisions, in the order of their numbers
+    failures: Vec<Span>,
+}
+
+/// Module errors are boxed, they are much larger than any successful result.
+type LowerResult<T> = Result<T, Box<ModuleError>>;
+
+impl Lowering {
+    fn cl_type(&self, value_type: Type) -> Option<types::Type> {
+        match value_type {
+            Type::Void => None,
+            Type::Int | Type::Boolean => Some(types::I32),
+            Type::Float => Some(types::F32),
+            Type::String | Type::Null => Some(self.module.target_config().pointer_type()),
+            Type::Reference => unreachable!("unresolved types are rejected by the checker"),
+        }
+    }
+
+    /// Declares and defines every function and global of the IR module.
+    fn lower_module(&mut self, ir_module: &ir::Module) -> LowerResult<()> {
+        for (print, param) in [
+            ("zrt_print_int", Some(Type::Int)),
+            ("zrt_print_float", Some(Type::Float)),
+            ("zrt_print_bool", Some(Type::Boolean)),
+            ("zrt_print_string", Some(Type::String)),
+            ("zrt_print_null", None),
+        ] {
+            let mut signature = self.module.make_signature();
+            if let Some(param) = param.and_then(|param| self.cl_type(param)) {
+                signature.params.push(AbiParam::new(param));
+            }
+
+            let id = self.module.declare_function(print, Linkage::Import, &signature)?;
+            self.prints.insert(print, id);
+        }
+
+        let mut failure = DataDescription::new();
+        failure.define_zeroinit(4);
+        failure.set_align(4);
+        self.module.define_data(self.failure, &failure)?;
+
+        for global in &ir_module.globals {
+            let id = self.module.declare_data(&format!("zpp_{}", global.name), Linkage::Local, true, false)?;
+
+            let mut data = DataDescription::new();
+            data.define_zeroinit(8);
+            data.set_align(8);
+            self.module.define_data(id, &data)?;
+
+            self.globals.push(id);
+        }
+
+        for function in &ir_module.functions {
+            let mut signature = self.module.make_signature();
+            for param_type in function.param_types() {
+                let param = self.cl_type(param_type).expect("parameters always have a value type");
+                signature.params.push(AbiParam::new(param));
+            }
+
+            if let Some(returns) = self.cl_type(function.returns) {
+                signature.returns.push(AbiParam::new(returns));
+            }
+
+            let id = self.module.declare_anonymous_function(&signature)?;
+            self.functions.push(id);
+        }
+
+        let mut context = self.module.make_context();
+        let mut builder_context = FunctionBuilderContext::new();
+
+        for (index, function) in ir_module.functions.iter().enumerate() {
+            let id = self.functions[index];
+            context.func.signature = self.module.declarations().get_function_decl(id).signature.clone();
+
+            let mut builder = FunctionBuilder::new(&mut context.func, &mut builder_context);
+            self.lower_function(&mut builder, ir_module, function)?;
+            builder.finalize();
+
+            self.module.define_function(id, &mut context)?;
+            self.module.clear_context(&mut context);
+        }
+
+        Ok(())
+    }
+
+    fn lower_function(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        ir_module: &ir::Module,
+        function: &ir::Function,
+    ) -> LowerResult<()> {
+        let pointer = self.module.target_config().pointer_type();
+
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        builder.seal_block(entry);
+
+        // IR values map directly onto Cranelift's SSA values
+        let mut values: HashMap<ir::Value, Value> = function.params.iter()
+            .zip(builder.block_params(entry).to_vec())
+            .map(|((_, param), value)| (*param, value))
+            .collect();
+
+        for block in &function.blocks {
+            for inst in &block.insts {
+                let result = match &inst.kind {
+                    InstKind::Const(constant) => Some(match constant {
+                        Constant::Int(value) => builder.ins().iconst(types::I32, *value as i64),
+                        Constant::Float(value) => builder.ins().f32const(*value),
+                        Constant::Bool(value) => builder.ins().iconst(types::I32, *value as i64),
+                        Constant::String(value) => {
+                            let id = self.intern(value)?;
+                            let string = self.module.declare_data_in_func(id, builder.func);
+                            builder.ins().global_value(pointer, string)
+                        }
+                        Constant::Null => builder.ins().iconst(pointer, 0),
+                    }),
+                    InstKind::Copy(value) => Some(values[value]),
+                    InstKind::IntToFloat(value) => Some(builder.ins().fcvt_from_sint(types::F32, values[value])),
+                    InstKind::Binary(BinaryOperator::Div, lhs, rhs) if function.value_type(*lhs) == Type::Int => {
+                        let (lhs, rhs) = (values[lhs], values[rhs]);
+
+                        // `sdiv` traps on a zero divisor, and when the result overflows
+                        let zero = builder.ins().icmp_imm(IntCC::Equal, rhs, 0);
+                        let min = builder.ins().icmp_imm(IntCC::Equal, lhs, i32::MIN as i64);
+                        let minus_one = builder.ins().icmp_imm(IntCC::Equal, rhs, -1);
+                        let overflows = builder.ins().band(min, minus_one);
+                        let undefined = builder.ins().bor(zero, overflows);
+
+                        let span = inst.span.expect("integer divisions are lowered with their span");
+                        self.leave_if(builder, undefined, Some(span), function.returns);
+                        Some(builder.ins().sdiv(lhs, rhs))
+                    }
+                    InstKind::Binary(op, lhs, rhs) => {
+                        let float = function.value_type(*lhs) == Type::Float;
+                        Some(binary(builder, *op, float, values[lhs], values[rhs]))
+                    }
+                    InstKind::Unary(op, value) => Some(match (op, function.value_type(*value)) {
+                        (UnaryOperator::Neg, Type::Float) => builder.ins().fneg(values[value]),
+                        (UnaryOperator::Neg, _) => builder.ins().ineg(values[value]),
+                        (UnaryOperator::Not, _) => {
+                            let not = builder.ins().icmp_imm(IntCC::Equal, values[value], 0);
+                            builder.ins().uextend(types::I32, not)
+                        }
+                    }),
+                    InstKind::Load(global) => {
+                        let value_type = self.cl_type(ir_module.global(*global).var_type)
+                            .expect("variables always have a value type");
+                        let global = self.module.declare_data_in_func(self.globals[global.0 as usize], builder.func);
+                        let address = builder.ins().global_value(pointer, global);
+                        Some(builder.ins().load(value_type, MemFlags::trusted(), address, 0))
+                    }
+                    InstKind::Store(global, value) => {
+                        let global = self.module.declare_data_in_func(self.globals[global.0 as usize], builder.func);
+                        let address = builder.ins().global_value(pointer, global);
+                        builder.ins().store(MemFlags::trusted(), values[value], address, 0);
+                        None
+                    }
+                    InstKind::Call(callee, args) => {
+                        let args: Vec<Value> = args.iter().map(|arg| values[arg]).collect();
+                        let callee = self.module.declare_func_in_func(self.functions[callee.0 as usize], builder.func);
+                        let call = builder.ins().call(callee, &args);
+                        let result = builder.inst_results(call).first().copied();
+
+                        let failure = self.module.declare_data_in_func(self.failure, builder.func);
+                        let address = builder.ins().global_value(pointer, failure);
+                        let failed = builder.ins().load(types::I32, MemFlags::trusted(), address, 0);
+                        self.leave_if(builder, failed, None, function.returns);
+
+                        result
+                    }
+                    InstKind::Print(value) => {
+                        let (print, args) = match function.value_type(*value) {
+                            Type::String => ("zrt_print_string", vec![values[value]]),
+                            Type::Int => ("zrt_print_int", vec![values[value]]),
+                            Type::Float => ("zrt_print_float", vec![values[value]]),
+                            Type::Boolean => ("zrt_print_bool", vec![values[value]]),
+                            _ => ("zrt_print_null", vec![]),
+                        };
+
+                        let callee = self.module.declare_func_in_func(self.prints[print], builder.func);
+                        builder.ins().call(callee, &args);
+                        None
+                    }
+                };
+
+                if let (Some(result), Some(value)) = (inst.result, result) {
+                    values.insert(result, value);
+                }
+            }
+
+            match &block.terminator {
+                Terminator::Return(Some(value)) => builder.ins().return_(&[values[value]]),
+                Terminator::Return(None) => builder.ins().return_(&[]),
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Returns from the function if `condition` is not zero, and continues in a new block
+    /// otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The builder of the function.
+    /// * `condition` - An integer that is not zero if the function has to be left.
+    /// * `failure` - The span of the check that failed if `condition` is set, which is
+    ///   recorded, or `None` if the failure was already recorded.
+    /// * `returns` - The return type of the function, whose zero value is returned.
+    fn leave_if(&mut self, builder: &mut FunctionBuilder, condition: Value, failure: Option<Span>, returns: Type) {
+        let (leave, next) = (builder.create_block(), builder.create_block());
+        builder.ins().brif(condition, leave, &[], next, &[]);
+
+        builder.switch_to_block(leave);
+        builder.seal_block(leave);
+        if let Some(span) = failure {
+            self.failures.push(span);
+
+            let pointer = self.module.target_config().pointer_type();
+            let data = self.module.declare_data_in_func(self.failure, builder.func);
+            let address = builder.ins().global_value(pointer, data);
+            let number = builder.ins().iconst(types::I32, self.failures.len() as i64);
+            builder.ins().store(MemFlags::trusted(), number, address, 0);
+        }
+        let zero: Vec<Value> = match self.cl_type(returns) {
+            Some(types::F32) => vec![builder.ins().f32const(0.0)],
+            Some(value_type) => vec![builder.ins().iconst(value_type, 0)],
+            None => vec![],
+        };
+        builder.ins().return_(&zero);
+
+        builder.switch_to_block(next);
+        builder.seal_block(next);
+    }
+
+    /// Places a string literal in a read-only data object.
+    fn intern(&mut self, value: &str) -> LowerResult<DataId> {
+        if let Some(id) = self.strings.get(value) {
+            return Ok(*id)
+        }
+
+        let mut contents = (value.len() as u32).to_le_bytes().to_vec();
+        contents.extend_from_slice(value.as_bytes());
+
+        let mut data = DataDescription::new();
+        data.define(contents.into_boxed_slice());
+        data.set_align(4);
+
+        let id = self.module.declare_anonymous_data(false, false)?;
+        self.module.define_data(id, &data)?;
+        self.strings.insert(value.to_string(), id);
+
+        Ok(id)
+    }
+}
+
+/// Applies a binary operator to two operands, which are floats if `float` is set and `i32`
+/// integers or booleans otherwise. Comparisons produce an `i32` boolean.
+fn binary(builder: &mut FunctionBuilder, op: BinaryOperator, float: bool, lhs: Value, rhs: Value) -> Value {
+    let (int_cc, float_cc) = match op {
+        BinaryOperator::Add if float => return builder.ins().fadd(lhs, rhs),
+        BinaryOperator::Sub if float => return builder.ins().fsub(lhs, rhs),
+        BinaryOperator::Mul if float => return builder.ins().fmul(lhs, rhs),
+        BinaryOperator::Div if float => return builder.ins().fdiv(lhs, rhs),
+        BinaryOperator::Add => return builder.ins().iadd(lhs, rhs),
+        BinaryOperator::Sub => return builder.ins().isub(lhs, rhs),
+        BinaryOperator::Mul => return builder.ins().imul(lhs, rhs),
+        BinaryOperator::Div => return builder.ins().sdiv(lhs, rhs),
+        BinaryOperator::And => return builder.ins().band(lhs, rhs),
+        BinaryOperator::Or => return builder.ins().bor(lhs, rhs),
+        BinaryOperator::Eq => (IntCC::Equal, FloatCC::Equal),
+        BinaryOperator::NotEq => (IntCC::NotEqual, FloatCC::NotEqual),
+        BinaryOperator::Less => (IntCC::SignedLessThan, FloatCC::LessThan),
+        BinaryOperator::Greater => (IntCC::SignedGreaterThan, FloatCC::GreaterThan),
+        BinaryOperator::LessEq => (IntCC::SignedLessThanOrEqual, FloatCC::LessThanOrEqual),
+        BinaryOperator::GreaterEq => (IntCC::SignedGreaterThanOrEqual, FloatCC::GreaterThanOrEqual),
+    };
+
+    let compared = if float {
+        builder.ins().fcmp(float_cc, lhs, rhs)
+    } else {
+        builder.ins().icmp(int_cc, lhs, rhs)
+    };
+
+    builder.ins().uextend(types::I32, compared)
+}
@@ -0,0 +1,5 @@
+pub mod c;
+#[cfg(feature = "cranelift")]
+pub mod cranelift;
+pub mod native;
+pub mod wasm;
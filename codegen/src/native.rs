@@ -0,0 +1,58 @@
+use codespan::Span;
+use ir::Module;
+
+/// The names of every native backend zxx knows about, whether or not it was compiled in.
+pub const BACKENDS: &[&str] = &["cranelift"];
+
+/// A backend that lowers an IR module to machine code for the host and executes it
+/// in process.
+///
+/// Native backends share the calling convention of the other targets: the module's `init`
+/// function runs first, followed by its entry point if it has one.
+pub trait NativeBackend {
+    /// Returns the name the backend is selected by on the command line.
+    fn name(&self) -> &'static str;
+
+    /// Compiles and runs the given module.
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - The module to run.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the backend could not generate code for the host, or if the
+    /// program failed at run time.
+    fn run(&mut self, module: &Module) -> Result<(), RunError>;
+}
+
+/// Why running a module failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunError {
+    /// The backend could not generate code for the host
+    Backend(String),
+    /// The program stopped because an operation failed, such as a division by zero. What it
+    /// printed before is kept.
+    Runtime {
+        message: String,
+        /// The span of the expression that failed
+        span: Span,
+    },
+}
+
+/// Looks up a native backend by name.
+///
+/// # Arguments
+///
+/// * `name` - One of the names in `BACKENDS`.
+///
+/// # Returns
+///
+/// Returns the backend, or `None` if zxx was built without the feature that provides it.
+pub fn backend(name: &str) -> Option<Box<dyn NativeBackend>> {
+    match name {
+        #[cfg(feature = "cranelift")]
+        "cranelift" => Some(Box::new(crate::cranelift::CraneliftBackend)),
+        _ => None,
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use codespan::Span;
+use ir::{BinaryOperator, Constant, FuncId, Function, InstKind, Module, Terminator, Type, UnaryOperator};
+
+/// The module name every host import is declared under.
+pub const HOST_MODULE: &str = "zpp";
+
+/// The name the linear memory is exported under, so hosts can read strings out of it. It is
+/// not a valid Z++ name, so it never clashes with the export of a function.
+pub const MEMORY_EXPORT: &str = "zpp.memory";
+
+/// The message of the runtime error an integer division reports when it divides by zero or
+/// overflows, which is the message the interpreter reports.
+const DIVISION_ERROR: &str = "The result of `/` is undefined, it overflows or divides by zero";
+
+/// The address the first string literal is placed at. Address `0` represents `null`.
+const DATA_START: u32 = 8;
+
+/// A WebAssembly module generated from a Z++ document, in both of its encodings.
+pub struct WasmModule {
+    /// The module in the WebAssembly text format (`.wat`)
+    pub text: String,
+    /// The module in the WebAssembly binary format (`.wasm`)
+    pub binary: Vec<u8>,
+}
+
+/// Translates an IR module into a WebAssembly module.
+///
+/// Every Z++ function is exported under its own name, and top-level statements run from the
+/// module's start function. Values of type `String` are pointers into the exported linear
+/// memory, where each string is stored as a little endian `i32` byte length followed by its
+/// UTF-8 bytes. The null pointer represents `null`.
+///
+/// Printing is delegated to the host, which has to provide the following functions in the
+/// `zpp` module: `print_int (i32)`, `print_float (f32)`, `print_bool (i32)`,
+/// `print_string (i32)` and `print_null ()`.
+///
+/// The host also provides `runtime_error (i32 i32 i32)`, which is called with the address of
+/// the message of a runtime error, such as a division by zero, and the start and end byte
+/// offsets of the code that failed. It should report the error as E013, the module traps
+/// right after it returns.
+///
+/// # Arguments
+///
+/// * `source_name` - The name of the file the module was lowered from.
+/// * `module` - The module to translate.
+///
+/// # Returns
+///
+/// Returns the generated `WasmModule`.
+pub fn emit(source_name: &str, module: &Module) -> WasmModule {
+    let mut backend = WasmBackend {
+        strings: HashMap::new(),
+        data: Vec::new(),
+    };
+
+    // string literals are placed in memory while generating code, so the code is
+    // generated before the module header
+    let mut code = String::new();
+    for (index, function) in module.functions.iter().enumerate() {
+        backend.emit_function(&mut code, module, FuncId(index as u32), function);
+    }
+    writeln!(code, "  (start $f{})", module.init.0).unwrap();
+
+    let mut out = String::new();
+    writeln!(out, ";; Generated by zxx from {}.", source_name.replace('\n', " ")).unwrap();
+    out.push_str("(module\n");
+    for (import, params) in [
+        ("print_int", " (param i32)"),
+        ("print_float", " (param f32)"),
+        ("print_bool", " (param i32)"),
+        ("print_string", " (param i32)"),
+        ("print_null", ""),
+        ("runtime_error", " (param i32 i32 i32)"),
+    ] {
+        writeln!(
+            out,
+            "  (import \"{}\" \"{}\" (func ${}{}))",
+            HOST_MODULE, import, import, params
+        ).unwrap();
+    }
+
+    let pages = (DATA_START as usize + backend.data.len()).div_ceil(65536).max(1);
+    writeln!(out, "  (memory (export \"{}\") {})", MEMORY_EXPORT, pages).unwrap();
+
+    if !backend.data.is_empty() {
+        let data: String = backend.data.iter().map(|byte| format!("\\{:02x}", byte)).collect();
+        writeln!(out, "  (data (i32.const {}) \"{}\")", DATA_START, data).unwrap();
+    }
+
+    for global in &module.globals {
+        let value_type = wasm_type(global.var_type).expect("variables always have a value type");
+        writeln!(
+            out,
+            "  (global $g_{} (mut {}) ({}.const 0))",
+            global.name, value_type, value_type
+        ).unwrap();
+    }
+
+    out.push_str(&code);
+    out.push_str(")\n");
+
+    let binary = wat::parse_str(&out)
+        .expect("the wasm backend always generates a valid module");
+
+    WasmModule {
+        text: out,
+        binary,
+    }
+}
+
+/// Maps a Z++ type to the WebAssembly value type that represents it.
+fn wasm_type(value_type: Type) -> Option<&'static str> {
+    match value_type {
+        Type::Void => None,
+        Type::String | Type::Int | Type::Boolean | Type::Null => Some("i32"),
+        Type::Float => Some("f32"),
+        Type::Reference => unreachable!("unresolved types are rejected by the checker"),
+    }
+}
+
+struct WasmBackend {
+    /// Addresses of string literals that were already placed in memory
+    strings: HashMap<String, u32>,
+    /// Contents of the data segment, which starts at `DATA_START`
+    data: Vec<u8>,
+}
+
+impl WasmBackend {
+    /// Emits a function, every IR value becomes a WebAssembly local named after its number.
+    fn emit_function(&mut self, code: &mut String, module: &Module, id: FuncId, function: &Function) {
+        write!(code, "  (func $f{}", id.0).unwrap();
+
+        // init runs as the start function
+        if id != module.init {
+            write!(code, " (export \"{}\")", function.name).unwrap();
+        }
+
+        for (_, value) in &function.params {
+            let value_type = wasm_type(function.value_type(*value)).expect("parameters always have a value type");
+            write!(code, " (param $v{} {})", value.0, value_type).unwrap();
+        }
+
+        if let Some(value_type) = wasm_type(function.returns) {
+            write!(code, " (result {})", value_type).unwrap();
+        }
+        code.push('\n');
+
+        // WebAssembly declares every local up front
+        for block in &function.blocks {
+            for result in block.insts.iter().filter_map(|inst| inst.result) {
+                let value_type = wasm_type(function.value_type(result)).expect("values always have a value type");
+                writeln!(code, "    (local $v{} {})", result.0, value_type).unwrap();
+            }
+        }
+
+        for block in &function.blocks {
+            for inst in &block.insts {
+                match &inst.kind {
+                    InstKind::Const(constant) => match constant {
+                        Constant::Int(value) => writeln!(code, "    i32.const {}", value).unwrap(),
+                        Constant::Float(value) => writeln!(code, "    f32.const {:?}", value).unwrap(),
+                        Constant::Bool(value) => writeln!(code, "    i32.const {}", *value as i32).unwrap(),
+                        Constant::String(value) => {
+                            let address = self.intern(value);
+                            writeln!(code, "    i32.const {}", address).unwrap();
+                        }
+                        Constant::Null => code.push_str("    i32.const 0\n"),
+                    },
+                    InstKind::Copy(value) => writeln!(code, "    local.get $v{}", value.0).unwrap(),
+                    InstKind::IntToFloat(value) => {
+                        writeln!(code, "    local.get $v{}", value.0).unwrap();
+                        code.push_str("    f32.convert_i32_s\n");
+                    }
+                    InstKind::Binary(BinaryOperator::Div, lhs, rhs) if function.value_type(*lhs) == Type::Int => {
+                        self.emit_division_check(code, lhs.0, rhs.0, inst.span);
+                        writeln!(code, "    local.get $v{}", lhs.0).unwrap();
+                        writeln!(code, "    local.get $v{}", rhs.0).unwrap();
+                        code.push_str("    i32.div_s\n");
+                    }
+                    InstKind::Binary(op, lhs, rhs) => {
+                        writeln!(code, "    local.get $v{}", lhs.0).unwrap();
+                        writeln!(code, "    local.get $v{}", rhs.0).unwrap();
+                        writeln!(code, "    {}", binary_instruction(*op, function.value_type(*lhs))).unwrap();
+                    }
+                    InstKind::Unary(op, value) => match (op, function.value_type(*value)) {
+                        (UnaryOperator::Neg, Type::Int) => {
+                            code.push_str("    i32.const 0\n");
+                            writeln!(code, "    local.get $v{}", value.0).unwrap();
+                            code.push_str("    i32.sub\n");
+                        }
+                        (UnaryOperator::Neg, _) => {
+                            writeln!(code, "    local.get $v{}", value.0).unwrap();
+                            code.push_str("    f32.neg\n");
+                        }
+                        (UnaryOperator::Not, _) => {
+                            writeln!(code, "    local.get $v{}", value.0).unwrap();
+                            code.push_str("    i32.eqz\n");
+                        }
+                    },
+                    InstKind::Load(global) => {
+                        writeln!(code, "    global.get $g_{}", module.global(*global).name).unwrap();
+                    }
+                    InstKind::Store(global, value) => {
+                        writeln!(code, "    local.get $v{}", value.0).unwrap();
+                        writeln!(code, "    global.set $g_{}", module.global(*global).name).unwrap();
+                    }
+                    InstKind::Call(callee, args) => {
+                        for arg in args {
+                            writeln!(code, "    local.get $v{}", arg.0).unwrap();
+                        }
+                        writeln!(code, "    call $f{}", callee.0).unwrap();
+                    }
+                    InstKind::Print(value) => {
+                        let print = match function.value_type(*value) {
+                            Type::String => "print_string",
+                            Type::Int => "print_int",
+                            Type::Float => "print_float",
+                            Type::Boolean => "print_bool",
+                            _ => {
+                                code.push_str("    call $print_null\n");
+                                continue;
+                            }
+                        };
+
+                        writeln!(code, "    local.get $v{}", value.0).unwrap();
+                        writeln!(code, "    call ${}", print).unwrap();
+                    }
+                }
+
+                if let Some(result) = inst.result {
+                    writeln!(code, "    local.set $v{}", result.0).unwrap();
+                }
+            }
+
+            match &block.terminator {
+                Terminator::Return(Some(value)) => {
+                    writeln!(code, "    local.get $v{}", value.0).unwrap();
+                    code.push_str("    return\n");
+                }
+                // the implicit return at the end of the function
+                Terminator::Return(None) => {}
+            }
+        }
+
+        code.push_str("  )\n");
+    }
+
+    /// Emits the check before an integer division, which reports a runtime error to the host
+    /// and traps if the divisor is zero, or if the smallest integer is divided by -1. Without
+    /// it, `i32.div_s` would trap without saying why.
+    fn emit_division_check(&mut self, code: &mut String, lhs: u32, rhs: u32, span: Option<Span>) {
+        let (start, end) = span.map_or((0, 0), |span| (span.start().0, span.end().0));
+
+        writeln!(code, "    local.get $v{}", rhs).unwrap();
+        code.push_str("    i32.eqz\n");
+        writeln!(code, "    local.get $v{}", lhs).unwrap();
+        code.push_str("    i32.const -2147483648\n");
+        code.push_str("    i32.eq\n");
+        writeln!(code, "    local.get $v{}", rhs).unwrap();
+        code.push_str("    i32.const -1\n");
+        code.push_str("    i32.eq\n");
+        code.push_str("    i32.and\n");
+        code.push_str("    i32.or\n");
+        code.push_str("    if\n");
+        writeln!(code, "      i32.const {}", self.intern(DIVISION_ERROR)).unwrap();
+        writeln!(code, "      i32.const {}", start).unwrap();
+        writeln!(code, "      i32.const {}", end).unwrap();
+        code.push_str("      call $runtime_error\n");
+        code.push_str("      unreachable\n");
+        code.push_str("    end\n");
+    }
+
+    /// Places a string literal in the data segment, returning its address.
+    fn intern(&mut self, value: &str) -> u32 {
+        if let Some(address) = self.strings.get(value) {
+            return *address
+        }
+
+        // keep the length prefix aligned
+        while !self.data.len().is_multiple_of(4) {
+            self.data.push(0);
+        }
+
+        let address = DATA_START + self.data.len() as u32;
+        self.data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(value.as_bytes());
+        self.strings.insert(value.to_string(), address);
+
+        address
+    }
+}
+
+/// Returns the instruction that applies a binary operator to two operands of the given type.
+fn binary_instruction(op: BinaryOperator, operand_type: Type) -> &'static str {
+    if operand_type == Type::Float {
+        return match op {
+            BinaryOperator::Add => "f32.add",
+            BinaryOperator::Sub => "f32.sub",
+            BinaryOperator::Mul => "f32.mul",
+            BinaryOperator::Div => "f32.div",
+            BinaryOperator::Eq => "f32.eq",
+            BinaryOperator::NotEq => "f32.ne",
+            BinaryOperator::Less => "f32.lt",
+            BinaryOperator::Greater => "f32.gt",
+            BinaryOperator::LessEq => "f32.le",
+            BinaryOperator::GreaterEq => "f32.ge",
+            BinaryOperator::And | BinaryOperator::Or => unreachable!("logical operators only apply to booleans"),
+        }
+    }
+
+    // integers and booleans are both represented as i32
+    match op {
+        BinaryOperator::Add => "i32.add",
+        BinaryOperator::Sub => "i32.sub",
+        BinaryOperator::Mul => "i32.mul",
+        BinaryOperator::Div => "i32.div_s",
+        BinaryOperator::Eq => "i32.eq",
+        BinaryOperator::NotEq => "i32.ne",
+        BinaryOperator::Less => "i32.lt_s",
+        BinaryOperator::Greater => "i32.gt_s",
+        BinaryOperator::LessEq => "i32.le_s",
+        BinaryOperator::GreaterEq => "i32.ge_s",
+        BinaryOperator::And => "i32.and",
+        BinaryOperator::Or => "i32.or",
+    }
+}
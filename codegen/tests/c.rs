@@ -0,0 +1,147 @@
+use std::process::{Command, Output};
+use ast::AstNode;
+use ast::checker::Checker;
+use ast::interpreter::Interpreter;
+use ast::lexer::Lexer;
+use ast::parser::Parser;
+use common::features::Features;
+use common::temp::TempFile;
+use ir::passes::{optimize, MAX_OPT_LEVEL};
+
+fn compile(source: &str, opt_level: u8) -> String {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let ast = Parser::new(tokens).parse();
+
+    Checker::new("test.zpp", source)
+        .with_features(Features::all())
+        .check(&ast)
+        .expect("the test program should pass the checker");
+
+    let mut module = ir::lower::lower(&ast);
+    optimize(&mut module, opt_level, |_, _, _| {});
+
+    codegen::c::emit("test.zpp", &module)
+}
+
+/// Builds generated C with the system compiler as strict C99, treating every warning except
+/// for unused values, which unoptimized code is full of, as an error, and runs the program.
+fn build_and_run(code: &str) -> Output {
+    let source = TempFile::new("c", code).unwrap();
+    let binary = TempFile::new("out", "").unwrap();
+
+    let compiled = Command::new("cc")
+        .args(["-std=c99", "-pedantic-errors", "-Wall", "-Wno-unused-variable", "-Werror", "-o"])
+        .arg(binary.path())
+        .arg(source.path())
+        .output()
+        .expect("a C compiler should be installed as `cc`");
+    assert!(compiled.status.success(), "cc failed:\n{}", String::from_utf8_lossy(&compiled.stderr));
+
+    Command::new(binary.path()).output().unwrap()
+}
+
+/// Runs a program with the interpreter, which calls `Main` after the top-level statements like
+/// the generated `main` function does, and returns the lines it printed.
+fn interpret(source: &str) -> Vec<String> {
+    let source = format!("{}\nMain()\n", source);
+    let tokens = Lexer::new("test.zpp", &source).tokenize();
+    let AstNode::Document { body } = Parser::new(tokens).parse() else {
+        unreachable!()
+    };
+    let AstNode::Block { body } = *body else {
+        unreachable!()
+    };
+
+    let mut interpreter = Interpreter::new(Vec::new());
+    interpreter.run(&body).expect("the test program should run");
+
+    String::from_utf8(interpreter.output().clone()).unwrap().lines().map(str::to_string).collect()
+}
+
+fn lines(output: &Output) -> Vec<&str> {
+    std::str::from_utf8(&output.stdout).unwrap().lines().collect()
+}
+
+#[test]
+fn programs_print_what_the_interpreter_prints() {
+    let source = r#"
+        static int SIZE = 4 * 1024
+        mut int count = SIZE - 1
+        String name = "héllo?"
+        func show(int n, float f) {
+            print(n * 3 - SIZE)
+            print(f / 4)
+            print(f * 4000000)
+            print(f / 3000000)
+            print(-f / 0.1)
+            print(-n)
+            print(n / 7)
+        }
+        func Main() {
+            show(count, 2.5)
+            count = 2147483647
+            print(count + 1)
+            print(name)
+            print(null)
+            print(count > 5)
+        }
+    "#;
+    let expected = interpret(source);
+    assert_eq!(expected[..3], ["8189", "0.625", "10000000"]);
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        let output = build_and_run(&compile(source, opt_level));
+
+        assert!(output.status.success(), "at -O{}", opt_level);
+        assert_eq!(lines(&output), expected, "at -O{}", opt_level);
+    }
+}
+
+#[test]
+fn literals_keep_their_type() {
+    let source = r#"
+        func Main() {
+            print(7 / 2.0)
+            float third = 1 / 3.0
+            print(third)
+            int exact = 16777217
+            print(exact + 1)
+            print(2147483647)
+        }
+    "#;
+    assert_eq!(interpret(source), ["3.5", "0.33333334", "16777218", "2147483647"]);
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        let output = build_and_run(&compile(source, opt_level));
+
+        assert!(output.status.success(), "at -O{}", opt_level);
+        assert_eq!(lines(&output), ["3.5", "0.33333334", "16777218", "2147483647"], "at -O{}", opt_level);
+    }
+}
+
+#[test]
+fn undefined_divisions_are_runtime_errors() {
+    let source = r#"
+        mut int divisor = 0
+        func divide(int n) {
+            print(n / divisor)
+        }
+        func Main() {
+            print(1)
+            divide(7)
+            print(2)
+        }
+    "#;
+    let overflow = source.replace("mut int divisor = 0", "mut int divisor = -1")
+        .replace("divide(7)", "divide(-2147483647 - 1)");
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        for source in [source, &overflow] {
+            let output = build_and_run(&compile(source, opt_level));
+
+            assert_eq!(output.status.code(), Some(1), "at -O{}:\n{}", opt_level, source);
+            assert_eq!(lines(&output), ["1"], "at -O{}", opt_level);
+            assert!(String::from_utf8_lossy(&output.stderr).contains("error[E013]"), "at -O{}", opt_level);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use ast::checker::Checker;
+use ast::lexer::Lexer;
+use ast::parser::Parser;
+use codegen::wasm::{emit, WasmModule, HOST_MODULE, MEMORY_EXPORT};
+use common::features::Features;
+use ir::passes::{optimize, MAX_OPT_LEVEL};
+use wasmi::{Caller, Engine, Extern, Instance, Linker, Module, Store};
+
+fn compile(source: &str, opt_level: u8) -> WasmModule {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    let ast = Parser::new(tokens).parse();
+
+    Checker::new("test.zpp", source)
+        .with_features(Features::all())
+        .check(&ast)
+        .expect("the test program should pass the checker");
+
+    let mut module = ir::lower::lower(&ast);
+    optimize(&mut module, opt_level, |_, _, _| {});
+
+    emit("test.zpp", &module)
+}
+
+/// Reads a length prefixed string out of the module's memory.
+fn read_string(caller: &Caller<'_, Vec<String>>, address: i32) -> String {
+    let memory = match caller.get_export(MEMORY_EXPORT) {
+        Some(Extern::Memory(memory)) => memory,
+        _ => panic!("the module should export its memory"),
+    };
+
+    let mut len = [0; 4];
+    memory.read(caller, address as usize, &mut len).unwrap();
+
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    memory.read(caller, address as usize + 4, &mut bytes).unwrap();
+
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Instantiates a module with host imports that record every printed line.
+fn instantiate(module: &WasmModule) -> (Store<Vec<String>>, Instance) {
+    let engine = Engine::default();
+    let module = Module::new(&engine, &module.binary).unwrap();
+    let mut store = Store::new(&engine, Vec::new());
+    let mut linker = Linker::<Vec<String>>::new(&engine);
+
+    linker.func_wrap(HOST_MODULE, "print_int", |mut caller: Caller<'_, Vec<String>>, value: i32| {
+        caller.data_mut().push(value.to_string());
+    }).unwrap();
+    linker.func_wrap(HOST_MODULE, "print_float", |mut caller: Caller<'_, Vec<String>>, value: f32| {
+        caller.data_mut().push(value.to_string());
+    }).unwrap();
+    linker.func_wrap(HOST_MODULE, "print_bool", |mut caller: Caller<'_, Vec<String>>, value: i32| {
+        caller.data_mut().push((value != 0).to_string());
+    }).unwrap();
+    linker.func_wrap(HOST_MODULE, "print_string", |mut caller: Caller<'_, Vec<String>>, address: i32| {
+        let line = if address == 0 {
+            "null".to_string()
+        } else {
+            read_string(&caller, address)
+        };
+        caller.data_mut().push(line);
+    }).unwrap();
+    linker.func_wrap(HOST_MODULE, "print_null", |mut caller: Caller<'_, Vec<String>>| {
+        caller.data_mut().push("null".to_string());
+    }).unwrap();
+    linker.func_wrap(HOST_MODULE, "runtime_error", |mut caller: Caller<'_, Vec<String>>, message: i32, start: i32, end: i32| {
+        let line = format!("error[E013]: {} at {}..{}", read_string(&caller, message), start, end);
+        caller.data_mut().push(line);
+    }).unwrap();
+
+    let instance = linker.instantiate_and_start(&mut store, &module).unwrap();
+    (store, instance)
+}
+
+#[test]
+fn generated_modules_validate() {
+    let source = r#"
+        int count = 3
+        String name = "Z++"
+        func greet(String who, float times) {
+            print(who)
+            print(times)
+        }
+    "#;
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        let module = compile(source, opt_level);
+        wasmparser::validate(&module.binary).expect("the module should be valid");
+        assert_eq!(wat::parse_str(&module.text).unwrap(), module.binary);
+    }
+}
+
+#[test]
+fn top_level_statements_run_on_instantiation() {
+    let module = compile(r#"
+        int count = 3
+        String name = "héllo"
+        String nothing = null
+        print(count)
+        print(name)
+        print(nothing)
+        print(true)
+    "#, 0);
+
+    let (store, _) = instantiate(&module);
+    assert_eq!(store.data(), &["3", "héllo", "null", "true"]);
+}
+
+#[test]
+fn functions_are_exported() {
+    let module = compile(r#"
+        mut int count = 2
+        func show(float value, bool flag) {
+            print(value)
+            print(flag)
+        }
+        func Main() {
+            float half = 0.5
+            show(half, false)
+            show(count, true)
+            count = 5
+            print(count)
+        }
+    "#, 0);
+
+    let (mut store, instance) = instantiate(&module);
+    let main = instance.get_typed_func::<(), ()>(&store, "Main").unwrap();
+    main.call(&mut store, ()).unwrap();
+
+    let show = instance.get_typed_func::<(f32, i32), ()>(&store, "show").unwrap();
+    show.call(&mut store, (1.25, 1)).unwrap();
+
+    assert_eq!(store.data(), &["0.5", "false", "2", "true", "5", "1.25", "true"]);
+}
+
+#[test]
+fn optimizations_preserve_behavior() {
+    let source = r#"
+        int count = 2
+        float ratio = 4
+        func show(float value) {
+            float copy = value
+            print(copy)
+        }
+        func Main() {
+            int first = count
+            int second = count
+            float widened = first
+            show(widened)
+            show(7)
+            print(second)
+            print(ratio)
+        }
+    "#;
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        let (mut store, instance) = instantiate(&compile(source, opt_level));
+        let main = instance.get_typed_func::<(), ()>(&store, "Main").unwrap();
+        main.call(&mut store, ()).unwrap();
+
+        assert_eq!(store.data(), &["2", "7", "2", "4"], "at -O{}", opt_level);
+    }
+}
+
+#[test]
+fn statics_and_operators_evaluate_consistently() {
+    let source = r#"
+        static int SIZE = 4 * 1024
+        static float HALF = SIZE / 2
+        static bool LARGE = SIZE > 1000 && !false
+        int count = SIZE - 1
+        func show(int n, float f) {
+            static int OFFSET = SIZE * 2 + 1
+            print(n * 3 - OFFSET)
+            print(f / 4)
+            print(-n)
+            print(n >= 5 == true)
+            print(n + f)
+        }
+        func Main() {
+            print(SIZE)
+            print(HALF)
+            print(LARGE)
+            show(count, 2.5)
+            int big = 2147483647
+            print(big + 1)
+            print((1 + 2) * 3 - 8 / 2)
+        }
+    "#;
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        let (mut store, instance) = instantiate(&compile(source, opt_level));
+        let main = instance.get_typed_func::<(), ()>(&store, "Main").unwrap();
+        main.call(&mut store, ()).unwrap();
+
+        assert_eq!(
+            store.data(),
+            &["4096", "2048", "true", "4092", "0.625", "-4095", "true", "4097.5", "-2147483648", "5"],
+            "at -O{}",
+            opt_level
+        );
+    }
+}
+
+#[test]
+fn literals_keep_their_type() {
+    let source = r#"
+        func Main() {
+            print(7 / 2.0)
+            float third = 1 / 3.0
+            print(third)
+            int exact = 16777217
+            print(exact + 1)
+            print(2147483647)
+        }
+    "#;
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        let (mut store, instance) = instantiate(&compile(source, opt_level));
+        let main = instance.get_typed_func::<(), ()>(&store, "Main").unwrap();
+        main.call(&mut store, ()).unwrap();
+
+        assert_eq!(store.data(), &["3.5", "0.33333334", "16777218", "2147483647"], "at -O{}", opt_level);
+    }
+}
+
+#[test]
+fn undefined_divisions_are_runtime_errors() {
+    let source = r#"
+mut int divisor = 0
+func divide(int n) {
+    print(n / divisor)
+    print(3)
+}
+func Main() {
+    print(1)
+    divide(7)
+    print(2)
+}
+"#;
+    let overflow = source.replace("mut int divisor = 0", "mut int divisor = -1")
+        .replace("divide(7)", "divide(-2147483647 - 1)");
+
+    for opt_level in 0..=MAX_OPT_LEVEL {
+        for source in [source, &overflow] {
+            let start = source.find("n / divisor").unwrap();
+            let (mut store, instance) = instantiate(&compile(source, opt_level));
+            let main = instance.get_typed_func::<(), ()>(&store, "Main").unwrap();
+
+            // the module traps once the host was told about the error
+            assert!(main.call(&mut store, ()).is_err(), "at -O{}", opt_level);
+            assert_eq!(store.data(), &[
+                "1".to_string(),
+                format!("error[E013]: The result of `/` is undefined, it overflows or divides by zero at {}..{}", start, start + 11),
+            ], "at -O{}", opt_level);
+        }
+    }
+}
+
+#[test]
+fn functions_can_be_named_like_the_memory() {
+    let module = compile("func memory() {\n    print(1)\n}\n", 0);
+
+    let (mut store, instance) = instantiate(&module);
+    let memory = instance.get_typed_func::<(), ()>(&store, "memory").unwrap();
+    memory.call(&mut store, ()).unwrap();
+
+    assert_eq!(store.data(), &["1"]);
+    assert!(instance.get_memory(&store, MEMORY_EXPORT).is_some());
+}
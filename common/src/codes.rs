@@ -0,0 +1,683 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use codespan_reporting::diagnostic::Severity;
+
+/// The code of a kind of diagnostic, which diagnostics are reported with so that they can be
+/// looked up with `zxx explain`, configured and recognized by tools. Codes starting with `E`
+/// are errors, and codes starting with `W` are warnings, whose level can be configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Code {
+    E001,
+    E002,
+    E003,
+    E004,
+    E005,
+    E006,
+    E007,
+    E008,
+    E009,
+    E010,
+    E011,
+    E012,
+    E013,
+    E014,
+    E015,
+    E016,
+    E017,
+    E018,
+    W001,
+    W002,
+    W003,
+    W004,
+    W005,
+    W006,
+    W007,
+    W008,
+}
+
+/// Struct describing a code in the registry.
+#[derive(Debug)]
+pub struct CodeInfo {
+    pub code: Code,
+    /// A short name in kebab case, which SARIF rules are named by
+    pub name: &'static str,
+    /// A one-line description of the problem
+    pub title: &'static str,
+    /// The severity diagnostics with the code have unless they are configured otherwise
+    pub severity: Severity,
+    /// Describes the problem and how to fix it, with examples
+    pub explanation: &'static str,
+}
+
+/// Every code, in order.
+pub const REGISTRY: &[CodeInfo] = &[
+    CodeInfo {
+        code: Code::E001,
+        name: "lex-error",
+        title: "The input contains text that is not a token",
+        severity: Severity::Error,
+        explanation: "\
+The input contains characters that do not start any token of Z++, such as `$` or a string
+literal that is not closed before the end of the line.
+
+Erroneous code example:
+
+    int price = 5$
+
+Remove the characters, or close the string literal:
+
+    int price = 5
+",
+    },
+    CodeInfo {
+        code: Code::E002,
+        name: "unknown-variable",
+        title: "A variable is used that is not declared in scope",
+        severity: Severity::Error,
+        explanation: "\
+A variable is read or assigned to, but no variable of that name is declared in the current
+scope or any scope around it. Variables must be declared before they are used, and the
+parameters and variables of a function are not visible outside of it.
+
+Erroneous code example:
+
+    int total = count + 1
+
+Declare the variable first:
+
+    int count = 1
+    int total = count + 1
+",
+    },
+    CodeInfo {
+        code: Code::E003,
+        name: "mismatched-types",
+        title: "A value does not have the type that is expected",
+        severity: Severity::Error,
+        explanation: "\
+A value is stored in a variable, assigned to it or passed as an argument, but its type does
+not match the type of the variable or parameter. An `int` may be stored where a `float` is
+expected, but no other conversions happen implicitly.
+
+Erroneous code example:
+
+    bool done = 3
+
+Change the type of the variable, or the value:
+
+    int done = 3
+",
+    },
+    CodeInfo {
+        code: Code::E004,
+        name: "duplicate-definition",
+        title: "A function or variable is defined more than once",
+        severity: Severity::Error,
+        explanation: "\
+Two functions have the same name, or two variables of the same name are declared in the same
+scope. The built-in function `print` cannot be defined either.
+
+Erroneous code example:
+
+    int x = 1
+    int x = 2
+
+Rename one of them, or assign to the existing variable instead:
+
+    mut int x = 1
+    x = 2
+",
+    },
+    CodeInfo {
+        code: Code::E005,
+        name: "argument-count",
+        title: "A function is called with the wrong number of arguments",
+        severity: Severity::Error,
+        explanation: "\
+A function is called with more or fewer arguments than it has parameters. `print` takes
+exactly one argument.
+
+Erroneous code example:
+
+    func add(int a, int b) {
+        print(a + b)
+    }
+
+    add(1)
+
+Pass an argument for every parameter:
+
+    add(1, 2)
+",
+    },
+    CodeInfo {
+        code: Code::E006,
+        name: "unknown-function",
+        title: "A function is called that is not declared",
+        severity: Severity::Error,
+        explanation: "\
+A function is called, but no function of that name is declared in the file. Functions may be
+called before the place they are declared at, but they must be declared somewhere.
+
+Erroneous code example:
+
+    greet(1)
+
+Declare the function:
+
+    func greet(int times) {
+        print(times)
+    }
+
+    greet(1)
+",
+    },
+    CodeInfo {
+        code: Code::E007,
+        name: "unknown-type",
+        title: "The type of a variable or parameter cannot be found",
+        severity: Severity::Error,
+        explanation: "\
+A variable or parameter is declared with a type that does not exist. The types of Z++ are
+`int`, `float`, `bool`, `String` and `null`, and their names are case sensitive.
+
+Erroneous code example:
+
+    string name = \"Zed\"
+
+Use one of the types of Z++:
+
+    String name = \"Zed\"
+",
+    },
+    CodeInfo {
+        code: Code::E008,
+        name: "invalid-operand",
+        title: "An operator is applied to values of the wrong type",
+        severity: Severity::Error,
+        explanation: "\
+An operator is applied to a value, or to a pair of values, it is not defined for. Arithmetic
+works on numbers, `!`, `&&` and `||` work on booleans, and `==` and `!=` compare values of
+the same type.
+
+Erroneous code example:
+
+    int x = true * 2
+
+Use operands of the types the operator expects:
+
+    int x = 1 * 2
+",
+    },
+    CodeInfo {
+        code: Code::E009,
+        name: "static-depends-on-runtime",
+        title: "A static is initialized from a value only known at run time",
+        severity: Severity::Error,
+        explanation: "\
+The value of a static is computed when the program is compiled, so it can only be
+initialized from literals and other statics. A variable that is not static only has a value
+once the program runs.
+
+Erroneous code example:
+
+    int base = 10
+    static int limit = base * 2
+
+Make the value it depends on static too:
+
+    static int base = 10
+    static int limit = base * 2
+",
+    },
+    CodeInfo {
+        code: Code::E010,
+        name: "assignment-to-static",
+        title: "A static is assigned to after its declaration",
+        severity: Severity::Error,
+        explanation: "\
+Statics keep the value they are declared with, so they cannot be assigned to.
+
+Erroneous code example:
+
+    static int limit = 10
+    limit = 20
+
+Declare the variable with `mut` instead of `static` if it has to change:
+
+    mut int limit = 10
+    limit = 20
+",
+    },
+    CodeInfo {
+        code: Code::E011,
+        name: "static-evaluation",
+        title: "A static cannot be evaluated at compile time",
+        severity: Severity::Error,
+        explanation: "\
+Computing the value of a static overflows or divides by zero, so the static has no value.
+
+Erroneous code example:
+
+    static int zero = 0
+    static int ratio = 10 / zero
+
+Change the computation so that it is defined:
+
+    static int one = 1
+    static int ratio = 10 / one
+",
+    },
+    CodeInfo {
+        code: Code::E012,
+        name: "syntax-error",
+        title: "The input does not follow the grammar of Z++",
+        severity: Severity::Error,
+        explanation: "\
+The tokens of the input do not form a declaration, statement or expression of Z++, such as a
+declaration without a value or a block that is not closed. Statements end at the end of the
+line, and are not followed by a `;`.
+
+Erroneous code example:
+
+    int x =
+
+Complete the statement:
+
+    int x = 1
+",
+    },
+    CodeInfo {
+        code: Code::E013,
+        name: "runtime-error",
+        title: "Evaluating the input failed",
+        severity: Severity::Error,
+        explanation: "\
+Evaluating the input in the REPL, or running a program with `zxx run` or built for C or
+WebAssembly, failed because an operation overflowed or divided by zero, or because functions
+called each other too deeply. The program stops there, keeping what it printed before.
+
+Erroneous code example:
+
+    int zero = 0
+    int ratio = 10 / zero
+
+Check the values an operation is applied to before applying it, and make sure that
+recursive functions stop calling themselves.
+",
+    },
+    CodeInfo {
+        code: Code::E014,
+        name: "invalid-attribute",
+        title: "An attribute does not set the level of a diagnostic code",
+        severity: Severity::Error,
+        explanation: "\
+An attribute in front of a function is not one of `@allow`, `@warn`, `@deny` and `@forbid`,
+names a code that does not exist, or sets a level that cannot be set. Only warnings can be
+allowed or warned about, since code with errors cannot be compiled, and a code that is
+forbidden cannot be set to any other level.
+
+Erroneous code example:
+
+    @allow(E003)
+    func half(int a) {
+        bool b = a / 2
+    }
+
+Fix the error instead:
+
+    func half(int a) {
+        int b = a / 2
+    }
+",
+    },
+    CodeInfo {
+        code: Code::E015,
+        name: "return-outside-function",
+        title: "`return` is used outside of a function body",
+        severity: Severity::Error,
+        explanation: "\
+A `return` leaves the function it is in, so it can only be used in a function body. The
+statements at the top level of a file always run to the end.
+
+Erroneous code example:
+
+    int x = 1
+    return
+
+Move the statements that should not always run into a function:
+
+    func greet() {
+        return
+    }
+",
+    },
+    CodeInfo {
+        code: Code::E016,
+        name: "assignment-to-immutable",
+        title: "A variable is assigned to that is not mutable",
+        severity: Severity::Error,
+        explanation: "\
+Variables keep the value they are declared with unless they are declared with `mut`, and
+parameters always keep the value they are called with.
+
+Erroneous code example:
+
+    int count = 0
+    count = count + 1
+
+Declare the variable as mutable:
+
+    mut int count = 0
+    count = count + 1
+",
+    },
+    CodeInfo {
+        code: Code::E017,
+        name: "integer-out-of-range",
+        title: "An integer literal is larger than the largest `int`",
+        severity: Severity::Error,
+        explanation: "\
+An `int` is a 32-bit signed integer, so an integer literal cannot be larger than 2147483647.
+A literal is never rounded or wrapped into the range. The smallest `int` is written as
+`-2147483647 - 1`, since the literal of `-2147483648` is out of range before it is negated.
+
+Erroneous code example:
+
+    int population = 8000000000
+
+Use a `float`, which holds larger numbers but is only exact for integers up to 16777216:
+
+    float population = 8000000000.0
+",
+    },
+    CodeInfo {
+        code: Code::E018,
+        name: "feature-not-enabled",
+        title: "An incubating language feature is used without enabling it",
+        severity: Severity::Error,
+        explanation: "\
+The code uses a feature of Z++ whose RFC was accepted, but which is still incubating. Such
+features are not compatible with ZSharp scripts, so they have to be enabled explicitly. The
+features are named after their RFC, and `static` declarations are `static-declarations`.
+
+Erroneous code example:
+
+    static int SIZE = 1024
+
+Enable the feature on the command line with `--feature static-declarations`, or for the
+whole project in the `[features]` table of `zxx.toml`:
+
+    [features]
+    static-declarations = true
+",
+    },
+    CodeInfo {
+        code: Code::W001,
+        name: "float-comparison",
+        title: "Floats are compared with `==` or `!=`",
+        severity: Severity::Warning,
+        explanation: "\
+Floats are compared for equality. Most fractions cannot be stored exactly, so a computation
+often gives a value very close to the one it should give instead, and the values are not
+equal. This is a warning, which `@allow(W001)` in front of a function turns off.
+
+Erroneous code example:
+
+    float total = 0.1 + 0.2
+    bool exact = total == 0.3
+
+Check whether the difference is small instead:
+
+    float total = 0.1 + 0.2
+    float difference = total - 0.3
+    bool close = difference < 0.0001 && difference > -0.0001
+",
+    },
+    CodeInfo {
+        code: Code::W002,
+        name: "unused-variable",
+        title: "A variable is never read",
+        severity: Severity::Warning,
+        explanation: "\
+A variable is declared, but its value is never read, so it can be removed. Variables whose
+name starts with an underscore, such as `_size`, are never reported. This lint is run by
+`zxx lint`, which removes variables that are not assigned to either with `--fix`.
+
+Erroneous code example:
+
+    func area(int width, int height) {
+        int size = width * height
+    }
+
+    area(3, 4)
+
+Use the variable, or remove it:
+
+    func area(int width, int height) {
+        int size = width * height
+        print(size)
+    }
+
+    area(3, 4)
+",
+    },
+    CodeInfo {
+        code: Code::W003,
+        name: "unused-function",
+        title: "A function is never called",
+        severity: Severity::Warning,
+        explanation: "\
+A function is declared, but it is never called outside of its own body. `Main`, which is
+called after the top-level statements ran, and functions whose name starts with an
+underscore are never reported. This lint is run by `zxx lint`.
+
+Erroneous code example:
+
+    func greet(String name) {
+        print(name)
+    }
+
+Call the function, or remove it:
+
+    func greet(String name) {
+        print(name)
+    }
+
+    greet(\"Zed\")
+",
+    },
+    CodeInfo {
+        code: Code::W004,
+        name: "unused-parameter",
+        title: "A parameter is never read",
+        severity: Severity::Warning,
+        explanation: "\
+A function has a parameter whose value it never reads. Parameters whose name starts with an
+underscore are never reported, and `zxx lint --fix` renames parameters that are not assigned
+to either that way.
+
+Erroneous code example:
+
+    func greet(String name, int times) {
+        print(name)
+    }
+
+    greet(\"Zed\", 2)
+
+Use the parameter, remove it together with the arguments passed for it, or start its name
+with an underscore:
+
+    func greet(String name, int _times) {
+        print(name)
+    }
+
+    greet(\"Zed\", 2)
+",
+    },
+    CodeInfo {
+        code: Code::W005,
+        name: "shadowed-binding",
+        title: "A variable or parameter hides a global variable",
+        severity: Severity::Warning,
+        explanation: "\
+A parameter or a variable in a function body has the same name as a global variable declared
+before the function, so the global variable cannot be used in the function. Uses of the name
+that were meant for the global variable read the parameter or local variable instead. This
+lint is run by `zxx lint`.
+
+Erroneous code example:
+
+    int count = 0
+    print(count)
+
+    func add(int count) {
+        print(count + 1)
+    }
+
+    add(1)
+
+Rename the parameter or variable:
+
+    int count = 0
+    print(count)
+
+    func add(int amount) {
+        print(amount + 1)
+    }
+
+    add(1)
+",
+    },
+    CodeInfo {
+        code: Code::W006,
+        name: "never-reassigned",
+        title: "A mutable variable is never reassigned",
+        severity: Severity::Warning,
+        explanation: "\
+A variable is declared with `mut`, but it is never assigned to, so it does not need to be
+mutable. Leaving out `mut` makes the compiler check that it keeps its value. This lint is run
+by `zxx lint`, which removes `mut` from the declaration with `--fix`.
+
+Erroneous code example:
+
+    mut int limit = 10
+    print(limit)
+
+Remove `mut` from the declaration:
+
+    int limit = 10
+    print(limit)
+",
+    },
+    CodeInfo {
+        code: Code::W007,
+        name: "unreachable-code",
+        title: "Statements follow a `return`",
+        severity: Severity::Warning,
+        explanation: "\
+A `return` leaves the function it is in, so the statements after it in the function body
+never run. This lint is run by `zxx lint`, which removes the statements with `--fix`.
+
+Erroneous code example:
+
+    func greet() {
+        return
+        print(\"Hello\")
+    }
+
+    greet()
+
+Remove the statements, or the `return`:
+
+    func greet() {
+        print(\"Hello\")
+    }
+
+    greet()
+",
+    },
+    CodeInfo {
+        code: Code::W008,
+        name: "empty-block",
+        title: "A function body is empty",
+        severity: Severity::Warning,
+        explanation: "\
+A function body has no statements and no comments, so calling the function does nothing.
+This is often a function that was never finished. This lint is run by `zxx lint`.
+
+Erroneous code example:
+
+    func setup() {}
+
+    setup()
+
+Write the body, or explain in a comment why it is empty:
+
+    func setup() {
+        // nothing needs to be set up yet
+    }
+
+    setup()
+",
+    },
+];
+
+impl Code {
+    /// Returns the description of the code in the registry.
+    pub fn info(self) -> &'static CodeInfo {
+        REGISTRY.iter()
+            .find(|info| info.code == self)
+            .expect("every code is registered")
+    }
+
+    /// Returns the code as it is shown in diagnostics, such as `E001`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Code::E001 => "E001",
+            Code::E002 => "E002",
+            Code::E003 => "E003",
+            Code::E004 => "E004",
+            Code::E005 => "E005",
+            Code::E006 => "E006",
+            Code::E007 => "E007",
+            Code::E008 => "E008",
+            Code::E009 => "E009",
+            Code::E010 => "E010",
+            Code::E011 => "E011",
+            Code::E012 => "E012",
+            Code::E013 => "E013",
+            Code::E014 => "E014",
+            Code::E015 => "E015",
+            Code::E016 => "E016",
+            Code::E017 => "E017",
+            Code::E018 => "E018",
+            Code::W001 => "W001",
+            Code::W002 => "W002",
+            Code::W003 => "W003",
+            Code::W004 => "W004",
+            Code::W005 => "W005",
+            Code::W006 => "W006",
+            Code::W007 => "W007",
+            Code::W008 => "W008",
+        }
+    }
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Code {
+    type Err = ();
+
+    /// Parses a code as it is shown in diagnostics, ignoring case, or the name of a code.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        REGISTRY.iter()
+            .find(|info| info.code.as_str().eq_ignore_ascii_case(s) || info.name == s)
+            .map(|info| info.code)
+            .ok_or(())
+    }
+}
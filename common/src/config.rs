@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+use crate::codes::Code;
+use crate::features::{Feature, Features};
+use crate::levels::{Level, LintLevels};
+
+/// The name of the project config file, which is looked up in the directory of a project and
+/// the directories above it.
+pub const CONFIG_NAME: &str = "zxx.toml";
+
+/// Struct holding the settings of a project config file, which `zxx` and the language server
+/// both report diagnostics with.
+///
+/// The `[lints]` table maps codes or their names to levels, and may deny warnings, and the
+/// `[features]` table enables incubating features:
+///
+/// ```toml
+/// [lints]
+/// deny-warnings = true
+/// float-comparison = "allow"
+///
+/// [features]
+/// static-declarations = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// The levels diagnostic codes are reported at
+    pub levels: LintLevels,
+    /// The incubating features that are enabled
+    pub features: Features,
+}
+
+impl Config {
+    /// Returns the path of the config file of the project a directory belongs to, if there
+    /// is one.
+    pub fn find(directory: &Path) -> Option<PathBuf> {
+        directory.ancestors()
+            .map(|directory| directory.join(CONFIG_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Reads a config file.
+    ///
+    /// # Returns
+    ///
+    /// Returns a message describing the problem if the file cannot be read or is invalid.
+    pub fn read(path: &Path) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        Config::parse(&text)
+    }
+
+    /// Parses the text of a config file.
+    ///
+    /// # Returns
+    ///
+    /// Returns a message describing the problem if a level in it cannot be set, or it names a
+    /// code or feature that does not exist.
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let table: toml::Table = text.parse().map_err(|error: toml::de::Error| error.message().to_string())?;
+        let mut config = Config::default();
+
+        if let Some(lints) = table.get("lints") {
+            read_lints(lints.as_table().ok_or("`lints` must be a table")?, &mut config.levels)?;
+        }
+        if let Some(features) = table.get("features") {
+            read_features(features.as_table().ok_or("`features` must be a table")?, &mut config.features)?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Sets the levels of the `[lints]` table of a config file.
+fn read_lints(lints: &toml::Table, levels: &mut LintLevels) -> Result<(), String> {
+    for (key, value) in lints {
+        if key == "deny-warnings" {
+            levels.set_deny_warnings(value.as_bool().ok_or("`deny-warnings` must be a boolean")?);
+            continue
+        }
+
+        let code = key.parse::<Code>()
+            .map_err(|()| format!("`{}` is not a diagnostic code of zxx", key))?;
+        let level = value.as_str()
+            .and_then(|level| level.parse::<Level>().ok())
+            .ok_or_else(|| format!("the level of `{}` must be one of \"allow\", \"warn\", \"deny\" and \"forbid\"", key))?;
+
+        levels.set(code, level).map_err(|error| error.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Enables the features of the `[features]` table of a config file that are set to `true`.
+fn read_features(table: &toml::Table, features: &mut Features) -> Result<(), String> {
+    for (key, value) in table {
+        let feature = key.parse::<Feature>()
+            .map_err(|()| format!("`{}` is not a feature of zxx", key))?;
+
+        if value.as_bool().ok_or_else(|| format!("`{}` must be a boolean", key))? {
+            features.enable(feature);
+        }
+    }
+
+    Ok(())
+}
@@ -1,13 +1,42 @@
+use std::borrow::Cow;
 use codespan::{ByteIndex, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle, Severity};
-use codespan_reporting::files::SimpleFile;
-use codespan_reporting::term::{Config, emit};
-use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use serde_json::{json, Value};
+use crate::codes::Code;
+use crate::files::{FileId, FileSpan, Files};
+use crate::sink::{DiagnosticSink, Target};
 
 /// A helper for generating and emitting diagnostics for error reporting.
 #[derive(Clone)]
 pub struct Reporting<'a> {
-    file: SimpleFile<&'a str, &'a str>
+    /// The files diagnostics may point at
+    files: Cow<'a, Files>,
+    /// The file that is being reported, which spans refer to
+    file: FileId,
+    /// Whether diagnostics are only built and returned, without emitting them
+    quiet: bool,
+    /// Where diagnostics are emitted to, or `None` to write them to stderr right away
+    sink: Option<&'a DiagnosticSink>,
+}
+
+/// How a sink renders diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    /// Colored text that shows the source code the diagnostic points at
+    #[default]
+    Human,
+    /// One JSON object per line, for tools that consume diagnostics
+    Json,
+}
+
+/// A change to the source that fixes the problem a diagnostic reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// Describes the change to the user
+    pub message: String,
+    /// The part of the source that is replaced, in the file that is changed
+    pub span: FileSpan,
+    pub replacement: String,
 }
 
 pub fn merge_span(span1: &Span, span2: &Span) -> Span {
@@ -25,7 +54,8 @@ pub fn create_span(log: logos::Span) -> Span {
 }
 
 impl<'a> Reporting<'a> {
-    /// Constructs a new Reporting instance with the given file.
+    /// Constructs a new Reporting instance with the given file, which is the only file its
+    /// diagnostics can point at.
     ///
     /// # Arguments
     ///
@@ -35,8 +65,50 @@ impl<'a> Reporting<'a> {
     /// # Returns
     ///
     /// Returns a `Reporting` instance.
-    pub fn new(file: &'a str, content: &'a str) -> Self {
-        Reporting { file: SimpleFile::new(file, content) }
+    pub fn new(file: &str, content: &str) -> Self {
+        let mut files = Files::new();
+        let file = files.add(file, content);
+        Reporting { files: Cow::Owned(files), file, quiet: false, sink: None }
+    }
+
+    /// Constructs a new Reporting instance for a file of a database, whose diagnostics can
+    /// point at any file of the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The database of files.
+    /// * `file` - The ID of the file that is being reported.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Reporting` instance.
+    pub fn for_file(files: &'a Files, file: FileId) -> Self {
+        Reporting { files: Cow::Borrowed(files), file, quiet: false, sink: None }
+    }
+
+    /// Emits diagnostics to a sink, which may collect, sort or render them differently.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The sink the diagnostics are emitted to.
+    ///
+    /// # Returns
+    ///
+    /// Returns the `Reporting` instance with the sink set.
+    pub fn with_sink(mut self, sink: &'a DiagnosticSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Turns off emitting diagnostics, for callers such as the language server that
+    /// present the returned diagnostics themselves.
+    ///
+    /// # Returns
+    ///
+    /// Returns the quiet `Reporting` instance.
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
     }
 
     /// Constructs a diagnostic with the specified severity, message, span, notes, and code.
@@ -47,25 +119,25 @@ impl<'a> Reporting<'a> {
     /// * `message` - The message describing the diagnostic.
     /// * `span` - The span of the source code associated with the diagnostic.
     /// * `notes` - Additional notes to include with the diagnostic.
-    /// * `code` - A code associated with the diagnostic.
+    /// * `code` - The registered code of the diagnostic.
     ///
     /// # Returns
     ///
-    /// Returns a `Diagnostic<()>` instance.
+    /// Returns a `Diagnostic<FileId>` instance.
     fn make_diagnostic(
         &self,
         severity: Severity,
         message: String,
         span: Span,
         notes: Vec<String>,
-        code: String
-    ) -> Diagnostic<()> {
+        code: Code
+    ) -> Diagnostic<FileId> {
         Diagnostic::new(severity)
-            .with_code(code)
+            .with_code(code.as_str())
             .with_labels(vec![
                 Label::new(
                     LabelStyle::Primary,
-                    (),
+                    self.file,
                     span.start().0 as usize .. span.end().0 as usize
                 )
             ])
@@ -73,7 +145,7 @@ impl<'a> Reporting<'a> {
             .with_message(message)
     }
 
-    /// Emits the given diagnostic.
+    /// Emits the given diagnostic, such as one that was built by a quiet `Reporting` instance.
     ///
     /// # Arguments
     ///
@@ -82,14 +154,124 @@ impl<'a> Reporting<'a> {
     /// # Returns
     ///
     /// Returns None.
-    fn emit_diagnostic(
+    pub fn emit_diagnostic(
         &self,
-        diagnostic: &Diagnostic<()>
+        diagnostic: &Diagnostic<FileId>
     ) {
-        let writer = StandardStream::stdout(ColorChoice::Auto);
-        let config = Config::default();
-        emit(&mut writer.lock(), &config, &self.file, diagnostic)
-            .expect("Failed to emit diagnostic");
+        if self.quiet {
+            return
+        }
+
+        match self.sink {
+            Some(sink) => sink.emit(&self.files, self.file, diagnostic),
+            None => DiagnosticSink::new(Target::Stderr).emit(&self.files, self.file, diagnostic),
+        }
+    }
+
+    /// Converts a diagnostic into the JSON object `ErrorFormat::Json` writes.
+    ///
+    /// Every label of the diagnostic becomes a span with its file, its byte offsets, and the
+    /// 1-based line and column of its start and end, the way the human format counts them.
+    ///
+    /// # Arguments
+    ///
+    /// * `diagnostic` - The diagnostic to be converted.
+    ///
+    /// # Returns
+    ///
+    /// Returns the JSON object.
+    pub fn to_json(&self, diagnostic: &Diagnostic<FileId>) -> Value {
+        let severity = match diagnostic.severity {
+            Severity::Bug => "bug",
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        };
+
+        let location = |file: FileId, offset: usize| {
+            let (line, column) = self.files.location(file, offset);
+            json!({ "line": line, "column": column })
+        };
+
+        let spans: Vec<Value> = diagnostic.labels.iter()
+            .map(|label| json!({
+                "file": self.files.name(label.file_id),
+                "primary": label.style == LabelStyle::Primary,
+                "message": label.message,
+                "start": label.range.start,
+                "end": label.range.end,
+                "range": {
+                    "start": location(label.file_id, label.range.start),
+                    "end": location(label.file_id, label.range.end),
+                },
+            }))
+            .collect();
+
+        json!({
+            "severity": severity,
+            "code": diagnostic.code,
+            "message": diagnostic.message,
+            "notes": diagnostic.notes,
+            "file": self.name(),
+            "spans": spans,
+        })
+    }
+
+    /// Returns the files diagnostics may point at.
+    pub fn files(&self) -> &Files {
+        &self.files
+    }
+
+    /// Returns the ID of the file that is being reported.
+    pub fn file_id(&self) -> FileId {
+        self.file
+    }
+
+    /// Returns a span of the file that is being reported, such as the span of a node of its
+    /// syntax tree, together with the ID of the file.
+    pub fn file_span(&self, span: Span) -> FileSpan {
+        FileSpan::new(self.file, span)
+    }
+
+    /// Returns the name of the file that is being reported.
+    pub fn name(&self) -> &str {
+        self.files.name(self.file)
+    }
+
+    /// Returns the content of the file that is being reported.
+    pub fn source(&self) -> &str {
+        self.files.source(self.file)
+    }
+
+    /// Starts building an error diagnostic, which is emitted by `DiagnosticBuilder::emit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The error message.
+    /// * `span` - The span of the primary label, in the file that is being reported.
+    /// * `code` - The registered code of the error.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `DiagnosticBuilder` for the error.
+    pub fn error(&self, message: String, span: Span, code: Code) -> DiagnosticBuilder<'_, 'a> {
+        DiagnosticBuilder::new(self, self.make_diagnostic(Severity::Error, message, span, Vec::new(), code))
+    }
+
+    /// Starts building a warning diagnostic, which is emitted by `DiagnosticBuilder::emit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The warning message.
+    /// * `span` - The span of the primary label, in the file that is being reported.
+    /// * `code` - The registered code of the warning.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `DiagnosticBuilder` for the warning.
+    pub fn warning(&self, message: String, span: Span, code: Code) -> DiagnosticBuilder<'_, 'a> {
+        DiagnosticBuilder::new(self, self.make_diagnostic(Severity::Warning, message, span, Vec::new(), code))
     }
 
     /// Emits an error diagnostic with the specified message, span, notes, and code.
@@ -99,18 +281,18 @@ impl<'a> Reporting<'a> {
     /// * `message` - The error message.
     /// * `span` - The span of the source code associated with the error.
     /// * `notes` - Additional notes to include with the error.
-    /// * `code` - A code associated with the error.
+    /// * `code` - The registered code of the error.
     ///
     /// # Returns
     ///
-    /// Returns a `Diagnostic<()>` instance representing the emitted error.
+    /// Returns a `Diagnostic<FileId>` instance representing the emitted error.
     pub fn emit_error(
         &self,
         message: String,
         span: Span,
         notes: Vec<String>,
-        code: String,
-    ) -> Diagnostic<()> {
+        code: Code,
+    ) -> Diagnostic<FileId> {
         let diagnostic = self.make_diagnostic(
             Severity::Error,
             message,
@@ -130,18 +312,18 @@ impl<'a> Reporting<'a> {
     /// * `message` - The warning message.
     /// * `span` - The span of the source code associated with the warning.
     /// * `notes` - Additional notes to include with the warning.
-    /// * `code` - A code associated with the warning.
+    /// * `code` - The registered code of the warning.
     ///
     /// # Returns
     ///
-    /// Returns a `Diagnostic<()>` instance representing the emitted warning.
+    /// Returns a `Diagnostic<FileId>` instance representing the emitted warning.
     pub fn emit_warning(
         &self,
         message: String,
         span: Span,
         notes: Vec<String>,
-        code: String,
-    ) -> Diagnostic<()> {
+        code: Code,
+    ) -> Diagnostic<FileId> {
         let diagnostic = self.make_diagnostic(
             Severity::Warning,
             message,
@@ -161,18 +343,18 @@ impl<'a> Reporting<'a> {
     /// * `message` - The info message.
     /// * `span` - The span of the source code associated with the info message.
     /// * `notes` - Additional notes to include with the info message.
-    /// * `code` - A code associated with the info message.
+    /// * `code` - The registered code of the info message.
     ///
     /// # Returns
     ///
-    /// Returns a `Diagnostic<()>` instance representing the emitted info message.
+    /// Returns a `Diagnostic<FileId>` instance representing the emitted info message.
     pub fn emit_info(
         &self,
         message: String,
         span: Span,
         notes: Vec<String>,
-        code: String,
-    ) -> Diagnostic<()> {
+        code: Code,
+    ) -> Diagnostic<FileId> {
         let diagnostic = self.make_diagnostic(
             Severity::Help,
             message,
@@ -192,18 +374,18 @@ impl<'a> Reporting<'a> {
     /// * `message` - The note message.
     /// * `span` - The span of the source code associated with the note.
     /// * `notes` - Additional notes to include with the note.
-    /// * `code` - A code associated with the note.
+    /// * `code` - The registered code of the note.
     ///
     /// # Returns
     ///
-    /// Returns a `Diagnostic<()>` instance representing the emitted note.
+    /// Returns a `Diagnostic<FileId>` instance representing the emitted note.
     pub fn emit_note(
         &self,
         message: String,
         span: Span,
         notes: Vec<String>,
-        code: String,
-    ) -> Diagnostic<()> {
+        code: Code,
+    ) -> Diagnostic<FileId> {
         let diagnostic = self.make_diagnostic(
             Severity::Note,
             message,
@@ -215,4 +397,103 @@ impl<'a> Reporting<'a> {
         self.emit_diagnostic(&diagnostic);
         diagnostic
     }
-}
\ No newline at end of file
+}
+
+/// Struct building a diagnostic that has more to say than a message and a span: labels with
+/// messages of their own, which may be in other files, help and note sub-diagnostics, and
+/// suggestions that fix the problem.
+///
+/// Suggestions are machine-applicable, so they are returned along with the diagnostic, and
+/// they are shown inline as a label on the code they replace, such as "help: add `mut` here".
+#[must_use = "the diagnostic is only emitted by `emit`"]
+pub struct DiagnosticBuilder<'r, 'a> {
+    reporter: &'r Reporting<'a>,
+    diagnostic: Diagnostic<FileId>,
+    suggestions: Vec<Suggestion>,
+}
+
+impl<'r, 'a> DiagnosticBuilder<'r, 'a> {
+    fn new(reporter: &'r Reporting<'a>, diagnostic: Diagnostic<FileId>) -> Self {
+        DiagnosticBuilder { reporter, diagnostic, suggestions: Vec::new() }
+    }
+
+    /// Sets the message of the primary label, which is shown under the code it points at.
+    pub fn label(mut self, message: &str) -> Self {
+        if let Some(label) = self.diagnostic.labels.first_mut() {
+            label.message = message.to_string();
+        }
+        self
+    }
+
+    /// Adds a secondary label in the file that is being reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The span the label points at.
+    /// * `message` - The message shown under the span.
+    pub fn secondary(self, span: Span, message: &str) -> Self {
+        let span = self.reporter.file_span(span);
+        self.secondary_in(span, message)
+    }
+
+    /// Adds a secondary label in any file of the reporter's files.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The span the label points at, in the file it points into.
+    /// * `message` - The message shown under the span.
+    pub fn secondary_in(mut self, span: FileSpan, message: &str) -> Self {
+        let label = Label::secondary(span.file, span.range()).with_message(message);
+        self.diagnostic.labels.push(label);
+        self
+    }
+
+    /// Adds a note sub-diagnostic, which explains the circumstances of the problem.
+    pub fn note(mut self, message: &str) -> Self {
+        self.diagnostic.notes.push(format!("note: {}", message));
+        self
+    }
+
+    /// Adds a help sub-diagnostic, which explains how the problem can be fixed.
+    pub fn help(mut self, message: &str) -> Self {
+        self.diagnostic.notes.push(format!("help: {}", message));
+        self
+    }
+
+    /// Adds a suggestion, which is shown as a help label on the code it replaces.
+    pub fn suggestion(mut self, suggestion: Suggestion) -> Self {
+        let mut range = suggestion.span.range();
+
+        // a suggestion that removes whole lines is labelled without the last line break, so
+        // that the label does not reach onto the line after them
+        let source = self.reporter.files().source(suggestion.span.file);
+        if range.len() > 1 && source.get(range.clone()).is_some_and(|removed| removed.ends_with('\n')) {
+            range.end -= 1;
+        }
+
+        let label = Label::secondary(suggestion.span.file, range).with_message(format!("help: {}", suggestion.message));
+
+        self.diagnostic.labels.push(label);
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    /// Finishes the diagnostic without emitting it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the diagnostic together with its suggestions.
+    pub fn build(self) -> (Diagnostic<FileId>, Vec<Suggestion>) {
+        (self.diagnostic, self.suggestions)
+    }
+
+    /// Finishes the diagnostic and emits it through the reporter.
+    ///
+    /// # Returns
+    ///
+    /// Returns the diagnostic together with its suggestions.
+    pub fn emit(self) -> (Diagnostic<FileId>, Vec<Suggestion>) {
+        self.reporter.emit_diagnostic(&self.diagnostic);
+        (self.diagnostic, self.suggestions)
+    }
+}
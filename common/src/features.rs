@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A language feature whose RFC was accepted, but which is still incubating. Programs can only
+/// use it if it is enabled, since it is not compatible with ZSharp scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Feature {
+    /// Variables declared with `static`, which are evaluated at compile time
+    StaticDeclarations,
+}
+
+/// Every feature, in order.
+pub const FEATURES: [Feature; 1] = [Feature::StaticDeclarations];
+
+impl Feature {
+    /// Returns the name the feature is enabled by, which is the name of its RFC.
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::StaticDeclarations => "static-declarations",
+        }
+    }
+}
+
+impl Display for Feature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FEATURES.into_iter()
+            .find(|feature| feature.name() == s)
+            .ok_or(())
+    }
+}
+
+/// Struct holding the features that are enabled, which are set by the `[features]` table of
+/// the project config file and by the command line. No feature is enabled by default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Features {
+    enabled: BTreeSet<Feature>,
+}
+
+impl Features {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a set with every feature enabled.
+    pub fn all() -> Self {
+        Features { enabled: FEATURES.into_iter().collect() }
+    }
+
+    pub fn enable(&mut self, feature: Feature) {
+        self.enabled.insert(feature);
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.enabled.contains(&feature)
+    }
+}
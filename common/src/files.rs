@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use codespan::{ByteIndex, Span};
+use codespan_reporting::files::{Error, Files as _, SimpleFile};
+
+/// Identifies a file in a `Files` database, which every diagnostic label refers to its file by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(u32);
+
+/// A span in a file of a `Files` database.
+///
+/// The spans of tokens and syntax trees are relative to the document they were parsed from,
+/// which is the only file they can refer to. Spans that may point into any file, such as those of
+/// the labels and suggestions of a diagnostic, carry the ID of their file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileSpan {
+    pub file: FileId,
+    pub span: Span,
+}
+
+impl FileSpan {
+    pub fn new(file: FileId, span: Span) -> Self {
+        FileSpan { file, span }
+    }
+
+    pub fn start(&self) -> ByteIndex {
+        self.span.start()
+    }
+
+    pub fn end(&self) -> ByteIndex {
+        self.span.end()
+    }
+
+    /// Returns the byte range of the span, which labels of diagnostics point at.
+    pub fn range(&self) -> Range<usize> {
+        self.start().to_usize()..self.end().to_usize()
+    }
+}
+
+#[derive(Clone)]
+struct SourceFile {
+    file: SimpleFile<String, String>,
+    /// Whether the file only exists in memory, like the input of the REPL or stdin
+    is_virtual: bool,
+}
+
+/// Struct representing a database of source files, which diagnostics look up the names, text
+/// and lines of their files in.
+///
+/// A diagnostic may point at several files, for example at a definition and at the earlier
+/// definition it clashes with, so its labels are rendered with the file of each label.
+/// Virtual files are rendered like any other file, but have no path that could be written to.
+#[derive(Clone, Default)]
+pub struct Files {
+    files: BTreeMap<FileId, SourceFile>,
+    next: u32,
+}
+
+impl Files {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file that was read from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The path of the file, which diagnostics refer to it by.
+    /// * `source` - The content of the file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ID of the new file.
+    pub fn add(&mut self, name: &str, source: &str) -> FileId {
+        self.insert(name, source, false)
+    }
+
+    /// Adds a file that only exists in memory, such as the input of the REPL or stdin.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name diagnostics refer to the file by, such as `<stdin>`.
+    /// * `source` - The content of the file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ID of the new file.
+    pub fn add_virtual(&mut self, name: &str, source: &str) -> FileId {
+        self.insert(name, source, true)
+    }
+
+    fn insert(&mut self, name: &str, source: &str, is_virtual: bool) -> FileId {
+        let id = FileId(self.next);
+        self.next += 1;
+        self.files.insert(id, SourceFile { file: SimpleFile::new(name.to_string(), source.to_string()), is_virtual });
+        id
+    }
+
+    /// Replaces the content of a file, keeping its ID.
+    pub fn set_source(&mut self, id: FileId, source: &str) {
+        let file = self.file_mut(id);
+        file.file = SimpleFile::new(file.file.name().clone(), source.to_string());
+    }
+
+    /// Returns the ID of the first file with a name, if there is one.
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files.iter().find(|(_, file)| file.file.name() == name).map(|(id, _)| *id)
+    }
+
+    pub fn name(&self, id: FileId) -> &str {
+        self.file(id).file.name()
+    }
+
+    pub fn source(&self, id: FileId) -> &str {
+        self.file(id).file.source()
+    }
+
+    pub fn is_virtual(&self, id: FileId) -> bool {
+        self.file(id).is_virtual
+    }
+
+    /// Returns the 1-based line and column of a byte offset in a file, counting columns in
+    /// characters.
+    pub fn location(&self, id: FileId, offset: usize) -> (usize, usize) {
+        let location = self.file(id).file.location((), offset).expect("offsets are within the file");
+        (location.line_number, location.column_number)
+    }
+
+    /// Returns a database of only some of the files, which keep their IDs.
+    pub fn subset(&self, ids: impl IntoIterator<Item = FileId>) -> Files {
+        let files = ids.into_iter().map(|id| (id, self.file(id).clone())).collect();
+        Files { files, next: self.next }
+    }
+
+    fn file(&self, id: FileId) -> &SourceFile {
+        self.files.get(&id).expect("file IDs come from the same database")
+    }
+
+    fn file_mut(&mut self, id: FileId) -> &mut SourceFile {
+        self.files.get_mut(&id).expect("file IDs come from the same database")
+    }
+
+    fn get(&self, id: FileId) -> Result<&SimpleFile<String, String>, Error> {
+        self.files.get(&id).map(|file| &file.file).ok_or(Error::FileMissing)
+    }
+}
+
+impl<'a> codespan_reporting::files::Files<'a> for Files {
+    type FileId = FileId;
+    type Name = &'a str;
+    type Source = &'a str;
+
+    fn name(&'a self, id: FileId) -> Result<&'a str, Error> {
+        Ok(self.get(id)?.name().as_str())
+    }
+
+    fn source(&'a self, id: FileId) -> Result<&'a str, Error> {
+        Ok(self.get(id)?.source().as_str())
+    }
+
+    fn line_index(&'a self, id: FileId, byte_index: usize) -> Result<usize, Error> {
+        self.get(id)?.line_index((), byte_index)
+    }
+
+    fn line_range(&'a self, id: FileId, line_index: usize) -> Result<Range<usize>, Error> {
+        self.get(id)?.line_range((), line_index)
+    }
+}
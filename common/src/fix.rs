@@ -0,0 +1,168 @@
+use std::fmt::Write;
+use crate::errors::Suggestion;
+
+/// The number of unchanged lines shown around every change of a diff.
+const CONTEXT: usize = 3;
+
+/// Applies suggestions to a source, skipping every suggestion that conflicts with one that was
+/// applied before it.
+///
+/// Two suggestions conflict if the spans they replace overlap, or if both insert text at the
+/// same position, since either order could be meant. Earlier suggestions win.
+///
+/// # Arguments
+///
+/// * `source` - The text the spans of the suggestions refer to.
+/// * `suggestions` - The suggestions, which must all be for `source`.
+///
+/// # Returns
+///
+/// Returns the changed text, together with the number of suggestions that were applied.
+pub fn apply_suggestions(source: &str, suggestions: &[Suggestion]) -> (String, usize) {
+    let mut accepted: Vec<&Suggestion> = Vec::new();
+
+    for suggestion in suggestions {
+        let (start, end) = (suggestion.span.start(), suggestion.span.end());
+        let conflicts = accepted.iter().any(|other| {
+            let (other_start, other_end) = (other.span.start(), other.span.end());
+            (start < other_end && other_start < end) || start == other_start
+        });
+
+        if !conflicts {
+            accepted.push(suggestion);
+        }
+    }
+
+    // replacing from the end keeps the spans in front valid
+    accepted.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.span.start()));
+
+    let mut text = source.to_string();
+    for suggestion in &accepted {
+        text.replace_range(suggestion.span.range(), &suggestion.replacement);
+    }
+
+    (text, accepted.len())
+}
+
+/// A line of a diff.
+#[derive(Clone, Copy, PartialEq)]
+enum Line {
+    /// A line both texts have, at its index in the old and the new text
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// Compares two texts line by line, in the unified format `diff -u` and `patch` use.
+///
+/// # Arguments
+///
+/// * `name` - The name of the file, which the header refers to as `a/name` and `b/name`.
+/// * `old` - The text before the change.
+/// * `new` - The text after the change.
+///
+/// # Returns
+///
+/// Returns the diff, which is empty if the texts are the same.
+pub fn unified_diff(name: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let lines = diff_lines(&old, &new);
+
+    let mut out = String::new();
+    if lines.iter().all(|line| matches!(line, Line::Same(..))) {
+        return out
+    }
+    let _ = writeln!(out, "--- a/{}\n+++ b/{}", name, name);
+
+    let mut index = 0;
+    while let Some(first) = lines[index..].iter().position(|line| !matches!(line, Line::Same(..))) {
+        let start = (index + first).saturating_sub(CONTEXT);
+
+        // a hunk ends once more unchanged lines follow a change than two contexts could show
+        let mut end = index + first;
+        let mut unchanged = 0;
+        for (position, line) in lines.iter().enumerate().skip(end) {
+            if matches!(line, Line::Same(..)) {
+                unchanged += 1;
+                if unchanged > 2 * CONTEXT {
+                    break
+                }
+            } else {
+                unchanged = 0;
+                end = position;
+            }
+        }
+        let end = (end + 1 + CONTEXT).min(lines.len());
+        let hunk = &lines[start..end];
+
+        let old_count = hunk.iter().filter(|line| !matches!(line, Line::Added(_))).count();
+        let new_count = hunk.iter().filter(|line| !matches!(line, Line::Removed(_))).count();
+        let old_start = lines[..start].iter().filter(|line| !matches!(line, Line::Added(_))).count();
+        let new_start = lines[..start].iter().filter(|line| !matches!(line, Line::Removed(_))).count();
+        // empty ranges are given by the line in front of them
+        let from = |start: usize, count: usize| if count == 0 { start } else { start + 1 };
+        let _ = writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            from(old_start, old_count),
+            old_count,
+            from(new_start, new_count),
+            new_count,
+        );
+
+        for line in hunk {
+            let _ = match *line {
+                Line::Same(index, _) => writeln!(out, " {}", old[index]),
+                Line::Removed(index) => writeln!(out, "-{}", old[index]),
+                Line::Added(index) => writeln!(out, "+{}", new[index]),
+            };
+        }
+
+        index = end;
+    }
+
+    out
+}
+
+/// Returns the lines of a shortest edit from `old` to `new`, with removals in front of the
+/// additions that replace them.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Line> {
+    // fixes change few lines, so only the lines between the common prefix and suffix are
+    // compared with each other
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old_middle, new_middle) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    // the length of the longest common subsequence of the rest of both
+    let width = new_middle.len() + 1;
+    let mut common = vec![0; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            common[i * width + j] = if old_middle[i] == new_middle[j] {
+                common[(i + 1) * width + j + 1] + 1
+            } else {
+                common[(i + 1) * width + j].max(common[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<Line> = (0..prefix).map(|index| Line::Same(index, index)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            lines.push(Line::Same(prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if j == new_middle.len() || (i < old_middle.len() && common[(i + 1) * width + j] >= common[i * width + j + 1]) {
+            lines.push(Line::Removed(prefix + i));
+            i += 1;
+        } else {
+            lines.push(Line::Added(prefix + j));
+            j += 1;
+        }
+    }
+    lines.extend((0..suffix).map(|offset| Line::Same(old.len() - suffix + offset, new.len() - suffix + offset)));
+
+    lines
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use codespan_reporting::diagnostic::Severity;
+use crate::codes::Code;
+
+/// How diagnostics with a code are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Not reported at all
+    Allow,
+    /// Reported as warnings
+    Warn,
+    /// Reported as errors
+    Deny,
+    /// Reported as errors, and cannot be set to any other level afterwards
+    Forbid,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Level::Allow => "allow",
+            Level::Warn => "warn",
+            Level::Deny => "deny",
+            Level::Forbid => "forbid",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Level {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Level::Allow),
+            "warn" => Ok(Level::Warn),
+            "deny" => Ok(Level::Deny),
+            "forbid" => Ok(Level::Forbid),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The reasons a level cannot be set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelError {
+    /// Errors are always reported as errors
+    Error(Code),
+    /// The code was forbidden before
+    Forbidden(Code, Level),
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelError::Error(code) => write!(f, "`{}` is an error, which cannot be allowed or warned about", code),
+            LevelError::Forbidden(code, level) => write!(f, "`{}` is forbidden, so it cannot be set to `{}`", code, level),
+        }
+    }
+}
+
+/// Struct holding the level of every diagnostic code, which decides whether and how the
+/// diagnostics with the code are reported.
+///
+/// Codes are reported with the severity of their registry entry unless a level is set for
+/// them. Levels are set by the project config file, then by the command line, and then by the
+/// attributes of the function a diagnostic is in, where later levels override earlier ones
+/// unless a code was forbidden.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintLevels {
+    levels: BTreeMap<Code, Level>,
+    /// Whether warnings are reported as errors
+    deny_warnings: bool,
+}
+
+impl LintLevels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level of a code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code.
+    /// * `level` - The level diagnostics with the code are reported at from now on.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the code is an error and the level is lower than `deny`, or if the
+    /// code is forbidden and the level is not `forbid`.
+    pub fn set(&mut self, code: Code, level: Level) -> Result<(), LevelError> {
+        if code.info().severity != Severity::Warning && level < Level::Deny {
+            return Err(LevelError::Error(code))
+        }
+        if self.level(code) == Level::Forbid && level != Level::Forbid {
+            return Err(LevelError::Forbidden(code, level))
+        }
+
+        self.levels.insert(code, level);
+        Ok(())
+    }
+
+    /// Sets whether warnings are reported as errors, which makes any warning fail the build.
+    pub fn set_deny_warnings(&mut self, deny_warnings: bool) {
+        self.deny_warnings = deny_warnings;
+    }
+
+    /// Returns the level of a code, which is `warn` for warnings and `deny` for errors unless
+    /// it was set.
+    pub fn level(&self, code: Code) -> Level {
+        self.levels.get(&code).copied().unwrap_or(match code.info().severity {
+            Severity::Warning => Level::Warn,
+            _ => Level::Deny,
+        })
+    }
+
+    /// Returns the severity diagnostics with a code are reported with, or `None` if they are
+    /// not reported.
+    pub fn severity(&self, code: Code) -> Option<Severity> {
+        match self.level(code) {
+            Level::Allow => None,
+            Level::Warn if !self.deny_warnings => Some(Severity::Warning),
+            Level::Warn | Level::Deny | Level::Forbid => Some(Severity::Error),
+        }
+    }
+}
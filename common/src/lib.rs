@@ -1 +1,11 @@
+pub mod codes;
+pub mod config;
 pub mod errors;
+pub mod features;
+pub mod files;
+pub mod fix;
+pub mod levels;
+pub mod sarif;
+pub mod sink;
+pub mod spelling;
+pub mod temp;
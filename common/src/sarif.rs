@@ -0,0 +1,219 @@
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use std::path::Path;
+use serde_json::{json, Value};
+use crate::codes::Code;
+use crate::errors::{Reporting, Suggestion};
+use crate::files::FileId;
+
+/// The version of the SARIF format that is written.
+const VERSION: &str = "2.1.0";
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// The base the URIs of relative paths are resolved against, which is the working directory.
+const SRCROOT: &str = "SRCROOT";
+
+/// Struct collecting diagnostics of any number of files into a SARIF log, the format
+/// code scanning services read.
+///
+/// Every diagnostic becomes a result of the run of `zxx`, which points at the file and region
+/// of its primary label and at the regions of its other labels as related locations. The
+/// suggestions of a diagnostic become fixes, and every code that occurs becomes a rule, which is
+/// described by the registry of codes.
+#[derive(Default)]
+pub struct SarifLog {
+    /// The codes of the rules, in the order they were first seen
+    rules: Vec<String>,
+    results: Vec<Value>,
+}
+
+impl SarifLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a diagnostic to the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - The `Reporting` instance of the file the diagnostic refers to, whose
+    ///   files the labels and suggestions point at.
+    /// * `diagnostic` - The diagnostic to be added.
+    /// * `suggestions` - The changes that fix the problem the diagnostic reports.
+    pub fn add(&mut self, reporter: &Reporting, diagnostic: &Diagnostic<FileId>, suggestions: &[Suggestion]) {
+        let files = reporter.files();
+        // the run counts columns in code points, so the offsets are too rather than in bytes
+        let region = |file: FileId, start: usize, end: usize| {
+            let (start_line, start_column) = files.location(file, start);
+            let (end_line, end_column) = files.location(file, end);
+            let source = files.source(file);
+            json!({
+                "startLine": start_line,
+                "startColumn": start_column,
+                "endLine": end_line,
+                "endColumn": end_column,
+                "charOffset": source[..start].chars().count(),
+                "charLength": source[start..end].chars().count(),
+            })
+        };
+        let artifact = |file: FileId| artifact_location(files.name(file));
+
+        let mut locations = Vec::new();
+        let mut related = Vec::new();
+        for label in &diagnostic.labels {
+            let mut location = json!({
+                "physicalLocation": {
+                    "artifactLocation": artifact(label.file_id),
+                    "region": region(label.file_id, label.range.start, label.range.end),
+                },
+            });
+            if !label.message.is_empty() {
+                location["message"] = json!({ "text": label.message });
+            }
+
+            match label.style {
+                LabelStyle::Primary => locations.push(location),
+                LabelStyle::Secondary => related.push(location),
+            }
+        }
+
+        let fixes: Vec<Value> = suggestions.iter()
+            .map(|suggestion| {
+                let span = suggestion.span;
+                json!({
+                    "description": { "text": suggestion.message },
+                    "artifactChanges": [{
+                        "artifactLocation": artifact(span.file),
+                        "replacements": [{
+                            "deletedRegion": region(span.file, span.range().start, span.range().end),
+                            "insertedContent": { "text": suggestion.replacement },
+                        }],
+                    }],
+                })
+            })
+            .collect();
+
+        let text = std::iter::once(diagnostic.message.as_str())
+            .chain(diagnostic.notes.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut result = json!({
+            "level": level(diagnostic.severity),
+            "message": { "text": text },
+            "locations": locations,
+        });
+        if let Some(code) = &diagnostic.code {
+            let index = match self.rules.iter().position(|rule| rule == code) {
+                Some(index) => index,
+                None => {
+                    self.rules.push(code.clone());
+                    self.rules.len() - 1
+                }
+            };
+            result["ruleId"] = json!(code);
+            result["ruleIndex"] = json!(index);
+        }
+        if !related.is_empty() {
+            result["relatedLocations"] = json!(related);
+        }
+        if !fixes.is_empty() {
+            result["fixes"] = json!(fixes);
+        }
+
+        self.results.push(result);
+    }
+
+    /// Converts the log into the JSON document of a SARIF log with a single run.
+    pub fn to_json(&self) -> Value {
+        let rules: Vec<Value> = self.rules.iter()
+            .map(|code| match code.parse::<Code>() {
+                Ok(code) => {
+                    let info = code.info();
+                    json!({
+                        "id": code.as_str(),
+                        "name": info.name,
+                        "shortDescription": { "text": info.title },
+                        "fullDescription": { "text": info.explanation },
+                        "defaultConfiguration": { "level": level(info.severity) },
+                    })
+                }
+                Err(()) => json!({ "id": code }),
+            })
+            .collect();
+
+        let mut run = json!({
+            "tool": {
+                "driver": {
+                    "name": "zxx",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": self.results,
+        });
+        if let Some(root) = root_uri() {
+            run["originalUriBaseIds"] = json!({ SRCROOT: { "uri": root } });
+        }
+
+        json!({
+            "$schema": SCHEMA,
+            "version": VERSION,
+            "runs": [run],
+        })
+    }
+}
+
+/// Returns the SARIF level of a severity.
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Bug | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note | Severity::Help => "note",
+    }
+}
+
+/// Returns the location of the artifact at a path. Absolute paths become `file` URIs, and the
+/// URIs of relative paths are relative to the working directory, which the run declares as
+/// `SRCROOT`.
+fn artifact_location(path: &str) -> Value {
+    if Path::new(path).is_absolute() {
+        json!({ "uri": file_uri(path) })
+    } else {
+        json!({ "uri": encode_path(path), "uriBaseId": SRCROOT })
+    }
+}
+
+/// Returns the `file` URI of the working directory, which ends with a slash so that relative
+/// URIs are resolved within it.
+fn root_uri() -> Option<String> {
+    let root = std::env::current_dir().ok()?;
+    let uri = file_uri(root.to_str()?);
+    Some(if uri.ends_with('/') { uri } else { uri + "/" })
+}
+
+/// Returns the `file` URI of an absolute path.
+fn file_uri(path: &str) -> String {
+    let path = encode_path(path);
+    // the paths of Windows start with a drive rather than a slash
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+/// Percent-encodes the bytes of a path that may not occur in the path of a URI, and separates
+/// its components with slashes.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::new();
+    for byte in path.bytes() {
+        match byte {
+            b'\\' if std::path::MAIN_SEPARATOR == '\\' => encoded.push('/'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,229 @@
+use std::cell::RefCell;
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use codespan_reporting::term::{Config, emit};
+use codespan_reporting::term::termcolor::{Buffer, Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use crate::errors::{ErrorFormat, Reporting};
+use crate::files::{FileId, Files};
+
+/// Where a sink writes the diagnostics it renders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Stdout,
+    /// Where compiler errors belong, so they do not mix with the output of the program
+    Stderr,
+    /// A string that `DiagnosticSink::output` returns
+    String,
+}
+
+/// A diagnostic together with the files it points at.
+#[derive(Clone)]
+struct Entry {
+    /// The file that was reported
+    file: FileId,
+    /// The reported file and the files of the labels, as they were when the diagnostic was
+    /// emitted
+    files: Files,
+    diagnostic: Diagnostic<FileId>,
+}
+
+impl Entry {
+    fn name(&self) -> &str {
+        self.files.name(self.file)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Every diagnostic that was emitted, without duplicates
+    diagnostics: Vec<Entry>,
+    /// The diagnostics that have not been rendered yet, if the sink is buffered
+    pending: Vec<Entry>,
+    /// The rendered diagnostics of a sink with `Target::String`
+    output: Vec<u8>,
+}
+
+/// Struct representing a diagnostic sink, which receives the diagnostics a `Reporting`
+/// instance emits and decides what happens to them.
+///
+/// A sink collects every diagnostic it receives, so they can be counted and inspected as
+/// values afterwards, and ignores a diagnostic it already received for a file of the same
+/// name. It
+/// renders them as text or JSON to stdout, stderr or a string, either right away or, if it is
+/// buffered, sorted by file and position when it is flushed.
+pub struct DiagnosticSink {
+    target: Target,
+    format: ErrorFormat,
+    color: ColorChoice,
+    /// Whether diagnostics are only rendered when the sink is flushed
+    buffered: bool,
+    state: RefCell<State>,
+}
+
+impl DiagnosticSink {
+    /// Constructs a sink that renders diagnostics as colored text right away, if the target is
+    /// a terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Where the rendered diagnostics are written.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `DiagnosticSink` instance.
+    pub fn new(target: Target) -> Self {
+        DiagnosticSink {
+            target,
+            format: ErrorFormat::default(),
+            color: ColorChoice::Auto,
+            buffered: false,
+            state: RefCell::new(State::default()),
+        }
+    }
+
+    /// Sets the format diagnostics are rendered in.
+    pub fn with_format(mut self, format: ErrorFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets whether rendered text is colored. Strings are only colored with
+    /// `ColorChoice::Always` or `ColorChoice::AlwaysAnsi`.
+    pub fn with_color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Holds diagnostics back until the sink is flushed, which renders them sorted by file and
+    /// position.
+    pub fn buffered(mut self) -> Self {
+        self.buffered = true;
+        self
+    }
+
+    /// Receives a diagnostic about a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The files the diagnostic may point at.
+    /// * `file` - The file that is being reported.
+    /// * `diagnostic` - The diagnostic.
+    pub fn emit(&self, files: &Files, file: FileId, diagnostic: &Diagnostic<FileId>) {
+        let ids = std::iter::once(file).chain(diagnostic.labels.iter().map(|label| label.file_id));
+        let entry = Entry { file, files: files.subset(ids), diagnostic: diagnostic.clone() };
+
+        let mut state = self.state.borrow_mut();
+        if state.diagnostics.iter().any(|other| other.name() == entry.name() && other.diagnostic == entry.diagnostic) {
+            return
+        }
+        state.diagnostics.push(entry.clone());
+
+        if self.buffered {
+            state.pending.push(entry);
+        } else {
+            drop(state);
+            self.render(&[entry]);
+        }
+    }
+
+    /// Renders the diagnostics a buffered sink held back, sorted by file and by the position
+    /// of their primary label.
+    pub fn flush(&self) {
+        let mut pending = std::mem::take(&mut self.state.borrow_mut().pending);
+        pending.sort_by(|a, b| (a.name(), position(&a.diagnostic)).cmp(&(b.name(), position(&b.diagnostic))));
+
+        self.render(&pending);
+    }
+
+    /// Returns every diagnostic the sink received, in the order it received them.
+    pub fn diagnostics(&self) -> Vec<Diagnostic<FileId>> {
+        self.state.borrow().diagnostics.iter().map(|entry| entry.diagnostic.clone()).collect()
+    }
+
+    /// Returns how many of the diagnostics the sink received have a severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.state.borrow().diagnostics.iter().filter(|entry| entry.diagnostic.severity == severity).count()
+    }
+
+    /// Returns how many errors the sink received, which includes bugs.
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error) + self.count(Severity::Bug)
+    }
+
+    /// Returns what a sink with `Target::String` rendered so far.
+    pub fn output(&self) -> String {
+        String::from_utf8_lossy(&self.state.borrow().output).into_owned()
+    }
+
+    /// Renders how many errors and warnings the sink received, such as
+    /// `warning: 2 warnings emitted`, which ends the output of a run. Nothing is rendered if
+    /// the sink received neither, or renders JSON.
+    pub fn summarize(&self) {
+        let (errors, warnings) = (self.error_count(), self.count(Severity::Warning));
+        if (errors == 0 && warnings == 0) || self.format == ErrorFormat::Json {
+            return
+        }
+
+        let counts: Vec<String> = [(errors, "error"), (warnings, "warning")].into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, noun)| format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" }))
+            .collect();
+        let (severity, color) = if errors > 0 { ("error", Color::Red) } else { ("warning", Color::Yellow) };
+
+        self.output_with(|writer| {
+            writer.set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true).set_intense(true))?;
+            write!(writer, "{}", severity)?;
+            writer.set_color(ColorSpec::new().set_bold(true))?;
+            write!(writer, ": {} emitted", counts.join(" and "))?;
+            writer.reset()?;
+            writeln!(writer)
+        });
+    }
+
+    fn render(&self, entries: &[Entry]) {
+        self.output_with(|writer| {
+            self.write(writer, entries);
+            Ok(())
+        });
+    }
+
+    /// Writes to the target of the sink.
+    fn output_with(&self, write: impl FnOnce(&mut dyn WriteColor) -> std::io::Result<()>) {
+        let result = match self.target {
+            Target::Stdout => write(&mut StandardStream::stdout(self.color).lock()),
+            Target::Stderr => write(&mut StandardStream::stderr(self.color).lock()),
+            Target::String => {
+                let mut buffer = match self.color {
+                    ColorChoice::Always | ColorChoice::AlwaysAnsi => Buffer::ansi(),
+                    ColorChoice::Auto | ColorChoice::Never => Buffer::no_color(),
+                };
+                let result = write(&mut buffer);
+                self.state.borrow_mut().output.extend_from_slice(buffer.as_slice());
+                result
+            }
+        };
+
+        result.expect("Failed to emit diagnostic");
+    }
+
+    fn write(&self, writer: &mut dyn WriteColor, entries: &[Entry]) {
+        for entry in entries {
+            match self.format {
+                ErrorFormat::Human => {
+                    emit(writer, &Config::default(), &entry.files, &entry.diagnostic)
+                        .expect("Failed to emit diagnostic");
+                }
+                ErrorFormat::Json => {
+                    let json = Reporting::for_file(&entry.files, entry.file).to_json(&entry.diagnostic);
+                    writeln!(writer, "{}", json).expect("Failed to emit diagnostic");
+                }
+            }
+        }
+    }
+}
+
+/// Returns the span of the primary label of a diagnostic, which diagnostics are sorted by.
+fn position(diagnostic: &Diagnostic<FileId>) -> (usize, usize) {
+    diagnostic.labels.iter()
+        .find(|label| label.style == LabelStyle::Primary)
+        .map_or((0, 0), |label| (label.range.start, label.range.end))
+}
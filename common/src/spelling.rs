@@ -0,0 +1,50 @@
+/// Returns the number of edits that turn one word into another, where an edit inserts,
+/// removes or replaces a character, or swaps two adjacent characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // the distances of the prefixes of `a` to the prefixes of `b`, by rows of `a`
+    let mut before_last: Vec<usize> = vec![0; b.len() + 1];
+    let mut last: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let replace = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (last[j] + 1).min(row[j - 1] + 1).min(last[j - 1] + replace);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before_last[j - 2] + 1);
+            }
+        }
+
+        before_last = std::mem::replace(&mut last, row);
+    }
+
+    last[b.len()]
+}
+
+/// Finds the candidate a misspelt name most likely was meant to be.
+///
+/// A candidate is only considered if it is at most a third of the length of the name away,
+/// and at least one edit. Of the closest candidates, the first in alphabetical order wins, so
+/// that the result does not depend on the order of the candidates.
+///
+/// # Arguments
+///
+/// * `name` - The name that could not be found.
+/// * `candidates` - The names that exist where the name was used.
+///
+/// # Returns
+///
+/// Returns the closest candidate, or `None` if no candidate is close enough.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+
+    candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
@@ -0,0 +1,73 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of temporary files and directories this process created, which makes their
+/// names unique.
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Struct representing a file in the temporary directory of the system, which is removed when
+/// it is dropped.
+///
+/// The tests that run `zxx` or a C compiler on programs write them to temporary files, and
+/// since tests run in parallel, every file gets a name no other file of any process has.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Creates a temporary file.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - The extension of the file name, without a dot.
+    /// * `contents` - What the file contains.
+    pub fn new(extension: &str, contents: impl AsRef<[u8]>) -> io::Result<Self> {
+        let path = unique_path().with_extension(extension);
+
+        std::fs::write(&path, contents)?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Struct representing a directory in the temporary directory of the system, which is removed
+/// together with its contents when it is dropped, such as a project with a config file.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new() -> io::Result<Self> {
+        let path = unique_path();
+
+        std::fs::create_dir(&path)?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Returns a path in the temporary directory that no other temporary file or directory of any
+/// process has.
+fn unique_path() -> PathBuf {
+    let count = CREATED.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("zpp-{}-{}", std::process::id(), count))
+}
@@ -0,0 +1,55 @@
+use std::fs;
+use std::path::Path;
+use common::codes::{Code, REGISTRY};
+
+#[test]
+fn codes_are_registered_once() {
+    for (index, info) in REGISTRY.iter().enumerate() {
+        assert_eq!(info.code.info().code, info.code);
+        assert_eq!(info.code.as_str().parse::<Code>(), Ok(info.code));
+        assert!(REGISTRY[..index].iter().all(|other| other.code != info.code && other.name != info.name));
+        assert!(info.explanation.contains("Erroneous code example:"), "{} has no example", info.code);
+    }
+
+    assert_eq!("e003".parse::<Code>(), Ok(Code::E003));
+    assert_eq!("E999".parse::<Code>(), Err(()));
+    assert_eq!("float-comparison".parse::<Code>(), Ok(Code::W001));
+}
+
+/// Collects the codes that are written as string literals, like `"E001"` or `"W001"`, in the
+/// Rust files of a directory.
+fn literal_codes(dir: &Path, codes: &mut Vec<(String, String)>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            literal_codes(&path, codes);
+            continue
+        }
+        if path.extension().is_none_or(|extension| extension != "rs") {
+            continue
+        }
+
+        let source = fs::read_to_string(&path).unwrap();
+        let starts = source.match_indices("\"E").chain(source.match_indices("\"W"));
+        for (start, _) in starts {
+            let literal = &source[start + 1..];
+            let digits = literal[1..].chars().take_while(char::is_ascii_digit).count();
+            if digits > 0 && literal[1 + digits..].starts_with('"') {
+                codes.push((path.display().to_string(), literal[..1 + digits].to_string()));
+            }
+        }
+    }
+}
+
+#[test]
+fn every_code_the_compiler_uses_is_registered() {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let mut codes = Vec::new();
+    for member in ["ast", "cli", "codegen", "common", "database", "ir", "lsp"] {
+        literal_codes(&workspace.join(member).join("src"), &mut codes);
+    }
+
+    for (path, code) in codes {
+        assert!(code.parse::<Code>().is_ok(), "{} uses the unregistered code {}", path, code);
+    }
+}
@@ -0,0 +1,50 @@
+use codespan_reporting::diagnostic::Severity;
+use common::codes::Code;
+use common::config::{Config, CONFIG_NAME};
+use common::features::{Feature, Features};
+use common::levels::Level;
+use common::temp::TempDir;
+
+#[test]
+fn config_files_set_levels_and_features() {
+    let config = Config::parse(r#"
+        [lints]
+        deny-warnings = true
+        float-comparison = "allow"
+        W002 = "forbid"
+
+        [features]
+        static-declarations = true
+    "#).unwrap();
+
+    assert_eq!(config.levels.level(Code::W001), Level::Allow);
+    assert_eq!(config.levels.level(Code::W002), Level::Forbid);
+    assert_eq!(config.levels.severity(Code::W003), Some(Severity::Error));
+    assert!(config.features.is_enabled(Feature::StaticDeclarations));
+
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+    assert_eq!(Config::parse("[features]\nstatic-declarations = false").unwrap().features, Features::new());
+}
+
+#[test]
+fn invalid_config_files_are_described() {
+    assert_eq!(Config::parse("lints = 1").unwrap_err(), "`lints` must be a table");
+    assert_eq!(Config::parse("[lints]\nW999 = \"deny\"").unwrap_err(), "`W999` is not a diagnostic code of zxx");
+    assert_eq!(
+        Config::parse("[lints]\nW001 = \"loud\"").unwrap_err(),
+        "the level of `W001` must be one of \"allow\", \"warn\", \"deny\" and \"forbid\"",
+    );
+    assert_eq!(Config::parse("[features]\ngenerics = true").unwrap_err(), "`generics` is not a feature of zxx");
+}
+
+#[test]
+fn config_files_are_found_in_the_directories_above() {
+    let project = TempDir::new().unwrap();
+    let nested = project.path().join("src").join("lib");
+    std::fs::create_dir_all(&nested).unwrap();
+
+    assert_eq!(Config::find(&nested), None);
+
+    std::fs::write(project.path().join(CONFIG_NAME), "").unwrap();
+    assert_eq!(Config::find(&nested), Some(project.path().join(CONFIG_NAME)));
+}
@@ -0,0 +1,69 @@
+use codespan::Span;
+use common::codes::Code;
+use common::errors::{Reporting, Suggestion};
+use common::sink::{DiagnosticSink, Target};
+use serde_json::json;
+
+#[test]
+fn diagnostics_convert_to_json() {
+    let input = "int x = 1\nint y = z\n";
+    let reporter = Reporting::new("test.zpp", input).quiet();
+    let diagnostic = reporter.emit_error(
+        "Cannot find variable `z` in this scope".to_string(),
+        Span::new(18, 19),
+        vec!["Declare `z` first".to_string()],
+        Code::E002,
+    );
+
+    assert_eq!(reporter.to_json(&diagnostic), json!({
+        "severity": "error",
+        "code": "E002",
+        "message": "Cannot find variable `z` in this scope",
+        "notes": ["Declare `z` first"],
+        "file": "test.zpp",
+        "spans": [{
+            "file": "test.zpp",
+            "primary": true,
+            "message": "",
+            "start": 18,
+            "end": 19,
+            "range": {
+                "start": { "line": 2, "column": 9 },
+                "end": { "line": 2, "column": 10 },
+            },
+        }],
+    }));
+}
+
+#[test]
+fn builder_adds_labels_sub_diagnostics_and_suggestions() {
+    let input = "int x = 1\nx = 2\n";
+    let sink = DiagnosticSink::new(Target::String);
+    let reporter = Reporting::new("test.zpp", input).with_sink(&sink);
+    let suggestion = Suggestion {
+        message: "add `mut` here".to_string(),
+        span: reporter.file_span(Span::new(0, 0)),
+        replacement: "mut ".to_string(),
+    };
+
+    let (diagnostic, suggestions) = reporter.error("Cannot assign twice to `x`".to_string(), Span::new(10, 15), Code::E010)
+        .label("cannot assign twice")
+        .secondary(Span::new(0, 9), "first assigned here")
+        .note("variables cannot be reassigned unless they are mutable")
+        .help("declare `x` as mutable")
+        .suggestion(suggestion.clone())
+        .emit();
+
+    assert_eq!(suggestions, [suggestion]);
+    assert_eq!(diagnostic.labels.len(), 3);
+    assert_eq!(diagnostic.labels[0].message, "cannot assign twice");
+    assert_eq!(diagnostic.notes, [
+        "note: variables cannot be reassigned unless they are mutable",
+        "help: declare `x` as mutable",
+    ]);
+
+    let output = sink.output();
+    assert!(output.contains("first assigned here"));
+    assert!(output.contains("help: add `mut` here"));
+    assert!(output.contains("= help: declare `x` as mutable"));
+}
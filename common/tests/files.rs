@@ -0,0 +1,43 @@
+use codespan::Span;
+use common::codes::Code;
+use common::errors::Reporting;
+use common::files::{FileSpan, Files};
+use common::sink::{DiagnosticSink, Target};
+
+#[test]
+fn diagnostics_point_at_several_files() {
+    let mut files = Files::new();
+    let lib = files.add("lib.zpp", "func greet() {\n}\n");
+    let main = files.add("main.zpp", "int x = 1\nfunc greet() {\n}\n");
+
+    let sink = DiagnosticSink::new(Target::String);
+    let reporter = Reporting::for_file(&files, main).quiet();
+    let (diagnostic, _) = reporter
+        .error("The function `greet` is defined multiple times".to_string(), Span::new(15, 20), Code::E004)
+        .secondary_in(FileSpan::new(lib, Span::new(5, 10)), "first defined here")
+        .build();
+    Reporting::for_file(&files, main).with_sink(&sink).emit_diagnostic(&diagnostic);
+
+    let output = sink.output();
+    assert!(output.contains("┌─ main.zpp:2:6"));
+    assert!(output.contains("┌─ lib.zpp:1:6"));
+    assert!(output.contains("first defined here"));
+
+    let json = reporter.to_json(&diagnostic);
+    assert_eq!(json["file"], "main.zpp");
+    assert_eq!(json["spans"][1]["file"], "lib.zpp");
+    assert_eq!(json["spans"][1]["range"]["start"]["line"], 1);
+}
+
+#[test]
+fn virtual_files_keep_their_id_as_they_grow() {
+    let mut files = Files::new();
+    let disk = files.add("main.zpp", "int x = 1\n");
+    let repl = files.add_virtual("<repl>", "int y = 2\n");
+    files.set_source(repl, "int y = 2\nint z = y\n");
+
+    assert!(!files.is_virtual(disk));
+    assert!(files.is_virtual(repl));
+    assert_eq!(files.find("<repl>"), Some(repl));
+    assert_eq!(files.location(repl, 18), (2, 9));
+}
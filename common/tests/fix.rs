@@ -0,0 +1,62 @@
+use codespan::Span;
+use common::errors::Suggestion;
+use common::files::{FileSpan, Files};
+use common::fix::{apply_suggestions, unified_diff};
+
+fn suggestion(start: u32, end: u32, replacement: &str) -> Suggestion {
+    Suggestion {
+        message: format!("Replace with `{}`", replacement),
+        span: FileSpan::new(Files::new().add("test.zpp", ""), Span::new(start, end)),
+        replacement: replacement.to_string(),
+    }
+}
+
+#[test]
+fn conflicting_suggestions_are_skipped() {
+    let input = "bool x = 1\nstatic int y = 2\n";
+    let suggestions = [
+        suggestion(11, 18, ""),
+        suggestion(0, 4, "int"),
+        // overlaps the first replacement
+        suggestion(2, 6, "float"),
+        // inserts where the `static` removal starts
+        suggestion(11, 11, "const "),
+    ];
+
+    let (fixed, applied) = apply_suggestions(input, &suggestions);
+    assert_eq!(fixed, "int x = 1\nint y = 2\n");
+    assert_eq!(applied, 2);
+}
+
+#[test]
+fn diffs_show_changed_lines_with_context() {
+    let old: String = (1..=12).map(|line| format!("int x{} = {}\n", line, line)).collect();
+    let new = old.replace("int x2 ", "bool x2 ").replace("int x11 ", "float x11 ");
+
+    assert_eq!(unified_diff("test.zpp", &old, &old), "");
+    assert_eq!(unified_diff("test.zpp", &old, &new), "\
+--- a/test.zpp
++++ b/test.zpp
+@@ -1,5 +1,5 @@
+ int x1 = 1
+-int x2 = 2
++bool x2 = 2
+ int x3 = 3
+ int x4 = 4
+ int x5 = 5
+@@ -8,5 +8,5 @@
+ int x8 = 8
+ int x9 = 9
+ int x10 = 10
+-int x11 = 11
++float x11 = 11
+ int x12 = 12
+");
+
+    assert_eq!(unified_diff("test.zpp", "int x = 1\n", ""), "\
+--- a/test.zpp
++++ b/test.zpp
+@@ -1,1 +0,0 @@
+-int x = 1
+");
+}
@@ -0,0 +1,52 @@
+use codespan_reporting::diagnostic::Severity;
+use common::codes::Code;
+use common::levels::{Level, LevelError, LintLevels};
+
+#[test]
+fn codes_are_reported_with_their_registered_severity_by_default() {
+    let levels = LintLevels::new();
+
+    assert_eq!(levels.level(Code::W001), Level::Warn);
+    assert_eq!(levels.severity(Code::W001), Some(Severity::Warning));
+    assert_eq!(levels.level(Code::E003), Level::Deny);
+    assert_eq!(levels.severity(Code::E003), Some(Severity::Error));
+}
+
+#[test]
+fn warnings_can_be_allowed_denied_and_forbidden() {
+    let mut levels = LintLevels::new();
+
+    levels.set(Code::W001, Level::Allow).unwrap();
+    assert_eq!(levels.severity(Code::W001), None);
+
+    levels.set(Code::W001, Level::Deny).unwrap();
+    assert_eq!(levels.severity(Code::W001), Some(Severity::Error));
+
+    levels.set(Code::W001, Level::Forbid).unwrap();
+    assert_eq!(levels.set(Code::W001, Level::Allow), Err(LevelError::Forbidden(Code::W001, Level::Allow)));
+    assert_eq!(levels.set(Code::W001, Level::Forbid), Ok(()));
+    assert_eq!(levels.severity(Code::W001), Some(Severity::Error));
+}
+
+#[test]
+fn errors_cannot_be_allowed_or_warned_about() {
+    let mut levels = LintLevels::new();
+
+    assert_eq!(levels.set(Code::E003, Level::Allow), Err(LevelError::Error(Code::E003)));
+    assert_eq!(levels.set(Code::E003, Level::Warn), Err(LevelError::Error(Code::E003)));
+    assert_eq!(levels.set(Code::E003, Level::Forbid), Ok(()));
+    assert_eq!(
+        LevelError::Error(Code::E003).to_string(),
+        "`E003` is an error, which cannot be allowed or warned about",
+    );
+}
+
+#[test]
+fn denied_warnings_are_reported_as_errors_unless_allowed() {
+    let mut levels = LintLevels::new();
+    levels.set_deny_warnings(true);
+    assert_eq!(levels.severity(Code::W001), Some(Severity::Error));
+
+    levels.set(Code::W001, Level::Allow).unwrap();
+    assert_eq!(levels.severity(Code::W001), None);
+}
@@ -0,0 +1,91 @@
+use codespan::Span;
+use common::codes::Code;
+use common::errors::{Reporting, Suggestion};
+use common::files::Files;
+use common::sarif::SarifLog;
+use serde_json::json;
+
+#[test]
+fn diagnostics_become_results_with_rules_and_fixes() {
+    let input = "int x = 1\nbool y = x\n";
+    let reporter = Reporting::new("test.zpp", input).quiet();
+    let diagnostic = reporter.emit_error(
+        "Mismatched types, expected `bool` but found `int`".to_string(),
+        Span::new(10, 20),
+        Vec::new(),
+        Code::E003,
+    );
+    let suggestion = Suggestion {
+        message: "Change the type of `y` to `int`".to_string(),
+        span: reporter.file_span(Span::new(10, 14)),
+        replacement: "int".to_string(),
+    };
+
+    let mut log = SarifLog::new();
+    log.add(&reporter, &diagnostic, &[suggestion]);
+    log.add(&reporter, &diagnostic, &[]);
+    let log = log.to_json();
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"], json!([{
+        "id": "E003",
+        "name": "mismatched-types",
+        "shortDescription": { "text": "A value does not have the type that is expected" },
+        "fullDescription": { "text": Code::E003.info().explanation },
+        "defaultConfiguration": { "level": "error" },
+    }]));
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["ruleId"], "E003");
+    assert_eq!(results[1]["ruleIndex"], 0);
+    assert_eq!(results[0]["locations"][0]["physicalLocation"]["region"], json!({
+        "startLine": 2,
+        "startColumn": 1,
+        "endLine": 2,
+        "endColumn": 11,
+        "charOffset": 10,
+        "charLength": 10,
+    }));
+
+    let replacement = &results[0]["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["charLength"], 4);
+    assert_eq!(replacement["insertedContent"]["text"], "int");
+    assert!(results[1].get("fixes").is_none());
+}
+
+#[test]
+fn regions_count_code_points_and_artifacts_are_uris() {
+    let input = "String s = \"héllo\" + 1\n";
+    let mut files = Files::new();
+    let relative = files.add("src/my file.zpp", input);
+    let absolute = files.add("/tmp/main.zpp", input);
+    let start = input.find('+').unwrap();
+
+    let mut log = SarifLog::new();
+    for file in [relative, absolute] {
+        let reporter = Reporting::for_file(&files, file).quiet();
+        let diagnostic = reporter.emit_error(
+            "Mismatched types".to_string(),
+            Span::new(start as u32, start as u32 + 3),
+            Vec::new(),
+            Code::E003,
+        );
+        log.add(&reporter, &diagnostic, &[]);
+    }
+    let log = log.to_json();
+
+    let run = &log["runs"][0];
+    let root = run["originalUriBaseIds"]["SRCROOT"]["uri"].as_str().unwrap();
+    assert!(root.starts_with("file:///") && root.ends_with('/'));
+
+    let location = &run["results"][0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"], json!({ "uri": "src/my%20file.zpp", "uriBaseId": "SRCROOT" }));
+    assert_eq!(location["region"]["startColumn"], 20);
+    assert_eq!(location["region"]["charOffset"], 19);
+    assert_eq!(location["region"]["charLength"], 3);
+
+    let location = &run["results"][1]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"], json!({ "uri": "file:///tmp/main.zpp" }));
+}
@@ -0,0 +1,69 @@
+use codespan::Span;
+use codespan_reporting::diagnostic::Severity;
+use common::codes::Code;
+use common::errors::{ErrorFormat, Reporting};
+use common::sink::{DiagnosticSink, Target};
+
+const INPUT: &str = "int x = y\nint z = w\n";
+
+fn emit_all(reporter: &Reporting) {
+    reporter.emit_error("Cannot find variable `w` in this scope".to_string(), Span::new(18, 19), Vec::new(), Code::E002);
+    reporter.emit_warning("The variable `x` is defined multiple times".to_string(), Span::new(4, 5), Vec::new(), Code::E004);
+    reporter.emit_error("Cannot find variable `y` in this scope".to_string(), Span::new(8, 9), Vec::new(), Code::E002);
+    reporter.emit_error("Cannot find variable `w` in this scope".to_string(), Span::new(18, 19), Vec::new(), Code::E002);
+}
+
+#[test]
+fn diagnostics_are_collected_and_counted() {
+    let sink = DiagnosticSink::new(Target::String);
+    emit_all(&Reporting::new("test.zpp", INPUT).with_sink(&sink));
+
+    // the repeated diagnostic is dropped
+    assert_eq!(sink.diagnostics().len(), 3);
+    assert_eq!(sink.error_count(), 2);
+    assert_eq!(sink.count(Severity::Warning), 1);
+
+    let output = sink.output();
+    assert!(output.starts_with("error[E002]: Cannot find variable `w` in this scope"));
+    assert!(output.contains("┌─ test.zpp:2:9"));
+    assert!(!output.contains('\u{1b}'), "strings are not colored by default");
+}
+
+#[test]
+fn buffered_diagnostics_are_sorted_when_flushed() {
+    let sink = DiagnosticSink::new(Target::String).with_format(ErrorFormat::Json).buffered();
+    emit_all(&Reporting::new("test.zpp", INPUT).with_sink(&sink));
+    assert_eq!(sink.output(), "");
+
+    sink.flush();
+    let lines: Vec<serde_json::Value> = sink.output().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let starts: Vec<u64> = lines.iter().map(|line| line["spans"][0]["start"].as_u64().unwrap()).collect();
+    assert_eq!(starts, [4, 8, 18]);
+}
+
+#[test]
+fn quiet_reporting_bypasses_the_sink() {
+    let sink = DiagnosticSink::new(Target::String);
+    emit_all(&Reporting::new("test.zpp", INPUT).with_sink(&sink).quiet());
+
+    assert!(sink.diagnostics().is_empty());
+}
+
+#[test]
+fn the_summary_counts_errors_and_warnings() {
+    let sink = DiagnosticSink::new(Target::String);
+    emit_all(&Reporting::new("test.zpp", INPUT).with_sink(&sink));
+    sink.summarize();
+    assert!(sink.output().ends_with("\nerror: 2 errors and 1 warning emitted\n"));
+
+    let sink = DiagnosticSink::new(Target::String);
+    Reporting::new("test.zpp", INPUT).with_sink(&sink)
+        .emit_warning("The variable `x` is defined multiple times".to_string(), Span::new(4, 5), Vec::new(), Code::E004);
+    sink.summarize();
+    assert!(sink.output().ends_with("\nwarning: 1 warning emitted\n"));
+
+    let sink = DiagnosticSink::new(Target::String).with_format(ErrorFormat::Json);
+    emit_all(&Reporting::new("test.zpp", INPUT).with_sink(&sink));
+    sink.summarize();
+    assert!(!sink.output().contains("emitted"));
+}
@@ -0,0 +1,22 @@
+use common::spelling::{closest, edit_distance};
+
+#[test]
+fn swapped_characters_are_a_single_edit() {
+    assert_eq!(edit_distance("fucn", "func"), 1);
+    assert_eq!(edit_distance("Strng", "String"), 1);
+    assert_eq!(edit_distance("count", "cuont"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "int"), 3);
+}
+
+#[test]
+fn only_close_candidates_are_suggested() {
+    let types = ["bool", "int", "float", "String", "null"];
+    assert_eq!(closest("Strng", types), Some("String"));
+    assert_eq!(closest("flaot", types), Some("float"));
+    assert_eq!(closest("char", types), None);
+    assert_eq!(closest("int", types), None, "exact matches are not misspellings");
+
+    // ties go to the first candidate in alphabetical order
+    assert_eq!(closest("ab", ["cb", "ac"]), Some("ac"));
+}
@@ -0,0 +1,61 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use common::features::Features;
+use database::Database;
+
+/// Generates a script of about `lines` lines, made of small functions and the calls to them.
+fn script(lines: usize) -> String {
+    let mut script = String::from("static int LIMIT = 10\nmut int total = 0\n\n");
+
+    for index in 0..lines / 6 {
+        script.push_str(&format!(
+            "// adds to the total\nfunc add{index}(int step) {{\n    total = total + step * LIMIT\n}}\nadd{index}({index})\n\n"
+        ));
+    }
+
+    script
+}
+
+/// Returns a database that holds a script as `bench.zpp`.
+fn database(text: &str) -> Database {
+    let mut db = Database::new();
+    db.set_features(Features::all());
+    db.set_source("bench.zpp", text);
+    db
+}
+
+/// Compares typing in the middle of a script and checking it again with checking a fresh copy
+/// of the edited script, which lexes and parses all of it.
+fn edit_latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("edit and check");
+
+    for lines in [500, 1_000, 5_000] {
+        let text = script(lines);
+        let offset = text.len() / 2 + text[text.len() / 2..].find("step *").unwrap();
+        let edited = format!("{}1 + {}", &text[..offset], &text[offset..]);
+
+        group.bench_with_input(BenchmarkId::new("incremental", lines), &text, |b, text| {
+            let mut db = database(text);
+            assert!(db.check("bench.zpp").is_empty());
+
+            // the text is typed and deleted again, so that every iteration edits the same text
+            b.iter(|| {
+                db.edit_source("bench.zpp", offset..offset, "1 + ");
+                db.check("bench.zpp");
+                db.edit_source("bench.zpp", offset..offset + 4, "");
+                db.check("bench.zpp")
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("full", lines), &text, |b, text| {
+            b.iter(|| {
+                database(&edited).check("bench.zpp");
+                database(text).check("bench.zpp")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, edit_latency);
+criterion_main!(benches);
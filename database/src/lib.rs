@@ -0,0 +1,457 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+use codespan::Span;
+use codespan_reporting::diagnostic::Severity;
+use ast::AstNode;
+use ast::checker::{CheckError, Checker};
+use ast::incremental;
+use ast::lexer::{Lexer, Token};
+use ast::lints::Linter;
+use ast::parser::SyntaxError;
+use ast::resolver::{resolve, Resolution};
+use common::errors::Reporting;
+use common::features::Features;
+use common::files::{FileId, Files};
+use common::levels::LintLevels;
+
+/// A point in the history of the inputs, which every change to a source advances.
+type Revision = u64;
+
+/// A query, together with the file it is about.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Query {
+    /// The levels of the diagnostic codes, which are set rather than computed and are the
+    /// same for every file
+    Levels,
+    /// The incubating features files may use, which are set like the levels
+    Features,
+    /// The text of a file, which is set rather than computed
+    Source(String),
+    /// The tokens, AST and syntax errors of a file
+    Parse(String),
+    /// What the names in a file refer to
+    Resolve(String),
+    /// The diagnostics of a file, which include type errors
+    Check(String),
+    /// The findings of the lints about a file
+    Lint(String),
+    /// The IR of a file, if it has no errors
+    Lower(String),
+}
+
+/// The tokens and AST of a file, which are shared with the document they were parsed from
+/// until it is edited.
+#[derive(Debug, PartialEq)]
+pub struct Parse {
+    pub tokens: Rc<Vec<Token>>,
+    pub ast: Rc<AstNode>,
+    pub syntax_errors: Vec<SyntaxError>,
+    /// The spans of the text that could not be lexed
+    pub lex_errors: Vec<Span>,
+}
+
+struct Source {
+    /// The text, which is relexed and reparsed only where it is edited, or `None` if the file
+    /// was removed
+    document: Option<incremental::Document>,
+    changed_at: Revision,
+}
+
+/// The result of a query, as of the last revision it was verified in.
+struct Memo {
+    value: Rc<dyn Any>,
+    verified_at: Revision,
+    /// The revision in which the value last differed from the value before it
+    changed_at: Revision,
+    /// The revision the query was last run in
+    executed_at: Revision,
+    /// The queries that were run to compute the value
+    dependencies: Vec<Query>,
+}
+
+/// Struct representing the compilation database, which answers queries about source files on
+/// demand and remembers the answers.
+///
+/// Every query records the queries it runs. When a source changes, a remembered answer is
+/// reused if none of the queries it depends on changed, which is checked by bringing them up
+/// to date first. A query that is run again but gives the same answer as before does not count
+/// as changed, so replacing a comment with one of the same length reparses and checks the file,
+/// but does not resolve or lower it again. The results hold spans, so any edit that moves the
+/// code after it changes them and runs every query of the file again.
+///
+/// Both `zxx check` and the language server answer through a `Database`, so they share the
+/// same pipeline.
+pub struct Database {
+    revision: Revision,
+    sources: HashMap<String, Source>,
+    /// The files diagnostics point at, which hold the same text as the sources
+    files: Files,
+    levels: LintLevels,
+    /// The revision the levels were last set in
+    levels_changed_at: Revision,
+    features: Features,
+    /// The revision the features were last set in
+    features_changed_at: Revision,
+    memos: RefCell<HashMap<Query, Memo>>,
+    /// The dependencies recorded for every query that is being run, innermost last
+    active: RefCell<Vec<Vec<Query>>>,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Database {
+            revision: 0,
+            sources: HashMap::new(),
+            files: Files::new(),
+            levels: LintLevels::new(),
+            levels_changed_at: 0,
+            features: Features::new(),
+            features_changed_at: 0,
+            memos: RefCell::new(HashMap::new()),
+            active: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Sets the text of a file, adding the file if it is new.
+    pub fn set_source(&mut self, name: &str, text: &str) {
+        self.set(name, text, false);
+    }
+
+    /// Sets the text of a file that only exists in memory, such as stdin, adding the file if it
+    /// is new.
+    pub fn set_virtual_source(&mut self, name: &str, text: &str) {
+        self.set(name, text, true);
+    }
+
+    fn set(&mut self, name: &str, text: &str, is_virtual: bool) {
+        if self.text(name) == Some(text) {
+            return
+        }
+
+        self.revision += 1;
+        self.sources.insert(name.to_string(), Source {
+            document: Some(incremental::Document::new(text)),
+            changed_at: self.revision,
+        });
+
+        match self.files.find(name) {
+            Some(id) => self.files.set_source(id, text),
+            None if is_virtual => drop(self.files.add_virtual(name, text)),
+            None => drop(self.files.add(name, text)),
+        }
+    }
+
+    /// Sets the levels diagnostic codes are reported at in every file, before the attributes
+    /// of functions are applied.
+    pub fn set_levels(&mut self, levels: LintLevels) {
+        if self.levels == levels {
+            return
+        }
+
+        self.revision += 1;
+        self.levels = levels;
+        self.levels_changed_at = self.revision;
+    }
+
+    /// Sets the incubating features every file may use.
+    pub fn set_features(&mut self, features: Features) {
+        if self.features == features {
+            return
+        }
+
+        self.revision += 1;
+        self.features = features;
+        self.features_changed_at = self.revision;
+    }
+
+    /// Replaces a byte range of the text of a file, which is only relexed and reparsed where
+    /// it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the file, which must have been set.
+    /// * `range` - The byte range that is replaced.
+    /// * `replacement` - The text the range is replaced by.
+    pub fn edit_source(&mut self, name: &str, range: Range<usize>, replacement: &str) {
+        self.revision += 1;
+
+        let source = self.sources.get_mut(name).expect("edited file was never set");
+        let document = source.document.as_mut().expect("edited file was removed");
+        document.edit(range, replacement);
+        source.changed_at = self.revision;
+
+        let id = self.files.find(name).expect("set files are in the file database");
+        self.files.set_source(id, document.text());
+    }
+
+    /// Removes a file, forgetting everything that was computed about it. Its ID stays
+    /// reserved for it.
+    pub fn remove_source(&mut self, name: &str) {
+        self.revision += 1;
+        self.sources.insert(name.to_string(), Source { document: None, changed_at: self.revision });
+        self.memos.borrow_mut().retain(|query, _| query.file() != Some(name));
+
+        if let Some(id) = self.files.find(name) {
+            self.files.set_source(id, "");
+        }
+    }
+
+    /// Returns the files that diagnostics point at, which are the files that were set.
+    pub fn files(&self) -> &Files {
+        &self.files
+    }
+
+    /// Returns the ID of a file that was set.
+    pub fn file_id(&self, name: &str) -> Option<FileId> {
+        self.files.find(name)
+    }
+
+    /// Returns the text of a file, or `None` if it was never set or was removed.
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.sources.get(name)?.document.as_ref().map(|document| document.text())
+    }
+
+    /// Returns the queries that were run rather than reused since the sources last changed,
+    /// which shows how much work a change caused.
+    pub fn executed(&self) -> Vec<Query> {
+        let mut executed: Vec<Query> = self.memos.borrow().iter()
+            .filter(|(_, memo)| memo.executed_at == self.revision)
+            .map(|(query, _)| query.clone())
+            .collect();
+        executed.sort();
+        executed
+    }
+
+    /// Returns the text of a file as a dependency of the query that is being run. Files that
+    /// do not exist are empty.
+    fn source(&self, name: &str) -> &str {
+        self.record(Query::Source(name.to_string()));
+        self.text(name).unwrap_or_default()
+    }
+
+    /// Returns the levels of the diagnostic codes as a dependency of the query that is being
+    /// run.
+    fn levels(&self) -> &LintLevels {
+        self.record(Query::Levels);
+        &self.levels
+    }
+
+    /// Returns the enabled features as a dependency of the query that is being run.
+    fn features(&self) -> &Features {
+        self.record(Query::Features);
+        &self.features
+    }
+
+    /// Returns the tokens, AST and syntax errors of a file.
+    pub fn parse(&self, name: &str) -> Rc<Parse> {
+        self.fetch(Query::Parse(name.to_string()), |db| {
+            db.source(name);
+
+            match db.sources.get(name).and_then(|source| source.document.as_ref()) {
+                Some(document) => Parse {
+                    tokens: document.tokens(),
+                    ast: document.ast(),
+                    syntax_errors: document.syntax_errors(),
+                    lex_errors: document.lex_errors(),
+                },
+                None => Parse {
+                    tokens: Rc::new(Vec::new()),
+                    ast: Rc::new(AstNode::Document { body: Box::new(AstNode::Block { body: Vec::new() }) }),
+                    syntax_errors: Vec::new(),
+                    lex_errors: Vec::new(),
+                },
+            }
+        })
+    }
+
+    /// Returns what the names in a file refer to, which is known for the parts of the file
+    /// that could be parsed.
+    pub fn resolution(&self, name: &str) -> Rc<Resolution> {
+        self.fetch(Query::Resolve(name.to_string()), |db| {
+            let parse = db.parse(name);
+            resolve(&parse.ast, &parse.tokens)
+        })
+    }
+
+    /// Returns the errors and warnings of a file, together with the suggestions that fix
+    /// them.
+    ///
+    /// Like the stages of the compiler, syntax errors are only reported if the file could be
+    /// lexed, and the file is only checked if it could be parsed, so that names whose
+    /// declaration has a syntax error are not reported as missing.
+    pub fn check(&self, name: &str) -> Rc<Vec<CheckError>> {
+        self.fetch(Query::Check(name.to_string()), |db| {
+            let parse = db.parse(name);
+            let text = db.source(name);
+            let Some(file) = db.file_id(name) else {
+                return Vec::new()
+            };
+            let reporter = Reporting::for_file(&db.files, file).quiet();
+
+            if !parse.lex_errors.is_empty() {
+                let lexer = Lexer::new(name, text).with_reporting(reporter);
+                return parse.lex_errors.iter().map(|span| (lexer.report_error(*span), Vec::new())).collect()
+            }
+
+            if !parse.syntax_errors.is_empty() {
+                return parse.syntax_errors.iter().map(|error| error.report(&reporter)).collect()
+            }
+
+            Checker::new(name, text)
+                .with_reporting(reporter)
+                .with_levels(db.levels().clone())
+                .with_features(db.features().clone())
+                .check_with_suggestions(&parse.ast)
+        })
+    }
+
+    /// Returns the warnings of the lints about a file, together with the fixes of the lints
+    /// that have one. Files with errors are not linted, since the lints rely on every name
+    /// being resolved and every value being well typed.
+    pub fn lint(&self, name: &str) -> Rc<Vec<CheckError>> {
+        self.fetch(Query::Lint(name.to_string()), |db| {
+            if db.check(name).iter().any(|(diagnostic, _)| diagnostic.severity >= Severity::Error) {
+                return Vec::new()
+            }
+
+            let parse = db.parse(name);
+            let resolution = db.resolution(name);
+            let text = db.source(name);
+            let Some(file) = db.file_id(name) else {
+                return Vec::new()
+            };
+
+            Linter::new(name, text)
+                .with_reporting(Reporting::for_file(&db.files, file).quiet())
+                .with_levels(db.levels().clone())
+                .lint(&parse.ast, &resolution)
+        })
+    }
+
+    /// Returns the unoptimized IR of a file, or `None` if the file has errors.
+    pub fn lower(&self, name: &str) -> Rc<Option<ir::Module>> {
+        self.fetch(Query::Lower(name.to_string()), |db| {
+            if db.check(name).iter().any(|(diagnostic, _)| diagnostic.severity >= Severity::Error) {
+                return None
+            }
+
+            Some(ir::lower::lower(&db.parse(name).ast))
+        })
+    }
+
+    /// Returns the remembered result of a query if it is up to date, or runs the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query, which is recorded as a dependency of the query being run.
+    /// * `run` - Computes the result, recording the queries it runs as dependencies.
+    fn fetch<T: PartialEq + 'static>(&self, query: Query, run: impl FnOnce(&Self) -> T) -> Rc<T> {
+        self.record(query.clone());
+
+        if let Some(value) = self.verify(&query) {
+            return value.downcast().expect("queries always give the same type")
+        }
+
+        self.active.borrow_mut().push(Vec::new());
+        let value = Rc::new(run(self));
+        let dependencies = self.active.borrow_mut().pop().expect("the query's dependencies were pushed");
+
+        let mut memos = self.memos.borrow_mut();
+        // a value that did not change keeps the revision it changed in, so that the queries
+        // depending on it are not run again
+        let changed_at = match memos.get(&query) {
+            Some(memo) if memo.value.downcast_ref::<T>() == Some(&*value) => memo.changed_at,
+            _ => self.revision,
+        };
+        memos.insert(query, Memo {
+            value: value.clone(),
+            verified_at: self.revision,
+            changed_at,
+            executed_at: self.revision,
+            dependencies,
+        });
+
+        value
+    }
+
+    /// Returns the remembered result of a query if none of its dependencies changed since it
+    /// was last verified.
+    fn verify(&self, query: &Query) -> Option<Rc<dyn Any>> {
+        let (verified_at, dependencies) = {
+            let memos = self.memos.borrow();
+            let memo = memos.get(query)?;
+            if memo.verified_at == self.revision {
+                return Some(memo.value.clone())
+            }
+
+            (memo.verified_at, memo.dependencies.clone())
+        };
+
+        if dependencies.iter().any(|dependency| self.changed_at(dependency) > verified_at) {
+            return None
+        }
+
+        let mut memos = self.memos.borrow_mut();
+        let memo = memos.get_mut(query)?;
+        memo.verified_at = self.revision;
+        Some(memo.value.clone())
+    }
+
+    /// Brings a query up to date without recording it as a dependency, returning the revision
+    /// its result last changed in.
+    fn changed_at(&self, query: &Query) -> Revision {
+        match query {
+            Query::Levels => return self.levels_changed_at,
+            Query::Features => return self.features_changed_at,
+            Query::Source(name) => return self.sources.get(name).map_or(0, |source| source.changed_at),
+            _ => {}
+        }
+
+        // the query is a dependency of the query being verified, not of the one being run
+        self.active.borrow_mut().push(Vec::new());
+        match query {
+            Query::Levels | Query::Features | Query::Source(_) => {}
+            Query::Parse(name) => drop(self.parse(name)),
+            Query::Resolve(name) => drop(self.resolution(name)),
+            Query::Check(name) => drop(self.check(name)),
+            Query::Lint(name) => drop(self.lint(name)),
+            Query::Lower(name) => drop(self.lower(name)),
+        }
+        self.active.borrow_mut().pop();
+
+        self.memos.borrow()[query].changed_at
+    }
+
+    /// Records a query as a dependency of the query that is being run.
+    fn record(&self, query: Query) {
+        if let Some(dependencies) = self.active.borrow_mut().last_mut() {
+            if !dependencies.contains(&query) {
+                dependencies.push(query);
+            }
+        }
+    }
+}
+
+impl Query {
+    /// Returns the name of the file the query is about, or `None` if it is about every file.
+    pub fn file(&self) -> Option<&str> {
+        match self {
+            Query::Levels | Query::Features => None,
+            Query::Source(name)
+            | Query::Parse(name)
+            | Query::Resolve(name)
+            | Query::Check(name)
+            | Query::Lint(name)
+            | Query::Lower(name) => Some(name),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use common::codes::{Code, REGISTRY};
+use database::Database;
+
+/// Returns the first indented block after a line of an explanation.
+fn example(explanation: &str, heading: &str) -> String {
+    let start = explanation.find(heading).unwrap() + heading.len();
+    explanation[start..].trim_start_matches('\n')
+        .lines()
+        .take_while(|line| line.is_empty() || line.starts_with("    "))
+        .map(|line| format!("{}\n", line.strip_prefix("    ").unwrap_or(line)))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[test]
+fn erroneous_examples_report_their_code() {
+    let mut db = Database::new();
+
+    // runtime errors are only reported by the REPL
+    for info in REGISTRY.iter().filter(|info| info.code != Code::E013) {
+        let source = example(info.explanation, "Erroneous code example:");
+        db.set_source("example.zpp", &format!("{}\n", source));
+
+        let codes: Vec<String> = db.check("example.zpp").iter()
+            .chain(db.lint("example.zpp").iter())
+            .filter_map(|(diagnostic, _)| diagnostic.code.clone())
+            .collect();
+        assert!(codes.iter().any(|code| code == info.code.as_str()), "{} reports {:?}:\n{}", info.code, codes, source);
+    }
+}
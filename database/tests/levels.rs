@@ -0,0 +1,69 @@
+use codespan_reporting::diagnostic::Severity;
+use common::codes::Code;
+use common::levels::{Level, LintLevels};
+use database::{Database, Query};
+
+const SOURCE: &str = "float total = 0.1 + 0.2\nbool exact = total == 0.3\n";
+
+/// Returns the codes and severities of the diagnostics of a file.
+fn diagnostics(db: &Database, name: &str) -> Vec<(String, Severity)> {
+    db.check(name).iter()
+        .map(|(diagnostic, _)| (diagnostic.code.clone().unwrap(), diagnostic.severity))
+        .collect()
+}
+
+#[test]
+fn warnings_do_not_stop_lowering() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", SOURCE);
+
+    assert_eq!(diagnostics(&db, "a.zpp"), [("W001".to_string(), Severity::Warning)]);
+    assert!(db.lower("a.zpp").is_some());
+}
+
+#[test]
+fn changing_the_levels_checks_files_again() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", SOURCE);
+    db.lower("a.zpp");
+    db.executed();
+
+    let mut levels = LintLevels::new();
+    levels.set(Code::W001, Level::Deny).unwrap();
+    db.set_levels(levels);
+
+    assert_eq!(diagnostics(&db, "a.zpp"), [("W001".to_string(), Severity::Error)]);
+    assert!(db.lower("a.zpp").is_none());
+    assert_eq!(db.executed(), [Query::Check("a.zpp".to_string()), Query::Lower("a.zpp".to_string())]);
+}
+
+#[test]
+fn attributes_set_levels_within_a_function() {
+    let source = "@allow(W001)\nfunc f(float a) {\n    bool b = a == 1.0\n}\n\
+        @deny(float-comparison)\nfunc g(float a) {\n    bool b = a != 1.0\n}\n\
+        func h(float a) {\n    bool b = a == 1.0\n}\n";
+    let mut db = Database::new();
+    db.set_source("a.zpp", source);
+
+    assert_eq!(diagnostics(&db, "a.zpp"), [
+        ("W001".to_string(), Severity::Error),
+        ("W001".to_string(), Severity::Warning),
+    ]);
+}
+
+#[test]
+fn invalid_attributes_are_errors() {
+    let mut levels = LintLevels::new();
+    levels.set(Code::W001, Level::Forbid).unwrap();
+    let mut db = Database::new();
+    db.set_levels(levels);
+    db.set_source("a.zpp", "@allow(W001)\nfunc f() {}\n@inline()\nfunc g() {}\n@deny(W999)\nfunc h() {}\n@warn(E003)\nfunc i() {}\n");
+
+    let messages: Vec<String> = db.check("a.zpp").iter().map(|(diagnostic, _)| diagnostic.message.clone()).collect();
+    assert_eq!(messages, [
+        "`W001` is forbidden, so it cannot be set to `allow`",
+        "Unknown attribute `@inline`",
+        "Unknown diagnostic code `W999`",
+        "`E003` is an error, which cannot be allowed or warned about",
+    ]);
+}
@@ -0,0 +1,107 @@
+use database::{Database, Query};
+
+const A: &str = "func add(int a, int b) {\n    int c = a + b\n}\n\n// the answer\nint x = 1\nadd(x, 2)\n";
+const B: &str = "bool flag = true\nflag = !flag\n";
+
+/// Runs every query of a file.
+fn check_all(db: &Database, name: &str) {
+    db.resolution(name);
+    db.lower(name);
+}
+
+fn queries(name: &str, kinds: &[fn(String) -> Query]) -> Vec<Query> {
+    let mut queries: Vec<Query> = kinds.iter().map(|kind| kind(name.to_string())).collect();
+    queries.sort();
+    queries
+}
+
+#[test]
+fn answers_are_remembered() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", A);
+
+    check_all(&db, "a.zpp");
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lower]));
+    assert!(db.check("a.zpp").is_empty());
+    assert!(db.lower("a.zpp").is_some());
+
+    // setting the same text again is not a change
+    db.set_source("a.zpp", A);
+    check_all(&db, "a.zpp");
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lower]));
+}
+
+#[test]
+fn editing_a_file_leaves_other_files_alone() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", A);
+    db.set_source("b.zpp", B);
+    check_all(&db, "a.zpp");
+    check_all(&db, "b.zpp");
+
+    let start = A.find("a + b").unwrap();
+    db.edit_source("a.zpp", start..start + 5, "a * b");
+    check_all(&db, "a.zpp");
+    check_all(&db, "b.zpp");
+
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lower]));
+    assert_eq!(db.text("a.zpp").unwrap(), A.replace("a + b", "a * b"));
+}
+
+#[test]
+fn unchanged_results_cut_off_dependent_queries() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", A);
+    check_all(&db, "a.zpp");
+
+    // a comment of the same length leaves the tokens, the AST and the diagnostics as they were
+    let start = A.find("answer").unwrap();
+    db.edit_source("a.zpp", start..start + 6, "result");
+    check_all(&db, "a.zpp");
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Check]));
+
+    // a longer comment moves the code after it, so every span in the results changes
+    let start = db.text("a.zpp").unwrap().find("result").unwrap();
+    db.edit_source("a.zpp", start..start + 6, "final result");
+    check_all(&db, "a.zpp");
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lower]));
+
+    // a type error is found, and the file no longer lowers
+    let start = db.text("a.zpp").unwrap().find("1\n").unwrap();
+    db.edit_source("a.zpp", start..start + 1, "true");
+    check_all(&db, "a.zpp");
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lower]));
+    assert_eq!(db.check("a.zpp")[0].0.code.as_deref(), Some("E003"));
+    assert!(db.lower("a.zpp").is_none());
+}
+
+#[test]
+fn removed_files_are_forgotten() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", "int x = y\n");
+    assert_eq!(db.check("a.zpp")[0].0.code.as_deref(), Some("E002"));
+
+    db.remove_source("a.zpp");
+    assert_eq!(db.text("a.zpp"), None);
+    assert!(db.check("a.zpp").is_empty());
+
+    db.set_source("a.zpp", "int x = 1\n");
+    assert!(db.check("a.zpp").is_empty());
+}
+
+#[test]
+fn files_are_only_linted_without_errors() {
+    let mut db = Database::new();
+    db.set_source("a.zpp", "int unused = y\n");
+    assert!(db.lint("a.zpp").is_empty());
+
+    db.set_source("a.zpp", "int unused = 1\n");
+    assert_eq!(db.lint("a.zpp")[0].0.code.as_deref(), Some("W002"));
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lint]));
+
+    // the lints are remembered like every other query
+    db.lint("a.zpp");
+    db.set_source("a.zpp", "int unused = 1\n");
+    db.lint("a.zpp");
+    assert_eq!(db.executed(), queries("a.zpp", &[Query::Parse, Query::Resolve, Query::Check, Query::Lint]));
+}
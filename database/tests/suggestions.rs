@@ -0,0 +1,46 @@
+use database::Database;
+
+/// Returns the replacements suggested for a source, with the text they replace.
+fn suggestions(source: &str) -> Vec<(String, String)> {
+    let mut db = Database::new();
+    db.set_source("test.zpp", source);
+
+    db.check("test.zpp").iter()
+        .flat_map(|(_, suggestions)| suggestions.clone())
+        .map(|suggestion| {
+            let replaced = &source[suggestion.span.start().to_usize()..suggestion.span.end().to_usize()];
+            (replaced.to_string(), suggestion.replacement)
+        })
+        .collect()
+}
+
+fn pair(replaced: &str, replacement: &str) -> (String, String) {
+    (replaced.to_string(), replacement.to_string())
+}
+
+#[test]
+fn misspelt_names_are_corrected() {
+    assert_eq!(suggestions("int count = 1\nint y = cuont\ncuont = 2\n"), [pair("cuont", "count"), pair("cuont", "count")]);
+    assert_eq!(suggestions("static Strng s = \"a\"\n"), [pair("Strng", "String")]);
+    assert_eq!(suggestions("func f(int a, flaot b) {\n    print(a)\n}\n"), [pair("flaot", "float")]);
+    assert_eq!(suggestions("func add(int a) {\n    print(a)\n}\nad(1)\nprnt(1)\n"), [pair("ad", "add"), pair("prnt", "print")]);
+
+    // names that are not similar to any candidate get no suggestion
+    assert_eq!(suggestions("int y = total\n"), []);
+}
+
+#[test]
+fn misspelt_keywords_are_corrected() {
+    assert_eq!(suggestions("fucn add(int a) {\n    print(a)\n}\n"), [pair("fucn", "func")]);
+    assert_eq!(suggestions("statc int x = 1\n"), [pair("statc", "static")]);
+}
+
+#[test]
+fn assigned_variables_are_declared_mutable() {
+    assert_eq!(suggestions("int count = 1\ncount = 2\n"), [pair("", "mut ")]);
+    assert_eq!(suggestions("static int count = 1\ncount = 2\n"), [pair("static", "mut")]);
+    assert_eq!(suggestions("mut flaot ratio = 1\nratio = 2\n"), [pair("flaot", "float")]);
+
+    // parameters cannot be declared mutable
+    assert_eq!(suggestions("func f(int a) {\n    a = 2\n}\n"), []);
+}
@@ -0,0 +1,315 @@
+pub mod lower;
+pub mod passes;
+
+use std::fmt;
+use codespan::Span;
+pub use ast::{BinaryOperator, Type, UnaryOperator};
+
+/// An SSA value. Every value is defined exactly once, either as a function parameter or
+/// as the result of an instruction, and is numbered uniquely within its function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Value(pub u32);
+
+/// Identifies a global variable of a `Module`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlobalId(pub u32);
+
+/// Identifies a function of a `Module`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FuncId(pub u32);
+
+/// A constant operand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Int(i32),
+    Float(f32),
+    Bool(bool),
+    String(String),
+    /// The null value, its type is the type of the value it defines
+    Null,
+}
+
+/// The operation performed by an instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstKind {
+    /// Defines a constant
+    Const(Constant),
+    /// Defines a copy of another value
+    Copy(Value),
+    /// Converts an `int` to a `float`
+    IntToFloat(Value),
+    /// Applies an operator to two values of the same type
+    Binary(BinaryOperator, Value, Value),
+    /// Applies an operator to a single value
+    Unary(UnaryOperator, Value),
+    /// Reads a global variable
+    Load(GlobalId),
+    /// Writes a global variable
+    Store(GlobalId, Value),
+    /// Calls a function of the module
+    Call(FuncId, Vec<Value>),
+    /// Prints a value of any type on its own line
+    Print(Value),
+}
+
+impl InstKind {
+    /// Returns whether removing the instruction could change the behavior of the program.
+    pub fn has_side_effects(&self) -> bool {
+        matches!(self, InstKind::Store(..) | InstKind::Call(..) | InstKind::Print(_))
+    }
+
+    /// Returns the values the instruction reads.
+    pub fn operands(&self) -> Vec<Value> {
+        match self {
+            InstKind::Const(_) | InstKind::Load(_) => vec![],
+            InstKind::Copy(value)
+            | InstKind::IntToFloat(value)
+            | InstKind::Store(_, value)
+            | InstKind::Unary(_, value)
+            | InstKind::Print(value) => vec![*value],
+            InstKind::Binary(_, lhs, rhs) => vec![*lhs, *rhs],
+            InstKind::Call(_, args) => args.clone(),
+        }
+    }
+
+    /// Returns mutable references to the values the instruction reads.
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            InstKind::Const(_) | InstKind::Load(_) => vec![],
+            InstKind::Copy(value)
+            | InstKind::IntToFloat(value)
+            | InstKind::Store(_, value)
+            | InstKind::Unary(_, value)
+            | InstKind::Print(value) => vec![value],
+            InstKind::Binary(_, lhs, rhs) => vec![lhs, rhs],
+            InstKind::Call(_, args) => args.iter_mut().collect(),
+        }
+    }
+}
+
+/// A single instruction, optionally defining a value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inst {
+    pub result: Option<Value>,
+    pub kind: InstKind,
+    /// The span of the expression the instruction was lowered from, if it can fail at run
+    /// time, which is where the failure is reported
+    pub span: Option<Span>,
+}
+
+/// The instruction that ends a basic block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    /// Returns from the function, with a value unless it returns `void`
+    Return(Option<Value>),
+}
+
+impl Terminator {
+    /// Returns mutable references to the values the terminator reads.
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Terminator::Return(value) => value.iter_mut().collect(),
+        }
+    }
+
+    /// Returns the values the terminator reads.
+    pub fn operands(&self) -> Vec<Value> {
+        match self {
+            Terminator::Return(value) => value.iter().copied().collect(),
+        }
+    }
+}
+
+/// A straight-line sequence of instructions, the first block of a function is its entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub insts: Vec<Inst>,
+    pub terminator: Terminator,
+}
+
+/// A function in SSA form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    /// The names of the parameters in declaration order, together with the values they define
+    pub params: Vec<(String, Value)>,
+    pub returns: Type,
+    pub blocks: Vec<Block>,
+    /// The type of every value, indexed by the value's number
+    pub value_types: Vec<Type>,
+}
+
+impl Function {
+    pub fn new(name: &str, returns: Type) -> Self {
+        Function {
+            name: name.to_string(),
+            params: Vec::new(),
+            returns,
+            blocks: vec![Block {
+                insts: Vec::new(),
+                terminator: Terminator::Return(None),
+            }],
+            value_types: Vec::new(),
+        }
+    }
+
+    /// Creates a new value of the given type.
+    pub fn new_value(&mut self, value_type: Type) -> Value {
+        self.value_types.push(value_type);
+        Value(self.value_types.len() as u32 - 1)
+    }
+
+    /// Returns the type of a value.
+    pub fn value_type(&self, value: Value) -> Type {
+        self.value_types[value.0 as usize]
+    }
+
+    /// Returns the types of the parameters in declaration order.
+    pub fn param_types(&self) -> Vec<Type> {
+        self.params.iter()
+            .map(|(_, value)| self.value_type(*value))
+            .collect()
+    }
+
+    /// Returns the number of instructions in all blocks.
+    pub fn inst_count(&self) -> usize {
+        self.blocks.iter()
+            .map(|block| block.insts.len())
+            .sum()
+    }
+}
+
+/// A global variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Global {
+    pub name: String,
+    pub var_type: Type,
+}
+
+/// A lowered Z++ document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub globals: Vec<Global>,
+    pub functions: Vec<Function>,
+    /// The function that runs the document's top-level statements. It is not part of the
+    /// document's own functions, so backends should not export it under its name.
+    pub init: FuncId,
+    /// The parameterless `Main` function, if the document declares one
+    pub entry: Option<FuncId>,
+}
+
+impl Module {
+    pub fn function(&self, id: FuncId) -> &Function {
+        &self.functions[id.0 as usize]
+    }
+
+    pub fn global(&self, id: GlobalId) -> &Global {
+        &self.globals[id.0 as usize]
+    }
+
+    /// Returns the ids and definitions of the document's own functions, skipping `init`.
+    pub fn user_functions(&self) -> impl Iterator<Item = (FuncId, &Function)> {
+        self.functions.iter()
+            .enumerate()
+            .map(|(index, function)| (FuncId(index as u32), function))
+            .filter(move |(id, _)| *id != self.init)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Int(value) => write!(f, "{}", value),
+            Constant::Float(value) => write!(f, "{:?}", value),
+            Constant::Bool(value) => write!(f, "{}", value),
+            Constant::String(value) => write!(f, "{:?}", value),
+            Constant::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// Returns the name a binary operator is printed with in the text form of the IR.
+fn binary_mnemonic(op: BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => "add",
+        BinaryOperator::Sub => "sub",
+        BinaryOperator::Mul => "mul",
+        BinaryOperator::Div => "div",
+        BinaryOperator::Eq => "eq",
+        BinaryOperator::NotEq => "ne",
+        BinaryOperator::Less => "lt",
+        BinaryOperator::Greater => "gt",
+        BinaryOperator::LessEq => "le",
+        BinaryOperator::GreaterEq => "ge",
+        BinaryOperator::And => "and",
+        BinaryOperator::Or => "or",
+    }
+}
+
+impl Module {
+    fn fmt_inst(&self, f: &mut fmt::Formatter<'_>, function: &Function, inst: &Inst) -> fmt::Result {
+        write!(f, "    ")?;
+        if let Some(result) = inst.result {
+            write!(f, "{}: {} = ", result, function.value_type(result))?;
+        }
+
+        let join = |values: &[Value]| values.iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match &inst.kind {
+            InstKind::Const(constant) => writeln!(f, "const {}", constant),
+            InstKind::Copy(value) => writeln!(f, "copy {}", value),
+            InstKind::IntToFloat(value) => writeln!(f, "itof {}", value),
+            InstKind::Binary(op, lhs, rhs) => writeln!(f, "{} {}, {}", binary_mnemonic(*op), lhs, rhs),
+            InstKind::Unary(UnaryOperator::Neg, value) => writeln!(f, "neg {}", value),
+            InstKind::Unary(UnaryOperator::Not, value) => writeln!(f, "not {}", value),
+            InstKind::Load(global) => writeln!(f, "load @{}", self.global(*global).name),
+            InstKind::Store(global, value) => writeln!(f, "store @{}, {}", self.global(*global).name, value),
+            InstKind::Call(callee, args) => writeln!(f, "call @{}({})", self.function(*callee).name, join(args)),
+            InstKind::Print(value) => writeln!(f, "print {}", value),
+        }
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for global in &self.globals {
+            writeln!(f, "global @{}: {}", global.name, global.var_type)?;
+        }
+
+        for (index, function) in self.functions.iter().enumerate() {
+            let params = function.params.iter()
+                .map(|(name, value)| format!("{}: {} ({})", value, function.value_type(*value), name))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let kind = if FuncId(index as u32) == self.init { "init " } else { "" };
+            writeln!(f, "\n{}func @{}({}) -> {} {{", kind, function.name, params, function.returns)?;
+
+            for (index, block) in function.blocks.iter().enumerate() {
+                writeln!(f, "  bb{}:", index)?;
+
+                for inst in &block.insts {
+                    self.fmt_inst(f, function, inst)?;
+                }
+
+                match &block.terminator {
+                    Terminator::Return(Some(value)) => writeln!(f, "    return {}", value)?,
+                    Terminator::Return(None) => writeln!(f, "    return")?,
+                }
+            }
+
+            writeln!(f, "}}")?;
+        }
+
+        Ok(())
+    }
+}
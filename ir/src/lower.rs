@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use ast::{AstNode, Type, TypedValue};
+use ast::checker::{binary_types, literal_type, unary_type, PRINT};
+pub use ast::checker::ENTRY_POINT;
+use ast::constant::{coerce, evaluate};
+use crate::{BinaryOperator, Constant, FuncId, Function, Global, GlobalId, Inst, InstKind, Module, Value};
+
+/// The name given to the function that runs the top-level statements.
+const INIT: &str = "init";
+
+/// Lowers a checked document into an IR module.
+///
+/// Lowering is deliberately naive, for example every integer stored in a `float` is
+/// converted at run time, and every variable initialized from another one gets its own
+/// copy. Cleaning this up is left to the optimization passes. The exception are expressions
+/// that only read literals and static variables, which are always evaluated at compile
+/// time. Static variables therefore never become globals, every use is replaced by their
+/// value.
+///
+/// # Arguments
+///
+/// * `document` - The `AstNode::Document` to lower, which must have passed the checker.
+///
+/// # Returns
+///
+/// Returns the lowered `Module`.
+pub fn lower(document: &AstNode) -> Module {
+    let mut body: &[AstNode] = &[];
+    if let AstNode::Document { body: block } = document {
+        if let AstNode::Block { body: nodes } = block.as_ref() {
+            body = nodes;
+        }
+    }
+
+    let mut lowering = Lowering {
+        globals: HashMap::new(),
+        statics: HashMap::new(),
+        functions: HashMap::new(),
+    };
+
+    let mut module = Module {
+        globals: Vec::new(),
+        functions: Vec::new(),
+        init: FuncId(0),
+        entry: None,
+    };
+
+    for node in body {
+        match node {
+            AstNode::FuncDeclaration { name, params, returns, .. } => {
+                let id = FuncId(module.functions.len() as u32);
+                let mut function = Function::new(name, *returns);
+
+                for (param, param_type) in params {
+                    let value = function.new_value(*param_type);
+                    function.params.push((param.clone(), value));
+                }
+
+                if name == ENTRY_POINT && params.is_empty() {
+                    module.entry = Some(id);
+                }
+
+                lowering.functions.insert(name.clone(), (id, function.param_types()));
+                module.functions.push(function);
+            }
+            AstNode::VarDeclaration { name, var_type, is_static: true, value, .. } => {
+                let constant = evaluate(value, &|reference| lowering.statics.get(reference).cloned())
+                    .expect("the checker only accepts statics that can be evaluated");
+                lowering.statics.insert(name.clone(), coerce(constant, *var_type));
+            }
+            AstNode::VarDeclaration { name, var_type, .. } => {
+                let id = GlobalId(module.globals.len() as u32);
+                lowering.globals.insert(name.clone(), (id, *var_type));
+                module.globals.push(Global {
+                    name: name.clone(),
+                    var_type: *var_type,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    for node in body {
+        if let AstNode::FuncDeclaration { name, body, .. } = node {
+            let (id, _) = lowering.functions[name];
+            let function = &mut module.functions[id.0 as usize];
+
+            let mut locals = Locals {
+                values: function.params.iter().cloned().collect(),
+                statics: HashMap::new(),
+            };
+            if let AstNode::Block { body } = body.as_ref() {
+                // the statements after a `return` never run, and the function returns at the
+                // end of its only block anyway
+                for node in body.iter().take_while(|node| !matches!(node, AstNode::Return { .. })) {
+                    lowering.lower_statement(function, &mut locals, node);
+                }
+            }
+        }
+    }
+
+    let mut init = Function::new(INIT, Type::Void);
+    let mut locals = Locals::default();
+    for node in body {
+        match node {
+            AstNode::VarDeclaration { is_static: true, .. } => {}
+            AstNode::VarDeclaration { name, var_type, value, .. } => {
+                let (global, _) = lowering.globals[name];
+                let value = lowering.lower_expression(&mut init, &locals, value, *var_type);
+                lowering.push(&mut init, InstKind::Store(global, value));
+            }
+            AstNode::FuncDeclaration { .. } => {}
+            _ => lowering.lower_statement(&mut init, &mut locals, node),
+        }
+    }
+
+    module.init = FuncId(module.functions.len() as u32);
+    module.functions.push(init);
+
+    module
+}
+
+/// The variables declared in the function that is being lowered.
+#[derive(Default)]
+struct Locals {
+    values: HashMap<String, Value>,
+    /// Values of the static variables declared in the function
+    statics: HashMap<String, TypedValue>,
+}
+
+struct Lowering {
+    globals: HashMap<String, (GlobalId, Type)>,
+    /// Values of the static variables declared at the top level
+    statics: HashMap<String, TypedValue>,
+    /// Ids and parameter types of every function declared in the document
+    functions: HashMap<String, (FuncId, Vec<Type>)>,
+}
+
+impl Lowering {
+    fn lower_statement(
+        &self,
+        function: &mut Function,
+        locals: &mut Locals,
+        node: &AstNode,
+    ) {
+        match node {
+            AstNode::VarDeclaration { name, var_type, is_static: true, value, .. } => {
+                let constant = self.evaluate(locals, value)
+                    .expect("the checker only accepts statics that can be evaluated");
+                locals.values.remove(name);
+                locals.statics.insert(name.clone(), coerce(constant, *var_type));
+            }
+            AstNode::VarDeclaration { name, var_type, value, .. } => {
+                let value = self.lower_binding(function, locals, value, *var_type);
+                locals.statics.remove(name);
+                locals.values.insert(name.clone(), value);
+            }
+            AstNode::VarAssignment { name, new_value, .. } => {
+                if let Some(current) = locals.values.get(name) {
+                    // assignments to locals simply rebind the name to a new SSA value
+                    let var_type = function.value_type(*current);
+                    let value = self.lower_binding(function, locals, new_value, var_type);
+                    locals.values.insert(name.clone(), value);
+                } else {
+                    let (global, var_type) = self.globals[name];
+                    let value = self.lower_expression(function, locals, new_value, var_type);
+                    self.push(function, InstKind::Store(global, value));
+                }
+            }
+            AstNode::FunctionCall { name, param_list, .. } if name == PRINT => {
+                let arg = &param_list[0];
+                let arg_type = self.expression_type(function, locals, arg);
+                let value = self.lower_expression(function, locals, arg, arg_type);
+                self.push(function, InstKind::Print(value));
+            }
+            AstNode::FunctionCall { name, param_list, .. } => {
+                let (callee, param_types) = &self.functions[name];
+
+                let args = param_list.iter()
+                    .zip(param_types)
+                    .map(|(arg, param_type)| self.lower_expression(function, locals, arg, *param_type))
+                    .collect();
+
+                self.push(function, InstKind::Call(*callee, args));
+            }
+            _ => {}
+        }
+    }
+
+    /// Lowers the value a local variable is bound to, copying other locals so every variable
+    /// is defined by its own instruction.
+    fn lower_binding(
+        &self,
+        function: &mut Function,
+        locals: &Locals,
+        value: &AstNode,
+        var_type: Type,
+    ) -> Value {
+        let lowered = self.lower_expression(function, locals, value, var_type);
+
+        if let AstNode::Value { value: TypedValue::ReferenceVal(reference), .. } = value {
+            if locals.values.get(reference) == Some(&lowered) {
+                return self.define(function, InstKind::Copy(lowered), var_type)
+            }
+        }
+
+        lowered
+    }
+
+    /// Lowers an expression, converting its result to the `expected` type.
+    fn lower_expression(
+        &self,
+        function: &mut Function,
+        locals: &Locals,
+        node: &AstNode,
+        expected: Type,
+    ) -> Value {
+        if let Some(constant) = self.evaluate(locals, node) {
+            return self.lower_value(function, locals, &coerce(constant, expected), expected)
+        }
+
+        let found = self.expression_type(function, locals, node);
+        let lowered = match node {
+            AstNode::Value { value, .. } => return self.lower_value(function, locals, value, expected),
+            AstNode::UnaryOp { op, operand, .. } => {
+                let operand = self.lower_expression(function, locals, operand, found);
+                self.define(function, InstKind::Unary(*op, operand), found)
+            }
+            AstNode::BinaryOp { op, lhs, rhs, span } => {
+                let lhs_type = self.expression_type(function, locals, lhs);
+                let rhs_type = self.expression_type(function, locals, rhs);
+                let (operand_type, _) = binary_types(*op, lhs_type, rhs_type)
+                    .expect("the checker only accepts well typed operands");
+
+                let lhs = self.lower_expression(function, locals, lhs, operand_type);
+                let rhs = self.lower_expression(function, locals, rhs, operand_type);
+
+                // integer division fails on a zero divisor, or when its result overflows
+                let result = function.new_value(found);
+                let span = (*op == BinaryOperator::Div && operand_type == Type::Int).then_some(*span);
+                self.append(function, Inst { result: Some(result), kind: InstKind::Binary(*op, lhs, rhs), span });
+                result
+            }
+            _ => unreachable!("only expressions produce values"),
+        };
+
+        if expected == Type::Float && found == Type::Int {
+            return self.define(function, InstKind::IntToFloat(lowered), Type::Float)
+        }
+
+        lowered
+    }
+
+    /// Evaluates an expression at compile time, returning `None` if it reads a value that is
+    /// only known at run time or if its result is undefined.
+    fn evaluate(&self, locals: &Locals, node: &AstNode) -> Option<TypedValue> {
+        evaluate(node, &|reference| {
+            if locals.values.contains_key(reference) {
+                return None
+            }
+
+            locals.statics.get(reference)
+                .or_else(|| self.statics.get(reference))
+                .cloned()
+        }).ok()
+    }
+
+    /// Lowers a value, converting it to the `expected` type.
+    fn lower_value(
+        &self,
+        function: &mut Function,
+        locals: &Locals,
+        value: &TypedValue,
+        expected: Type,
+    ) -> Value {
+        let (lowered, found) = match value {
+            TypedValue::ReferenceVal(reference) => match locals.values.get(reference) {
+                Some(local) => (*local, function.value_type(*local)),
+                None => {
+                    let (global, var_type) = self.globals[reference];
+                    (self.define(function, InstKind::Load(global), var_type), var_type)
+                }
+            },
+            TypedValue::StringVal(lit) => {
+                let constant = InstKind::Const(Constant::String(lit.clone()));
+                (self.define(function, constant, Type::String), Type::String)
+            }
+            TypedValue::IntVal(lit) => {
+                let constant = InstKind::Const(Constant::Int(*lit));
+                (self.define(function, constant, Type::Int), Type::Int)
+            }
+            TypedValue::FloatVal(lit) => {
+                let constant = InstKind::Const(Constant::Float(*lit));
+                (self.define(function, constant, Type::Float), Type::Float)
+            }
+            TypedValue::BoolVal(lit) => {
+                let constant = InstKind::Const(Constant::Bool(*lit));
+                (self.define(function, constant, Type::Boolean), Type::Boolean)
+            }
+            TypedValue::NullVal => {
+                // null takes the type of the slot it is stored in
+                let null_type = if expected == Type::String { Type::String } else { Type::Null };
+                (self.define(function, InstKind::Const(Constant::Null), null_type), null_type)
+            }
+        };
+
+        if expected == Type::Float && found == Type::Int {
+            return self.define(function, InstKind::IntToFloat(lowered), Type::Float)
+        }
+
+        lowered
+    }
+
+    /// Returns the type of the value an expression produces.
+    fn expression_type(&self, function: &Function, locals: &Locals, node: &AstNode) -> Type {
+        match node {
+            AstNode::Value { value, .. } => self.value_type(function, locals, value),
+            AstNode::UnaryOp { op, operand, .. } => {
+                let operand = self.expression_type(function, locals, operand);
+                unary_type(*op, operand).expect("the checker only accepts well typed operands")
+            }
+            AstNode::BinaryOp { op, lhs, rhs, .. } => {
+                let lhs = self.expression_type(function, locals, lhs);
+                let rhs = self.expression_type(function, locals, rhs);
+                binary_types(*op, lhs, rhs)
+                    .expect("the checker only accepts well typed operands")
+                    .1
+            }
+            _ => unreachable!("only expressions produce values"),
+        }
+    }
+
+    fn value_type(&self, function: &Function, locals: &Locals, value: &TypedValue) -> Type {
+        match value {
+            TypedValue::ReferenceVal(reference) => {
+                if let Some(local) = locals.values.get(reference) {
+                    return function.value_type(*local)
+                }
+
+                match locals.statics.get(reference).or_else(|| self.statics.get(reference)) {
+                    Some(constant) => literal_type(constant).expect("statics are never references"),
+                    None => self.globals[reference].1,
+                }
+            }
+            _ => literal_type(value).expect("literals always have a type"),
+        }
+    }
+
+    /// Appends an instruction that defines a new value of the given type.
+    fn define(&self, function: &mut Function, kind: InstKind, result_type: Type) -> Value {
+        let result = function.new_value(result_type);
+        self.append(function, Inst { result: Some(result), kind, span: None });
+        result
+    }
+
+    /// Appends an instruction that is only executed for its side effects.
+    fn push(&self, function: &mut Function, kind: InstKind) {
+        self.append(function, Inst { result: None, kind, span: None });
+    }
+
+    fn append(&self, function: &mut Function, inst: Inst) {
+        function.blocks.last_mut()
+            .expect("functions always have an entry block")
+            .insts
+            .push(inst);
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use ast::constant;
+use ast::TypedValue;
+use crate::{Constant, InstKind, Module, Value};
+use super::Pass;
+
+/// Evaluates instructions whose operands are all constants at compile time, replacing them
+/// with the constant they produce.
+pub struct ConstantFolding;
+
+impl Pass for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "constant-folding"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = false;
+
+        for function in &mut module.functions {
+            let mut constants: HashMap<Value, Constant> = HashMap::new();
+
+            for block in &mut function.blocks {
+                for inst in &mut block.insts {
+                    if let Some(folded) = fold(&inst.kind, &constants) {
+                        inst.kind = InstKind::Const(folded);
+                        changed = true;
+                    }
+
+                    if let (Some(result), InstKind::Const(constant)) = (inst.result, &inst.kind) {
+                        constants.insert(result, constant.clone());
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+/// Returns the constant an instruction evaluates to, if it can be known at compile time.
+fn fold(kind: &InstKind, constants: &HashMap<Value, Constant>) -> Option<Constant> {
+    match kind {
+        InstKind::IntToFloat(value) => match constants.get(value)? {
+            Constant::Int(value) => Some(Constant::Float(*value as f32)),
+            _ => None,
+        },
+        InstKind::Copy(value) => constants.get(value).cloned(),
+        InstKind::Binary(op, lhs, rhs) => {
+            let lhs = to_typed_value(constants.get(lhs)?);
+            let rhs = to_typed_value(constants.get(rhs)?);
+            constant::binary(*op, &lhs, &rhs).map(from_typed_value)
+        }
+        InstKind::Unary(op, value) => {
+            let value = to_typed_value(constants.get(value)?);
+            constant::unary(*op, &value).map(from_typed_value)
+        }
+        _ => None,
+    }
+}
+
+fn to_typed_value(constant: &Constant) -> TypedValue {
+    match constant {
+        Constant::Int(value) => TypedValue::IntVal(*value),
+        Constant::Float(value) => TypedValue::FloatVal(*value),
+        Constant::Bool(value) => TypedValue::BoolVal(*value),
+        Constant::String(value) => TypedValue::StringVal(value.clone()),
+        Constant::Null => TypedValue::NullVal,
+    }
+}
+
+fn from_typed_value(value: TypedValue) -> Constant {
+    match value {
+        TypedValue::IntVal(value) => Constant::Int(value),
+        TypedValue::FloatVal(value) => Constant::Float(value),
+        TypedValue::BoolVal(value) => Constant::Bool(value),
+        TypedValue::StringVal(value) => Constant::String(value),
+        TypedValue::NullVal => Constant::Null,
+        TypedValue::ReferenceVal(_) => unreachable!("operators never produce references"),
+    }
+}
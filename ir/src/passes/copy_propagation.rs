@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+use crate::{InstKind, Module};
+use super::{replace_uses, Pass};
+
+/// Replaces every use of a copied value with the original value and removes the copies.
+pub struct CopyPropagation;
+
+impl Pass for CopyPropagation {
+    fn name(&self) -> &'static str {
+        "copy-propagation"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = false;
+
+        for function in &mut module.functions {
+            let mut copies = HashMap::new();
+
+            for block in &mut function.blocks {
+                block.insts.retain(|inst| match (inst.result, &inst.kind) {
+                    (Some(result), InstKind::Copy(source)) => {
+                        copies.insert(result, *source);
+                        false
+                    }
+                    _ => true,
+                });
+            }
+
+            if !copies.is_empty() {
+                replace_uses(function, &copies);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use crate::{InstKind, Module, Value};
+use super::{replace_uses, Pass};
+
+/// Reuses the result of an earlier, identical instruction within the same block instead of
+/// computing it again.
+pub struct CommonSubexpressionElimination;
+
+impl Pass for CommonSubexpressionElimination {
+    fn name(&self) -> &'static str {
+        "common-subexpression-elimination"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = false;
+
+        for function in &mut module.functions {
+            let mut replacements: HashMap<Value, Value> = HashMap::new();
+            let value_types = &function.value_types;
+
+            for block in &mut function.blocks {
+                // instructions whose results are still valid at the current point of the block
+                let mut available: Vec<(InstKind, Value)> = Vec::new();
+
+                block.insts.retain_mut(|inst| {
+                    for operand in inst.kind.operands_mut() {
+                        if let Some(replacement) = replacements.get(operand) {
+                            *operand = *replacement;
+                        }
+                    }
+
+                    match &inst.kind {
+                        // calls may write any global
+                        InstKind::Call(..) => {
+                            available.retain(|(kind, _)| !matches!(kind, InstKind::Load(_)));
+                        }
+                        InstKind::Store(global, _) => {
+                            available.retain(|(kind, _)| kind != &InstKind::Load(*global));
+                        }
+                        _ => {}
+                    }
+
+                    let Some(result) = inst.result else {
+                        return true
+                    };
+
+                    if inst.kind.has_side_effects() {
+                        return true
+                    }
+
+                    let existing = available.iter()
+                        .find(|(kind, value)| {
+                            kind == &inst.kind && value_types[value.0 as usize] == value_types[result.0 as usize]
+                        });
+
+                    if let Some((_, existing)) = existing {
+                        replacements.insert(result, *existing);
+                        return false
+                    }
+
+                    available.push((inst.kind.clone(), result));
+                    true
+                });
+            }
+
+            if !replacements.is_empty() {
+                replace_uses(function, &replacements);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
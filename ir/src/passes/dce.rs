@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+use crate::Module;
+use super::Pass;
+
+/// Removes instructions without side effects whose results are never used.
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn name(&self) -> &'static str {
+        "dead-code-elimination"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = false;
+
+        for function in &mut module.functions {
+            // removing an instruction can make its operands dead as well
+            loop {
+                let used: HashSet<_> = function.blocks.iter()
+                    .flat_map(|block| {
+                        block.insts.iter()
+                            .flat_map(|inst| inst.kind.operands())
+                            .chain(block.terminator.operands())
+                    })
+                    .collect();
+
+                let before = function.inst_count();
+                for block in &mut function.blocks {
+                    block.insts.retain(|inst| {
+                        inst.kind.has_side_effects()
+                            || inst.result.is_some_and(|result| used.contains(&result))
+                    });
+                }
+
+                if function.inst_count() == before {
+                    break;
+                }
+
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
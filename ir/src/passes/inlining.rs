@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use crate::{FuncId, Inst, InstKind, Module, Terminator, Value};
+use super::{replace_uses, Pass};
+
+/// Replaces calls to small leaf functions with a copy of the function's body.
+///
+/// Only functions consisting of a single block that do not call other functions are inlined,
+/// which also rules out recursion.
+pub struct Inlining {
+    /// The maximum number of instructions a function may have to be inlined
+    pub max_insts: usize,
+}
+
+impl Default for Inlining {
+    fn default() -> Self {
+        Inlining { max_insts: 32 }
+    }
+}
+
+impl Pass for Inlining {
+    fn name(&self) -> &'static str {
+        "inlining"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let inlinable: HashMap<FuncId, _> = module.functions.iter()
+            .enumerate()
+            .filter(|(_, function)| {
+                function.blocks.len() == 1
+                    && function.inst_count() <= self.max_insts
+                    && function.blocks[0].insts.iter().all(|inst| !matches!(inst.kind, InstKind::Call(..)))
+            })
+            .map(|(index, function)| (FuncId(index as u32), function.clone()))
+            .collect();
+
+        let mut changed = false;
+
+        for function in &mut module.functions {
+            let mut results = HashMap::new();
+
+            for block_index in 0..function.blocks.len() {
+                let insts = std::mem::take(&mut function.blocks[block_index].insts);
+                let mut inlined = Vec::with_capacity(insts.len());
+
+                for inst in insts {
+                    let callee = match &inst.kind {
+                        InstKind::Call(callee, _) => inlinable.get(callee),
+                        _ => None,
+                    };
+
+                    let (Some(callee), InstKind::Call(_, args)) = (callee, &inst.kind) else {
+                        inlined.push(inst);
+                        continue;
+                    };
+
+                    // every value of the callee gets a fresh value in the caller, except for
+                    // the parameters, which become the arguments of the call
+                    let mut values: HashMap<Value, Value> = callee.params.iter()
+                        .map(|(_, param)| *param)
+                        .zip(args.iter().copied())
+                        .collect();
+
+                    for (index, value_type) in callee.value_types.iter().enumerate() {
+                        values.entry(Value(index as u32))
+                            .or_insert_with(|| function.new_value(*value_type));
+                    }
+
+                    for callee_inst in &callee.blocks[0].insts {
+                        let mut kind = callee_inst.kind.clone();
+                        for operand in kind.operands_mut() {
+                            *operand = values[operand];
+                        }
+
+                        inlined.push(Inst {
+                            result: callee_inst.result.map(|result| values[&result]),
+                            kind,
+                            span: callee_inst.span,
+                        });
+                    }
+
+                    if let (Some(result), Terminator::Return(Some(returned))) = (inst.result, &callee.blocks[0].terminator) {
+                        results.insert(result, values[returned]);
+                    }
+
+                    changed = true;
+                }
+
+                function.blocks[block_index].insts = inlined;
+            }
+
+            if !results.is_empty() {
+                replace_uses(function, &results);
+            }
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,94 @@
+mod constant_folding;
+mod copy_propagation;
+mod cse;
+mod dce;
+mod inlining;
+
+pub use constant_folding::ConstantFolding;
+pub use copy_propagation::CopyPropagation;
+pub use cse::CommonSubexpressionElimination;
+pub use dce::DeadCodeElimination;
+pub use inlining::Inlining;
+
+use std::collections::HashMap;
+use crate::{Function, Module, Value};
+
+/// The highest supported optimization level.
+pub const MAX_OPT_LEVEL: u8 = 3;
+
+/// A transformation over a whole module that preserves the behavior of the program.
+pub trait Pass {
+    /// Returns the name of the pass, as shown in IR dumps.
+    fn name(&self) -> &'static str;
+
+    /// Runs the pass, returning whether the module was changed.
+    fn run(&self, module: &mut Module) -> bool;
+}
+
+/// Returns the passes that are run at the given optimization level.
+///
+/// * `0` - No optimizations.
+/// * `1` - Constant folding, copy propagation and dead code elimination.
+/// * `2` - Additionally, common subexpression elimination.
+/// * `3` - Additionally, inlining of small functions, which runs first so that the other passes
+///   clean up the inlined code.
+pub fn pipeline(opt_level: u8) -> Vec<Box<dyn Pass>> {
+    let mut passes: Vec<Box<dyn Pass>> = Vec::new();
+
+    if opt_level >= 3 {
+        passes.push(Box::new(Inlining::default()));
+    }
+
+    if opt_level >= 1 {
+        passes.push(Box::new(ConstantFolding));
+        passes.push(Box::new(CopyPropagation));
+    }
+
+    if opt_level >= 2 {
+        passes.push(Box::new(CommonSubexpressionElimination));
+    }
+
+    if opt_level >= 1 {
+        passes.push(Box::new(DeadCodeElimination));
+    }
+
+    passes
+}
+
+/// Optimizes a module at the given level.
+///
+/// # Arguments
+///
+/// * `module` - The module to optimize in place.
+/// * `opt_level` - The optimization level, from `0` to `MAX_OPT_LEVEL`.
+/// * `observer` - Called before and after every pass with the pass name, whether the pass
+///   has already run, and the module at that point. Used to dump the IR.
+pub fn optimize(module: &mut Module, opt_level: u8, mut observer: impl FnMut(&str, bool, &Module)) {
+    for pass in pipeline(opt_level) {
+        observer(pass.name(), false, module);
+        pass.run(module);
+        observer(pass.name(), true, module);
+    }
+}
+
+/// Replaces every use of a value inside a function according to the given map.
+pub(crate) fn replace_uses(function: &mut Function, replacements: &HashMap<Value, Value>) {
+    let resolve = |mut value: Value| {
+        while let Some(replacement) = replacements.get(&value) {
+            value = *replacement;
+        }
+        value
+    };
+
+    for block in &mut function.blocks {
+        for inst in &mut block.insts {
+            for operand in inst.kind.operands_mut() {
+                *operand = resolve(*operand);
+            }
+        }
+
+        for operand in block.terminator.operands_mut() {
+            *operand = resolve(*operand);
+        }
+    }
+}
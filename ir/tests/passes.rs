@@ -0,0 +1,179 @@
+use ast::lexer::Lexer;
+use ast::parser::Parser;
+use ir::Module;
+use ir::passes::{pipeline, CommonSubexpressionElimination, ConstantFolding, CopyPropagation, DeadCodeElimination, Inlining, Pass};
+
+fn lower(source: &str) -> Module {
+    let tokens = Lexer::new("test.zpp", source).tokenize();
+    ir::lower::lower(&Parser::new(tokens).parse())
+}
+
+/// Returns the text form of a function of a module, without its signature.
+fn body(module: &Module, name: &str) -> String {
+    let text = module.to_string();
+    let start = text.find(&format!("func @{}(", name)).expect("the function exists");
+    let start = start + text[start..].find('\n').unwrap() + 1;
+    let end = start + text[start..].find("}\n").unwrap();
+
+    text[start..end].to_string()
+}
+
+/// Runs a pass over the lowered source, checking the body of a function before and after it.
+///
+/// # Arguments
+///
+/// * `pass` - The pass to run.
+/// * `source` - The program that is lowered.
+/// * `name` - The name of the function whose body is checked.
+/// * `before` - The body the function is lowered to.
+/// * `after` - The body of the function after the pass, or `None` if the pass changes nothing.
+fn check(pass: &dyn Pass, source: &str, name: &str, before: &str, after: Option<&str>) {
+    let mut module = lower(source);
+    assert_eq!(body(&module, name), before, "before {}", pass.name());
+
+    let changed = pass.run(&mut module);
+    assert_eq!(changed, after.is_some(), "{} reported the wrong change", pass.name());
+    assert_eq!(body(&module, name), after.unwrap_or(before), "after {}", pass.name());
+}
+
+#[test]
+fn constant_folding_evaluates_constant_operands() {
+    let source = "func f() {\n    int a = 6\n    int b = a * 7\n    print(-b)\n    print(b / 0)\n}\n";
+
+    check(&ConstantFolding, source, "f", "  bb0:
+    %0: int = const 6
+    %1: int = const 7
+    %2: int = mul %0, %1
+    %3: int = neg %2
+    print %3
+    %4: int = const 0
+    %5: int = div %2, %4
+    print %5
+    return
+", Some("  bb0:
+    %0: int = const 6
+    %1: int = const 7
+    %2: int = const 42
+    %3: int = const -42
+    print %3
+    %4: int = const 0
+    %5: int = div %2, %4
+    print %5
+    return
+"));
+}
+
+#[test]
+fn copy_propagation_uses_the_original_values() {
+    let source = "func f(int a) {\n    int b = a\n    int c = b\n    print(c + b)\n}\n";
+
+    check(&CopyPropagation, source, "f", "  bb0:
+    %1: int = copy %0
+    %2: int = copy %1
+    %3: int = add %2, %1
+    print %3
+    return
+", Some("  bb0:
+    %3: int = add %0, %0
+    print %3
+    return
+"));
+}
+
+#[test]
+fn common_subexpressions_are_computed_once() {
+    let source = "mut int g = 1\nfunc f(int a) {\n    print(a * 2 + a * 2)\n    print(g + g)\n    g = 2\n    print(g)\n}\n";
+
+    check(&CommonSubexpressionElimination, source, "f", "  bb0:
+    %1: int = const 2
+    %2: int = mul %0, %1
+    %3: int = const 2
+    %4: int = mul %0, %3
+    %5: int = add %2, %4
+    print %5
+    %6: int = load @g
+    %7: int = load @g
+    %8: int = add %6, %7
+    print %8
+    %9: int = const 2
+    store @g, %9
+    %10: int = load @g
+    print %10
+    return
+", Some("  bb0:
+    %1: int = const 2
+    %2: int = mul %0, %1
+    %5: int = add %2, %2
+    print %5
+    %6: int = load @g
+    %8: int = add %6, %6
+    print %8
+    store @g, %1
+    %10: int = load @g
+    print %10
+    return
+"));
+}
+
+#[test]
+fn dead_code_elimination_keeps_side_effects() {
+    let source = "mut int g = 1\nfunc f(int a) {\n    int b = a + 1\n    int c = b * 2\n    g = a\n    print(a)\n}\n";
+
+    check(&DeadCodeElimination, source, "f", "  bb0:
+    %1: int = const 1
+    %2: int = add %0, %1
+    %3: int = const 2
+    %4: int = mul %2, %3
+    store @g, %0
+    print %0
+    return
+", Some("  bb0:
+    store @g, %0
+    print %0
+    return
+"));
+
+    let unchanged = "func f(int a) {\n    print(a)\n}\n";
+    check(&DeadCodeElimination, unchanged, "f", "  bb0:
+    print %0
+    return
+", None);
+}
+
+#[test]
+fn small_leaf_functions_are_inlined() {
+    let source = "func double(int n) {\n    print(n * 2)\n}\nfunc twice(int n) {\n    double(n)\n    double(n)\n}\nfunc f() {\n    twice(3)\n    double(4)\n}\n";
+
+    check(&Inlining::default(), source, "f", "  bb0:
+    %0: int = const 3
+    call @twice(%0)
+    %1: int = const 4
+    call @double(%1)
+    return
+", Some("  bb0:
+    %0: int = const 3
+    call @twice(%0)
+    %1: int = const 4
+    %2: int = const 2
+    %3: int = mul %1, %2
+    print %3
+    return
+"));
+
+    // functions that call others are not inlined, but the calls in them are
+    let mut module = lower(source);
+    Inlining::default().run(&mut module);
+    assert!(!body(&module, "twice").contains("call"));
+    assert!(body(&module, "f").contains("call @twice"));
+}
+
+#[test]
+fn every_level_adds_passes() {
+    let names = |opt_level| pipeline(opt_level).iter().map(|pass| pass.name()).collect::<Vec<_>>();
+
+    assert!(names(0).is_empty());
+    assert_eq!(names(1), ["constant-folding", "copy-propagation", "dead-code-elimination"]);
+    assert_eq!(names(2), ["constant-folding", "copy-propagation", "common-subexpression-elimination", "dead-code-elimination"]);
+    assert_eq!(names(3)[0], "inlining");
+    assert_eq!(names(3)[1..], names(2));
+}
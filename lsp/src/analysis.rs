@@ -0,0 +1,49 @@
+use std::rc::Rc;
+use ast::checker::CheckError;
+use ast::resolver::Resolution;
+use database::{Database, Parse};
+
+/// What the front end found out about a document.
+pub struct Analysis {
+    /// The tokens and AST of the document, leaving out any tokens that could not be lexed
+    pub parse: Rc<Parse>,
+    /// Every diagnostic reported by the lexer, parser and checker, together with the
+    /// suggestions that fix it
+    pub check: Rc<Vec<CheckError>>,
+    /// The findings of the lints, together with the fixes of the lints that have one
+    pub lint: Rc<Vec<CheckError>>,
+    /// The names of the parts of the document that could be parsed
+    pub resolution: Rc<Resolution>,
+}
+
+impl Analysis {
+    /// Returns the diagnostics of the checker followed by those of the lints.
+    pub fn diagnostics(&self) -> impl Iterator<Item = &CheckError> {
+        self.check.iter().chain(self.lint.iter())
+    }
+}
+
+/// Resolves, checks and lints a document through the database that `zxx check` and
+/// `zxx lint` use, which only recomputes what the last change affected.
+///
+/// Like `zxx`, the checker only runs when the document could be lexed and parsed, so that
+/// names whose declaration has a syntax error are not reported as missing, and the lints only
+/// run when the checker found no errors. Names are resolved regardless, so navigation keeps
+/// working while the document is being edited.
+///
+/// # Arguments
+///
+/// * `db` - The database the document was added to.
+/// * `name` - The name of the document in the database, which diagnostics refer to it by.
+///
+/// # Returns
+///
+/// Returns the `Analysis` of the document.
+pub fn analyze(db: &Database, name: &str) -> Analysis {
+    Analysis {
+        parse: db.parse(name),
+        check: db.check(name),
+        lint: db.lint(name),
+        resolution: db.resolution(name),
+    }
+}
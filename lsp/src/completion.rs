@@ -0,0 +1,147 @@
+use ast::checker::PRINT;
+use ast::lexer::{Token, TokenType, KEYWORDS};
+use ast::resolver::{DeclarationKind, Resolution};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind, ParameterInformation,
+    ParameterLabel, SignatureHelp, SignatureInformation,
+};
+use crate::navigation::documentation;
+
+/// How the built-in `print` function is shown, since it has no declaration.
+const PRINT_SIGNATURE: &str = "func print(value)";
+const PRINT_DOC: &str = "Prints a single value of any type, followed by a newline.";
+
+/// Returns the completions for the name that is being typed at a byte offset.
+///
+/// The candidates are the keywords, `print`, and every function and variable that is visible
+/// at the offset. The client filters them by what has been typed so far. Nothing is offered
+/// after a `.`, since Z++ has no standard library or other values with members yet.
+///
+/// # Arguments
+///
+/// * `text` - The contents of the document.
+/// * `resolution` - The resolution of the document, which may be incomplete.
+/// * `offset` - The byte offset of the cursor.
+///
+/// # Returns
+///
+/// Returns the completion items.
+pub fn completions(text: &str, resolution: &Resolution, offset: usize) -> Vec<CompletionItem> {
+    let before = text.get(..offset).unwrap_or(text);
+    let prefix = before.trim_end_matches(|char: char| char.is_ascii_alphanumeric() || char == '_');
+
+    // only members could follow a `.`, and no value has any
+    if prefix.ends_with('.') {
+        return Vec::new()
+    }
+
+    let keywords = KEYWORDS.iter().map(|keyword| CompletionItem {
+        label: keyword.to_string(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        ..Default::default()
+    });
+
+    let print = CompletionItem {
+        label: PRINT.to_string(),
+        kind: Some(CompletionItemKind::FUNCTION),
+        detail: Some(PRINT_SIGNATURE.to_string()),
+        documentation: Some(markdown(PRINT_DOC.to_string())),
+        ..Default::default()
+    };
+
+    let declarations = resolution.visible_at(offset).into_iter().map(|id| {
+        let declaration = &resolution.declarations[id];
+        let kind = match declaration.kind {
+            DeclarationKind::Function => CompletionItemKind::FUNCTION,
+            DeclarationKind::Variable if declaration.is_static => CompletionItemKind::CONSTANT,
+            DeclarationKind::Parameter | DeclarationKind::Variable => CompletionItemKind::VARIABLE,
+        };
+
+        CompletionItem {
+            label: declaration.name.clone(),
+            kind: Some(kind),
+            detail: Some(declaration.signature()),
+            documentation: documentation(text, declaration).map(markdown),
+            ..Default::default()
+        }
+    });
+
+    keywords.chain(std::iter::once(print)).chain(declarations).collect()
+}
+
+/// Describes the call whose parentheses contain a byte offset, highlighting the parameter the
+/// argument at the offset is passed to.
+///
+/// The call is found from the tokens before the offset alone, so it does not need to be closed
+/// or even parse.
+///
+/// # Arguments
+///
+/// * `text` - The contents of the document.
+/// * `tokens` - The tokens of the document.
+/// * `resolution` - The resolution of the document, which may be incomplete.
+/// * `offset` - The byte offset of the cursor.
+///
+/// # Returns
+///
+/// Returns the signature of the called function, or `None` if the offset is not within the
+/// arguments of a call to a known function.
+pub fn signature_help(text: &str, tokens: &[Token], resolution: &Resolution, offset: usize) -> Option<SignatureHelp> {
+    let before = &tokens[..tokens.partition_point(|token| token.span.end().to_usize() <= offset)];
+
+    // walk back to the unclosed `(` of the call, counting the arguments before the offset
+    let mut depth: usize = 0;
+    let mut argument: u32 = 0;
+    let mut open = None;
+    for (index, token) in before.iter().enumerate().rev() {
+        match token.token_type {
+            TokenType::RParen => depth += 1,
+            TokenType::LParen if depth == 0 => {
+                open = Some(index);
+                break
+            }
+            TokenType::LParen => depth -= 1,
+            TokenType::Comma if depth == 0 => argument += 1,
+            // calls never span statements, which blocks delimit
+            TokenType::LBrace | TokenType::RBrace => return None,
+            _ => {}
+        }
+    }
+
+    let name = match &before.get(open?.checked_sub(1)?)?.token_type {
+        TokenType::Identifier(name) => name,
+        _ => return None,
+    };
+
+    let (label, params, doc) = if name == PRINT {
+        (PRINT_SIGNATURE.to_string(), vec!["value".to_string()], Some(PRINT_DOC.to_string()))
+    } else {
+        let declaration = resolution.declarations.iter()
+            .find(|declaration| declaration.kind == DeclarationKind::Function && declaration.name == *name)?;
+        let params = declaration.params.iter()
+            .map(|(param, param_type)| format!("{} {}", param_type, param))
+            .collect();
+
+        (declaration.signature(), params, documentation(text, declaration))
+    };
+
+    let parameters = params.into_iter()
+        .map(|param| ParameterInformation { label: ParameterLabel::Simple(param), documentation: None })
+        .collect::<Vec<_>>();
+    let active_parameter = argument.min(parameters.len().saturating_sub(1) as u32);
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: doc.map(markdown),
+            parameters: Some(parameters),
+            active_parameter: Some(active_parameter),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
+
+fn markdown(value: String) -> Documentation {
+    Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value })
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use common::files::FileId;
+use lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Position, Range, Uri};
+use crate::line_index::LineIndex;
+
+/// The source every published diagnostic is attributed to.
+pub const SOURCE: &str = "zxx";
+
+/// Converts a diagnostic produced by `Reporting` into an LSP diagnostic.
+///
+/// The primary label determines the range, and the secondary labels with a message become
+/// related information, such as where a name was first defined. Notes are appended to the
+/// message on their own lines, because LSP has no equivalent for them.
+///
+/// # Arguments
+///
+/// * `diagnostic` - The diagnostic to convert.
+/// * `uri` - The URI of the document the diagnostic belongs to.
+/// * `index` - The line index of the document the diagnostic belongs to.
+///
+/// # Returns
+///
+/// Returns the LSP diagnostic.
+pub fn to_lsp_diagnostic(diagnostic: &Diagnostic<FileId>, uri: &Uri, index: &LineIndex) -> lsp_types::Diagnostic {
+    let primary = diagnostic.labels.iter().find(|label| label.style == LabelStyle::Primary);
+    let range = primary.map_or(Range::new(Position::new(0, 0), Position::new(0, 0)), |label| {
+        Range::new(index.position(label.range.start), index.position(label.range.end))
+    });
+
+    // only the labels in the document have positions, but since files cannot import each
+    // other, the labels of a diagnostic are all in its document
+    let related: Vec<DiagnosticRelatedInformation> = diagnostic.labels.iter()
+        .filter(|label| label.style == LabelStyle::Secondary && !label.message.is_empty())
+        .filter(|label| primary.is_none_or(|primary| primary.file_id == label.file_id))
+        .map(|label| DiagnosticRelatedInformation {
+            location: Location::new(
+                uri.clone(),
+                Range::new(index.position(label.range.start), index.position(label.range.end)),
+            ),
+            message: label.message.clone(),
+        })
+        .collect();
+
+    let severity = match diagnostic.severity {
+        Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Note => DiagnosticSeverity::INFORMATION,
+        Severity::Help => DiagnosticSeverity::HINT,
+    };
+
+    let mut message = diagnostic.message.clone();
+    for note in &diagnostic.notes {
+        message.push('\n');
+        message.push_str(note);
+    }
+
+    lsp_types::Diagnostic {
+        range,
+        severity: Some(severity),
+        code: diagnostic.code.clone().map(NumberOrString::String),
+        source: Some(SOURCE.to_string()),
+        message,
+        related_information: (!related.is_empty()).then_some(related),
+        ..Default::default()
+    }
+}
+
+/// Returns the path of a `file` URI, or `None` for URIs of other schemes.
+pub fn to_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme().is_none_or(|scheme| scheme.as_str() != "file") {
+        return None
+    }
+
+    Some(PathBuf::from(uri.path().as_estr().decode().into_string_lossy().into_owned()))
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use ast::lexer::{Token, TokenType};
+use ast::resolver::{DeclarationKind, Resolution};
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+use crate::line_index::LineIndex;
+
+/// The token types the server highlights, in the order of their indices.
+const TOKEN_TYPES: [SemanticTokenType; 8] = [
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::TYPE,
+];
+
+const KEYWORD: u32 = 0;
+const NUMBER: u32 = 1;
+const STRING: u32 = 2;
+const OPERATOR: u32 = 3;
+const FUNCTION: u32 = 4;
+const PARAMETER: u32 = 5;
+const VARIABLE: u32 = 6;
+const TYPE: u32 = 7;
+
+/// The bits of the token modifiers, in the order of the legend.
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+const STATIC: u32 = 1 << 2;
+/// Variables and parameters that can be assigned to
+const MUTABLE: u32 = 1 << 3;
+
+/// Returns the legend the client decodes semantic tokens with.
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::READONLY,
+            SemanticTokenModifier::STATIC,
+            SemanticTokenModifier::new("mutable"),
+        ],
+    }
+}
+
+/// Classifies the tokens of a document for semantic highlighting.
+///
+/// Keywords, literals and operators are classified by their `TokenType`. Names are classified
+/// by the declaration they resolve to, and the names of types by preceding a declared name.
+/// Names that cannot be resolved are left to the client's grammar.
+///
+/// # Arguments
+///
+/// * `text` - The contents of the document.
+/// * `tokens` - The tokens of the document.
+/// * `resolution` - The resolution of the document.
+///
+/// # Returns
+///
+/// Returns the semantic tokens, encoded relative to each other as LSP requires.
+pub fn semantic_tokens(text: &str, tokens: &[Token], resolution: &Resolution) -> Vec<SemanticToken> {
+    // the declaration every name resolves to, keyed by the name's start, and whether the name
+    // is the one in the declaration
+    let mut names = HashMap::new();
+    for (id, declaration) in resolution.declarations.iter().enumerate() {
+        names.insert(declaration.span.start(), (id, true));
+    }
+    for reference in &resolution.references {
+        names.insert(reference.span.start(), (reference.declaration, false));
+    }
+
+    let index = LineIndex::new(text);
+    let mut encoded = Vec::new();
+    let (mut previous_line, mut previous_start) = (0, 0);
+
+    for (position, token) in tokens.iter().enumerate() {
+        let classified = match &token.token_type {
+            TokenType::Func
+            | TokenType::Return
+            | TokenType::If
+            | TokenType::Else
+            | TokenType::For
+            | TokenType::While
+            | TokenType::Static
+            | TokenType::Mut
+            | TokenType::BoolLiteral(_)
+            | TokenType::NullLiteral => Some((KEYWORD, 0)),
+            TokenType::IntLiteral(_) | TokenType::FloatLiteral(_) => Some((NUMBER, 0)),
+            TokenType::StringLiteral(_) => Some((STRING, 0)),
+            TokenType::Identifier(_) => match names.get(&token.span.start()) {
+                Some((id, is_declaration)) => {
+                    let declaration = &resolution.declarations[*id];
+                    let mut modifiers = if *is_declaration { DECLARATION } else { 0 };
+
+                    let token_type = match declaration.kind {
+                        DeclarationKind::Function => FUNCTION,
+                        DeclarationKind::Parameter => PARAMETER,
+                        DeclarationKind::Variable => VARIABLE,
+                    };
+                    if declaration.kind != DeclarationKind::Function {
+                        modifiers |= if declaration.is_static {
+                            READONLY | STATIC
+                        } else if declaration.is_mutable {
+                            MUTABLE
+                        } else {
+                            READONLY
+                        };
+                    }
+
+                    Some((token_type, modifiers))
+                }
+                // the type of a variable or parameter is written in front of its name
+                None => tokens.get(position + 1)
+                    .and_then(|next| names.get(&next.span.start()))
+                    .filter(|(_, is_declaration)| *is_declaration)
+                    .map(|_| (TYPE, 0)),
+            },
+            TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Mul
+            | TokenType::Slash
+            | TokenType::Eq
+            | TokenType::DoubleEq
+            | TokenType::Not
+            | TokenType::NotEq
+            | TokenType::Greater
+            | TokenType::Less
+            | TokenType::GreaterEq
+            | TokenType::LessEq
+            | TokenType::And
+            | TokenType::Or => Some((OPERATOR, 0)),
+            _ => None,
+        };
+
+        let Some((token_type, modifiers)) = classified else {
+            continue
+        };
+
+        // tokens never span lines, so the length is the difference of the columns
+        let start = index.position(token.span.start().to_usize());
+        let end = index.position(token.span.end().to_usize());
+        let delta_start = if start.line == previous_line { start.character - previous_start } else { start.character };
+
+        encoded.push(SemanticToken {
+            delta_line: start.line - previous_line,
+            delta_start,
+            length: end.character - start.character,
+            token_type,
+            token_modifiers_bitset: modifiers,
+        });
+        (previous_line, previous_start) = (start.line, start.character);
+    }
+
+    encoded
+}
@@ -0,0 +1,56 @@
+use codespan::Span;
+use lsp_types::{Position, Range};
+
+/// Converts between the byte offsets spans are made of and LSP positions, whose character
+/// offsets count UTF-16 code units.
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// The byte offset every line starts at
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+
+        LineIndex { text, line_starts }
+    }
+
+    /// Returns the position of a byte offset, which is clamped to the end of the text.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+
+        let character = self.text.get(start..offset)
+            .map_or(0, |prefix| prefix.encode_utf16().count());
+
+        Position::new(line as u32, character as u32)
+    }
+
+    /// Returns the byte offset of a position. Positions past the end of a line are clamped to
+    /// the end of the line, and positions past the last line to the end of the text.
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(start) = self.line_starts.get(position.line as usize).copied() else {
+            return self.text.len()
+        };
+        let line = self.text[start..].split('\n').next().unwrap_or_default();
+
+        let mut units = 0;
+        for (offset, char) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + offset
+            }
+            units += char.len_utf16();
+        }
+
+        start + line.len()
+    }
+
+    /// Returns the range a span covers.
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start().to_usize()), self.position(span.end().to_usize()))
+    }
+}
@@ -0,0 +1,22 @@
+mod analysis;
+mod completion;
+mod convert;
+mod highlight;
+mod line_index;
+mod navigation;
+mod outline;
+mod rename;
+mod server;
+
+use std::error::Error;
+use lsp_server::Connection;
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // stdout carries the protocol, so nothing else may be written to it
+    let (connection, io_threads) = Connection::stdio();
+
+    server::run(connection)?;
+    io_threads.join()?;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use codespan::Span;
+use ast::lexer::doc_comment;
+use ast::resolver::{Declaration, DeclarationKind, Resolution};
+
+/// Returns the span of the name a declaration introduces, for the declaration of the name
+/// at a byte offset.
+pub fn definition(resolution: &Resolution, offset: usize) -> Option<Span> {
+    let id = resolution.declaration_at(offset)?;
+    Some(resolution.declarations[id].span)
+}
+
+/// Returns the spans of every use of the name at a byte offset.
+///
+/// # Arguments
+///
+/// * `resolution` - The resolution of the document.
+/// * `offset` - A byte offset on the name, either where it is declared or where it is used.
+/// * `include_declaration` - Whether the name in the declaration is returned as well.
+///
+/// # Returns
+///
+/// Returns the spans in the order they appear in, or an empty list if there is no name at the
+/// offset.
+pub fn references(resolution: &Resolution, offset: usize, include_declaration: bool) -> Vec<Span> {
+    let Some(id) = resolution.declaration_at(offset) else {
+        return Vec::new()
+    };
+
+    let mut spans: Vec<Span> = resolution.references_to(id)
+        .map(|reference| reference.span)
+        .collect();
+
+    if include_declaration {
+        spans.push(resolution.declarations[id].span);
+    }
+
+    spans.sort_by_key(|span| span.start());
+    spans
+}
+
+/// Describes the name at a byte offset with its signature and doc comment.
+///
+/// # Arguments
+///
+/// * `text` - The contents of the document.
+/// * `resolution` - The resolution of the document.
+/// * `offset` - A byte offset on the name.
+///
+/// # Returns
+///
+/// Returns the description as Markdown, together with the span of the name at the offset.
+pub fn hover(text: &str, resolution: &Resolution, offset: usize) -> Option<(String, Span)> {
+    let id = resolution.declaration_at(offset)?;
+    let declaration = &resolution.declarations[id];
+
+    // the name that was hovered, which is the declaration itself if no use contains the offset
+    let span = resolution.references.iter()
+        .map(|reference| reference.span)
+        .chain(std::iter::once(declaration.span))
+        .find(|span| span.start().to_usize() <= offset && offset <= span.end().to_usize())
+        .unwrap_or(declaration.span);
+
+    let mut contents = format!("```zpp\n{}\n```", declaration.signature());
+    if let Some(doc) = documentation(text, declaration) {
+        contents.push_str("\n\n");
+        contents.push_str(&doc);
+    }
+
+    Some((contents, span))
+}
+
+/// Returns the doc comment of a function or variable.
+pub fn documentation(text: &str, declaration: &Declaration) -> Option<String> {
+    // parameters share their function's first line, so the doc comment above it is not theirs
+    if declaration.kind == DeclarationKind::Parameter {
+        return None
+    }
+
+    doc_comment(text, declaration.node_span.start().to_usize())
+}
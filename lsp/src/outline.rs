@@ -0,0 +1,71 @@
+use ast::lexer::{Token, TokenType};
+use ast::resolver::{DeclarationKind, Resolution};
+use lsp_types::{DocumentSymbol, FoldingRange, FoldingRangeKind, SymbolKind};
+use crate::line_index::LineIndex;
+
+/// Returns the outline of a document, which lists its functions and top-level variables in
+/// the order they are declared in.
+pub fn document_symbols(text: &str, resolution: &Resolution) -> Vec<DocumentSymbol> {
+    let index = LineIndex::new(text);
+
+    let mut declarations: Vec<_> = resolution.declarations.iter()
+        .filter(|declaration| declaration.parent.is_none())
+        .collect();
+    // functions are declared before the statements around them are resolved
+    declarations.sort_by_key(|declaration| declaration.node_span.start());
+
+    declarations.into_iter()
+        .map(|declaration| {
+            let kind = match declaration.kind {
+                DeclarationKind::Function => SymbolKind::FUNCTION,
+                DeclarationKind::Variable if declaration.is_static => SymbolKind::CONSTANT,
+                DeclarationKind::Parameter | DeclarationKind::Variable => SymbolKind::VARIABLE,
+            };
+
+            #[allow(deprecated)] // `deprecated` is a required field, even though it is superseded by `tags`
+            DocumentSymbol {
+                name: declaration.name.clone(),
+                detail: Some(declaration.signature()),
+                kind,
+                tags: None,
+                deprecated: None,
+                range: index.range(declaration.node_span),
+                selection_range: index.range(declaration.span),
+                children: None,
+            }
+        })
+        .collect()
+}
+
+/// Returns a folding range for every pair of braces that spans multiple lines. The closing
+/// brace stays visible when a range is folded.
+pub fn folding_ranges(text: &str, tokens: &[Token]) -> Vec<FoldingRange> {
+    let index = LineIndex::new(text);
+    let mut open = Vec::new();
+    let mut ranges = Vec::new();
+
+    for token in tokens {
+        match token.token_type {
+            TokenType::LBrace => open.push(index.position(token.span.start().to_usize()).line),
+            TokenType::RBrace => {
+                let Some(start_line) = open.pop() else {
+                    continue
+                };
+                let end_line = index.position(token.span.start().to_usize()).line;
+
+                if end_line > start_line + 1 {
+                    ranges.push(FoldingRange {
+                        start_line,
+                        end_line: end_line - 1,
+                        kind: Some(FoldingRangeKind::Region),
+                        ..Default::default()
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ranges.sort_by_key(|range| range.start_line);
+    ranges
+}
@@ -0,0 +1,105 @@
+use codespan::Span;
+use ast::checker::PRINT;
+use ast::lexer::{Lexer, TokenType};
+use ast::parser::{ident_to_type, Parser};
+use ast::resolver::{resolve, DeclarationId, DeclarationKind, Resolution};
+use ast::Type;
+
+/// Returns the span of the name at a byte offset if it can be renamed, which is the case for
+/// every declared function, parameter and variable.
+pub fn prepare_rename(resolution: &Resolution, offset: usize) -> Option<Span> {
+    let id = resolution.declaration_at(offset)?;
+    let declaration = &resolution.declarations[id];
+
+    let contains = |span: &Span| span.start().to_usize() <= offset && offset <= span.end().to_usize();
+    resolution.references_to(id)
+        .map(|reference| reference.span)
+        .chain(std::iter::once(declaration.span))
+        .find(contains)
+}
+
+/// Renames the declaration of the name at a byte offset together with every use of it.
+///
+/// The rename is refused if the new name is not a valid name, if it is already declared in
+/// the same scope, or if it would change what any name in the document refers to, which happens
+/// when the new name shadows another declaration or is shadowed by one.
+///
+/// # Arguments
+///
+/// * `text` - The contents of the document.
+/// * `resolution` - The resolution of the document.
+/// * `offset` - A byte offset on the name, either where it is declared or where it is used.
+/// * `new_name` - The name to rename to.
+///
+/// # Returns
+///
+/// Returns the spans to replace with the new name, or why the rename is refused.
+pub fn rename(text: &str, resolution: &Resolution, offset: usize, new_name: &str) -> Result<Vec<Span>, String> {
+    let id = resolution.declaration_at(offset)
+        .ok_or_else(|| "There is no function, parameter or variable to rename here".to_string())?;
+    let declaration = &resolution.declarations[id];
+
+    let is_name = match Lexer::new("rename", new_name).quiet().try_tokenize().as_deref() {
+        Ok([token]) => matches!(token.token_type, TokenType::Identifier(_)),
+        _ => false,
+    };
+    if !is_name || ident_to_type(new_name.to_string()) != Type::Reference {
+        return Err(format!("`{}` cannot be used as a name", new_name))
+    }
+
+    let is_function = declaration.kind == DeclarationKind::Function;
+    let taken = (is_function && new_name == PRINT) || resolution.declarations.iter()
+        .enumerate()
+        .any(|(other, existing)| {
+            other != id
+                && existing.name == new_name
+                && (existing.kind == DeclarationKind::Function) == is_function
+                // functions share a single scope
+                && (is_function || existing.parent == declaration.parent)
+        });
+    if taken {
+        return Err(format!("`{}` is already declared in the scope of `{}`", new_name, declaration.name))
+    }
+
+    let mut spans: Vec<Span> = resolution.references_to(id)
+        .map(|reference| reference.span)
+        .chain(std::iter::once(declaration.span))
+        .collect();
+    spans.sort_by_key(|span| span.start());
+
+    if !resolves_the_same(text, resolution, &spans, new_name) {
+        return Err(format!(
+            "Renaming `{}` to `{}` would change what other names refer to",
+            declaration.name,
+            new_name
+        ))
+    }
+
+    Ok(spans)
+}
+
+/// Returns whether every name in the document still refers to the same declaration after the
+/// given spans are replaced with `new_name`.
+fn resolves_the_same(text: &str, resolution: &Resolution, spans: &[Span], new_name: &str) -> bool {
+    let mut renamed = String::with_capacity(text.len());
+    let mut copied = 0;
+    for span in spans {
+        renamed.push_str(&text[copied..span.start().to_usize()]);
+        renamed.push_str(new_name);
+        copied = span.end().to_usize();
+    }
+    renamed.push_str(&text[copied..]);
+
+    let (tokens, _) = Lexer::new("rename", &renamed).quiet().tokenize_with_errors();
+    let document = Parser::new(tokens.clone()).parse();
+    let after = resolve(&document, &tokens);
+
+    // the declarations keep their order, so their ids stay the same
+    let targets = |resolution: &Resolution| -> Vec<DeclarationId> {
+        let mut references = resolution.references.clone();
+        references.sort_by_key(|reference| reference.span.start());
+        references.into_iter().map(|reference| reference.declaration).collect()
+    };
+
+    after.declarations.len() == resolution.declarations.len() && targets(&after) == targets(resolution)
+}
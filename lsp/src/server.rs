@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, LogMessage, Notification as LspNotification,
+    PublishDiagnostics,
+};
+use lsp_types::request::{
+    CodeActionRequest, Completion, DocumentSymbolRequest, FoldingRangeRequest, Formatting, GotoDefinition, HoverRequest,
+    PrepareRenameRequest, References, Rename, Request as LspRequest, SemanticTokensFullRequest,
+    SignatureHelpRequest,
+};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability,
+    CodeActionResponse, CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DocumentFormattingParams, DocumentSymbolParams,
+    DocumentSymbolResponse, FoldingRange, FoldingRangeParams, FoldingRangeProviderCapability, DidCloseTextDocumentParams, DidOpenTextDocumentParams, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, InitializeParams, Location, LogMessageParams, MarkupContent,
+    MarkupKind, MessageType, OneOf, PrepareRenameResponse, PublishDiagnosticsParams, ReferenceParams, RenameOptions,
+    RenameParams, SemanticTokens, SemanticTokensFullOptions,
+    SemanticTokensOptions, SemanticTokensParams, SemanticTokensResult, SemanticTokensServerCapabilities,
+    ServerCapabilities, SignatureHelp, SignatureHelpOptions, SignatureHelpParams, TextDocumentContentChangeEvent, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit,
+};
+use ast::formatter::Formatter;
+use codespan::Span;
+use common::config::Config;
+use database::Database;
+use crate::analysis::{analyze, Analysis};
+use crate::completion;
+use crate::convert::{to_lsp_diagnostic, to_path};
+use crate::highlight;
+use crate::line_index::LineIndex;
+use crate::navigation;
+use crate::outline;
+use crate::rename;
+
+type ServerResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// An open document, as last sent by the client.
+struct Document {
+    text: String,
+    version: i32,
+    /// The analysis of the current text, which is brought up to date on every change
+    analysis: Analysis,
+}
+
+impl Document {
+    /// Analyzes a document that was added to the database under its URI.
+    fn new(db: &Database, uri: &Uri, version: i32) -> Self {
+        Document {
+            text: db.text(uri.as_str()).unwrap_or_default().to_string(),
+            version,
+            analysis: analyze(db, uri.as_str()),
+        }
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Struct representing the state of the language server.
+struct Server {
+    connection: Connection,
+    /// The text of every open document, which is lexed, parsed and checked on demand
+    db: Database,
+    /// Every document the client has opened
+    documents: HashMap<Uri, Document>,
+}
+
+/// Runs the language server on an established connection until the client shuts it down.
+///
+/// # Arguments
+///
+/// * `connection` - The connection to the client, which has not been initialized yet. It is
+///   dropped when the server stops, which ends the connection's IO threads.
+///
+/// # Returns
+///
+/// Returns an error if the connection to the client broke.
+pub fn run(connection: Connection) -> ServerResult<()> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::INCREMENTAL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            ..Default::default()
+        }),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+            legend: highlight::legend(),
+            full: Some(SemanticTokensFullOptions::Bool(true)),
+            ..Default::default()
+        })),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+    let params: InitializeParams = serde_json::from_value(params).unwrap_or_default();
+
+    let mut server = Server {
+        connection,
+        db: Database::new(),
+        documents: HashMap::new(),
+    };
+    server.load_config(&params)?;
+
+    while let Ok(message) = server.connection.receiver.recv() {
+        match message {
+            Message::Request(request) => {
+                if server.connection.handle_shutdown(&request)? {
+                    return Ok(())
+                }
+
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the directory of the workspace the client opened, or the working directory of the
+/// server if the client did not name one.
+fn workspace_directory(params: &InitializeParams) -> Option<PathBuf> {
+    #[allow(deprecated)]
+    let uri = params.workspace_folders.as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(params.root_uri.as_ref());
+
+    match uri {
+        Some(uri) => to_path(uri),
+        None => std::env::current_dir().ok(),
+    }
+}
+
+impl Server {
+    /// Reports diagnostics at the levels, and allows the features, that the project config
+    /// file of the workspace sets, like `zxx` does. An invalid config file is logged to the
+    /// client and ignored.
+    fn load_config(&mut self, params: &InitializeParams) -> ServerResult<()> {
+        let Some(path) = workspace_directory(params).and_then(|directory| Config::find(&directory)) else {
+            return Ok(())
+        };
+
+        match Config::read(&path) {
+            Ok(config) => {
+                self.db.set_levels(config.levels);
+                self.db.set_features(config.features);
+                Ok(())
+            }
+            Err(message) => self.log(format!("Ignoring the invalid {}: {}", path.display(), message)),
+        }
+    }
+
+    fn handle_request(&mut self, request: Request) -> ServerResult<()> {
+        let response = match request.method.as_str() {
+            GotoDefinition::METHOD => self.respond::<GotoDefinition>(request, Self::definition),
+            References::METHOD => self.respond::<References>(request, Self::references),
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, Self::hover),
+            Completion::METHOD => self.respond::<Completion>(request, Self::completion),
+            SignatureHelpRequest::METHOD => self.respond::<SignatureHelpRequest>(request, Self::signature_help),
+            SemanticTokensFullRequest::METHOD => self.respond::<SemanticTokensFullRequest>(request, Self::semantic_tokens),
+            DocumentSymbolRequest::METHOD => self.respond::<DocumentSymbolRequest>(request, Self::document_symbols),
+            FoldingRangeRequest::METHOD => self.respond::<FoldingRangeRequest>(request, Self::folding_ranges),
+            PrepareRenameRequest::METHOD => self.respond::<PrepareRenameRequest>(request, Self::prepare_rename),
+            Rename::METHOD => self.try_respond::<Rename>(request, Self::rename),
+            CodeActionRequest::METHOD => self.respond::<CodeActionRequest>(request, Self::code_actions),
+            Formatting::METHOD => self.respond::<Formatting>(request, Self::format),
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request `{}`", request.method),
+            ),
+        };
+
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    /// Answers a request with the result of `handler`, or with an error if its parameters are
+    /// malformed.
+    fn respond<R: LspRequest>(&self, request: Request, handler: fn(&Self, R::Params) -> R::Result) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+
+    /// Answers a request with the result of `handler`, or with the error it failed with.
+    fn try_respond<R: LspRequest>(
+        &self,
+        request: Request,
+        handler: fn(&Self, R::Params) -> Result<R::Result, String>,
+    ) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => match handler(self, params) {
+                Ok(result) => Response::new_ok(request.id, result),
+                Err(message) => Response::new_err(request.id, ErrorCode::RequestFailed as i32, message),
+            },
+            Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let (document, offset) = self.locate(&position)?;
+        let span = navigation::definition(&document.analysis.resolution, offset)?;
+
+        let range = LineIndex::new(document.text()).range(span);
+        Some(GotoDefinitionResponse::Scalar(Location::new(position.text_document.uri, range)))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let position = params.text_document_position;
+        let (document, offset) = self.locate(&position)?;
+
+        let index = LineIndex::new(document.text());
+        let spans = navigation::references(&document.analysis.resolution, offset, params.context.include_declaration);
+
+        Some(spans.into_iter()
+            .map(|span| Location::new(position.text_document.uri.clone(), index.range(span)))
+            .collect())
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let (document, offset) = self.locate(&params.text_document_position_params)?;
+        let (value, span) = navigation::hover(document.text(), &document.analysis.resolution, offset)?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+            range: Some(LineIndex::new(document.text()).range(span)),
+        })
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let (document, offset) = self.locate(&params.text_document_position)?;
+        let items = completion::completions(document.text(), &document.analysis.resolution, offset);
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn signature_help(&self, params: SignatureHelpParams) -> Option<SignatureHelp> {
+        let (document, offset) = self.locate(&params.text_document_position_params)?;
+        let analysis = &document.analysis;
+
+        completion::signature_help(document.text(), &analysis.parse.tokens, &analysis.resolution, offset)
+    }
+
+    fn semantic_tokens(&self, params: SemanticTokensParams) -> Option<SemanticTokensResult> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let analysis = &document.analysis;
+        let data = highlight::semantic_tokens(document.text(), &analysis.parse.tokens, &analysis.resolution);
+
+        Some(SemanticTokensResult::Tokens(SemanticTokens { result_id: None, data }))
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let symbols = outline::document_symbols(document.text(), &document.analysis.resolution);
+
+        Some(DocumentSymbolResponse::Nested(symbols))
+    }
+
+    fn folding_ranges(&self, params: FoldingRangeParams) -> Option<Vec<FoldingRange>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        Some(outline::folding_ranges(document.text(), &document.analysis.parse.tokens))
+    }
+
+    fn prepare_rename(&self, params: TextDocumentPositionParams) -> Option<PrepareRenameResponse> {
+        let (document, offset) = self.locate(&params)?;
+        let span = rename::prepare_rename(&document.analysis.resolution, offset)?;
+
+        Some(PrepareRenameResponse::Range(LineIndex::new(document.text()).range(span)))
+    }
+
+    /// Renames a name in the document it is declared in. Z++ has no imports, so no other file
+    /// in the project can refer to it, even if it declares the same name.
+    fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>, String> {
+        let position = params.text_document_position;
+        let Some((document, offset)) = self.locate(&position) else {
+            return Ok(None)
+        };
+
+        let spans = rename::rename(document.text(), &document.analysis.resolution, offset, &params.new_name)?;
+        let index = LineIndex::new(document.text());
+        let edits = spans.into_iter()
+            .map(|span| TextEdit::new(index.range(span), params.new_name.clone()))
+            .collect();
+
+        Ok(Some(WorkspaceEdit::new(HashMap::from([(position.text_document.uri, edits)]))))
+    }
+
+    /// Offers the suggestions of every diagnostic that overlaps the requested range as quick
+    /// fixes.
+    fn code_actions(&self, params: CodeActionParams) -> Option<CodeActionResponse> {
+        let uri = params.text_document.uri;
+        let document = self.documents.get(&uri)?;
+        let index = LineIndex::new(document.text());
+
+        let mut actions = Vec::new();
+        for (diagnostic, suggestions) in document.analysis.diagnostics() {
+            let diagnostic = to_lsp_diagnostic(diagnostic, &uri, &index);
+            if diagnostic.range.end < params.range.start || params.range.end < diagnostic.range.start {
+                continue
+            }
+
+            for suggestion in suggestions {
+                let edit = TextEdit::new(index.range(suggestion.span.span), suggestion.replacement.clone());
+
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: suggestion.message.clone(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit::new(HashMap::from([(uri.clone(), vec![edit])]))),
+                    is_preferred: Some(suggestions.len() == 1),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Some(actions)
+    }
+
+    /// Formats a document in the style of `zxx fmt`, which has no options. A document with
+    /// syntax errors is left as it is.
+    fn format(&self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let uri = params.text_document.uri;
+        let document = self.documents.get(&uri)?;
+        let formatted = Formatter::new(uri.as_str(), document.text()).quiet().format().ok()?;
+
+        if formatted == document.text() {
+            return Some(Vec::new())
+        }
+
+        let whole = Span::new(0, document.text().len() as u32);
+        Some(vec![TextEdit::new(LineIndex::new(document.text()).range(whole), formatted)])
+    }
+
+    /// Returns the open document a request refers to, together with the byte offset of the
+    /// requested position.
+    fn locate(&self, position: &TextDocumentPositionParams) -> Option<(&Document, usize)> {
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = LineIndex::new(document.text()).offset(position.position);
+
+        Some((document, offset))
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> ServerResult<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => self.on::<DidOpenTextDocument>(notification, Self::did_open),
+            DidChangeTextDocument::METHOD => self.on::<DidChangeTextDocument>(notification, Self::did_change),
+            DidCloseTextDocument::METHOD => self.on::<DidCloseTextDocument>(notification, Self::did_close),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handles a notification with `handler`. A notification whose parameters are malformed
+    /// cannot be answered with an error, so it is logged to the client and otherwise ignored.
+    fn on<N: LspNotification>(
+        &mut self,
+        notification: Notification,
+        handler: fn(&mut Self, N::Params) -> ServerResult<()>,
+    ) -> ServerResult<()> {
+        match serde_json::from_value(notification.params) {
+            Ok(params) => handler(self, params),
+            Err(err) => self.log(format!("Ignoring a malformed `{}` notification: {}", notification.method, err)),
+        }
+    }
+
+    /// Logs an error to the client.
+    fn log(&self, message: String) -> ServerResult<()> {
+        let params = LogMessageParams { typ: MessageType::ERROR, message };
+
+        self.connection.sender.send(Notification::new(LogMessage::METHOD.to_string(), params).into())?;
+        Ok(())
+    }
+
+    fn did_open(&mut self, params: DidOpenTextDocumentParams) -> ServerResult<()> {
+        let document = params.text_document;
+
+        self.db.set_source(document.uri.as_str(), &document.text);
+        self.documents.insert(document.uri.clone(), Document::new(&self.db, &document.uri, document.version));
+        self.publish_diagnostics(&document.uri)
+    }
+
+    fn did_change(&mut self, params: DidChangeTextDocumentParams) -> ServerResult<()> {
+        let uri = params.text_document.uri;
+
+        if self.documents.contains_key(&uri) {
+            self.change(&uri, params.content_changes);
+            self.documents.insert(uri.clone(), Document::new(&self.db, &uri, params.text_document.version));
+        }
+        self.publish_diagnostics(&uri)
+    }
+
+    fn did_close(&mut self, params: DidCloseTextDocumentParams) -> ServerResult<()> {
+        let uri = params.text_document.uri;
+
+        // the document may be deleted, so its diagnostics should not linger
+        self.documents.remove(&uri);
+        self.db.remove_source(uri.as_str());
+        self.send_diagnostics(uri, Vec::new(), None)
+    }
+
+    /// Applies the changes the client made to a document to the database, in order.
+    fn change(&mut self, uri: &Uri, changes: Vec<TextDocumentContentChangeEvent>) {
+        let name = uri.as_str();
+
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let index = LineIndex::new(self.db.text(name).unwrap_or_default());
+                    let (start, end) = (index.offset(range.start), index.offset(range.end));
+                    self.db.edit_source(name, start..end, &change.text);
+                }
+                None => self.db.set_source(name, &change.text),
+            }
+        }
+    }
+
+    /// Publishes the diagnostics of an open document.
+    fn publish_diagnostics(&self, uri: &Uri) -> ServerResult<()> {
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(())
+        };
+
+        let index = LineIndex::new(document.text());
+        let diagnostics = document.analysis.diagnostics()
+            .map(|(diagnostic, _)| to_lsp_diagnostic(diagnostic, uri, &index))
+            .collect();
+
+        self.send_diagnostics(uri.clone(), diagnostics, Some(document.version))
+    }
+
+    fn send_diagnostics(&self, uri: Uri, diagnostics: Vec<lsp_types::Diagnostic>, version: Option<i32>) -> ServerResult<()> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+}
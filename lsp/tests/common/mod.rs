@@ -0,0 +1,154 @@
+// every test binary uses a different part of the client
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use serde_json::{json, Value};
+
+pub const URI: &str = "file:///project/main.zpp";
+
+/// A scripted client that talks JSON-RPC to a `zxx-lsp` process over its stdio.
+pub struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Client {
+    /// Starts the server and completes the initialization handshake.
+    pub fn start() -> Self {
+        Self::initialize(json!({ "capabilities": {} }))
+    }
+
+    /// Starts the server for a workspace, whose project config file it reads.
+    pub fn start_in(workspace: &Path) -> Self {
+        let uri = format!("file://{}", workspace.display());
+        Self::initialize(json!({
+            "capabilities": {},
+            "workspaceFolders": [{ "uri": uri, "name": "project" }],
+        }))
+    }
+
+    fn initialize(params: Value) -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_zxx-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("the server should start");
+
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+        let mut client = Client { process, stdin, stdout, next_id: 0 };
+
+        let response = client.request("initialize", params);
+        assert!(response["result"]["capabilities"]["textDocumentSync"].is_number());
+        client.notify("initialized", json!({}));
+
+        client
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+
+        loop {
+            let mut header = String::new();
+            self.stdout.read_line(&mut header).unwrap();
+
+            let header = header.trim_end();
+            if header.is_empty() {
+                break
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    pub fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Sends a request and returns its response, skipping any notifications sent before it.
+    pub fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+
+        loop {
+            let message = self.receive();
+            if message["id"] == json!(id) {
+                return message
+            }
+        }
+    }
+
+    /// Waits for the next diagnostics the server publishes.
+    pub fn diagnostics(&mut self) -> Value {
+        self.notification("textDocument/publishDiagnostics")
+    }
+
+    /// Waits for the next notification with the given method and returns its parameters.
+    pub fn notification(&mut self, method: &str) -> Value {
+        loop {
+            let message = self.receive();
+            if message["method"] == method {
+                return message["params"].clone()
+            }
+        }
+    }
+
+    pub fn open(&mut self, text: &str) -> Value {
+        self.open_document(URI, text)
+    }
+
+    /// Opens a document other than the test document.
+    pub fn open_document(&mut self, uri: &str, text: &str) -> Value {
+        self.notify("textDocument/didOpen", json!({
+            "textDocument": { "uri": uri, "languageId": "zpp", "version": 1, "text": text }
+        }));
+        self.diagnostics()
+    }
+
+    pub fn change(&mut self, version: i32, text: &str) -> Value {
+        self.notify("textDocument/didChange", json!({
+            "textDocument": { "uri": URI, "version": version },
+            "contentChanges": [{ "text": text }]
+        }));
+        self.diagnostics()
+    }
+
+    pub fn shutdown(mut self) {
+        let response = self.request("shutdown", Value::Null);
+        assert_eq!(response["result"], Value::Null);
+        self.notify("exit", Value::Null);
+
+        assert!(self.process.wait().unwrap().success());
+    }
+}
+
+pub fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+/// Returns the parameters of a request about a position in the test document.
+pub fn at(line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": URI },
+        "position": { "line": line, "character": character },
+    })
+}
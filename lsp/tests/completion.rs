@@ -0,0 +1,74 @@
+mod common;
+
+use serde_json::{json, Value};
+use common::{at, Client};
+
+fn labels(response: &Value) -> Vec<String> {
+    response["result"].as_array().unwrap().iter()
+        .map(|item| item["label"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn completions_offer_keywords_and_visible_names_in_incomplete_code() {
+    let mut client = Client::start();
+
+    // the body of `grow` is never closed and its last statement is half typed
+    let published = client.open("int total = 0\n/// Grows the total.\nfunc grow(int step) {\n    int next = total + step\n    ne\nint later = 1");
+    assert_eq!(published["diagnostics"][0]["code"], "E012");
+
+    let response = client.request("textDocument/completion", at(4, 6));
+    let labels = labels(&response);
+    for expected in ["func", "static", "while", "true", "null", "print", "total", "grow", "step", "next"] {
+        assert!(labels.contains(&expected.to_string()), "`{}` is missing from {:?}", expected, labels);
+    }
+    assert!(!labels.contains(&"later".to_string()));
+
+    let grow = response["result"].as_array().unwrap().iter()
+        .find(|item| item["label"] == "grow")
+        .unwrap();
+    assert_eq!(grow["kind"], 3);
+    assert_eq!(grow["detail"], "func grow(int step)");
+    assert_eq!(grow["documentation"]["value"], "Grows the total.");
+
+    client.shutdown();
+}
+
+#[test]
+fn nothing_is_completed_after_a_dot() {
+    let mut client = Client::start();
+    client.open("int total = 0\ntotal.");
+
+    let response = client.request("textDocument/completion", at(1, 6));
+    assert_eq!(response["result"], json!([]));
+
+    client.shutdown();
+}
+
+#[test]
+fn signature_help_tracks_the_active_parameter() {
+    let mut client = Client::start();
+    client.open("func move(int x, float y) {\n}\nmove(1, ");
+
+    let response = client.request("textDocument/signatureHelp", at(2, 5));
+    let signature = &response["result"]["signatures"][0];
+    assert_eq!(signature["label"], "func move(int x, float y)");
+    assert_eq!(signature["parameters"], json!([{ "label": "int x" }, { "label": "float y" }]));
+    assert_eq!(response["result"]["activeParameter"], 0);
+
+    let response = client.request("textDocument/signatureHelp", at(2, 8));
+    assert_eq!(response["result"]["activeParameter"], 1);
+
+    // nested parentheses belong to the argument
+    client.change(2, "func move(int x, float y) {\n}\nmove((1 + 2), print(");
+    let response = client.request("textDocument/signatureHelp", at(2, 20));
+    assert_eq!(response["result"]["signatures"][0]["label"], "func print(value)");
+
+    let response = client.request("textDocument/signatureHelp", at(2, 13));
+    assert_eq!(response["result"]["activeParameter"], 1);
+
+    let response = client.request("textDocument/signatureHelp", at(0, 3));
+    assert_eq!(response["result"], Value::Null);
+
+    client.shutdown();
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use serde_json::json;
+use ::common::temp::TempDir;
+use common::{range, Client, URI};
+
+#[test]
+fn diagnostics_follow_every_change() {
+    let mut client = Client::start();
+
+    let published = client.open("int count = 1\nint total = missing");
+    assert_eq!(published["uri"], URI);
+    assert_eq!(published["version"], 1);
+
+    let diagnostics = published["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "E002");
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(diagnostics[0]["source"], "zxx");
+    assert_eq!(diagnostics[0]["range"], range((1, 12), (1, 19)));
+
+    let published = client.change(2, "int count = 1\nprint(count)");
+    assert_eq!(published["version"], 2);
+    assert_eq!(published["diagnostics"], json!([]));
+
+    let published = client.change(3, "int count = 1\ncount = )");
+    assert_eq!(published["diagnostics"][0]["code"], "E012");
+
+    client.notify("textDocument/didClose", json!({ "textDocument": { "uri": URI } }));
+    assert_eq!(client.diagnostics()["diagnostics"], json!([]));
+
+    client.shutdown();
+}
+
+#[test]
+fn incremental_changes_are_applied_in_order() {
+    let mut client = Client::start();
+    client.open("int count = 1\nfunc bump() {\n    count = count + 1\n    print(total)\n}\nbump()\n");
+
+    // rename `count` in the body to `total`, which is not declared, then declare it
+    client.notify("textDocument/didChange", json!({
+        "textDocument": { "uri": URI, "version": 2 },
+        "contentChanges": [
+            { "range": range((2, 4), (2, 9)), "text": "total" },
+            { "range": range((0, 0), (0, 0)), "text": "mut int total = 0\n" },
+        ]
+    }));
+    let published = client.diagnostics();
+    assert_eq!(published["version"], 2);
+    assert_eq!(published["diagnostics"], json!([]));
+
+    client.notify("textDocument/didChange", json!({
+        "textDocument": { "uri": URI, "version": 3 },
+        "contentChanges": [{ "range": range((0, 8), (0, 13)), "text": "sum" }]
+    }));
+    let diagnostics = client.diagnostics()["diagnostics"].clone();
+    assert_eq!(diagnostics[0]["code"], "E002");
+    assert_eq!(diagnostics[0]["range"], range((3, 4), (3, 21)));
+
+    client.shutdown();
+}
+
+#[test]
+fn ranges_count_utf16_code_units() {
+    let mut client = Client::start();
+
+    // `é` is one UTF-16 code unit but two bytes, `😀` is two code units but four bytes
+    let published = client.open("String s = \"é😀\" print(s, s)");
+    let diagnostics = published["diagnostics"].as_array().unwrap();
+
+    assert_eq!(diagnostics[0]["code"], "E005");
+    assert_eq!(diagnostics[0]["range"], range((0, 17), (0, 28)));
+
+    client.shutdown();
+}
+
+#[test]
+fn secondary_labels_are_related_information() {
+    let mut client = Client::start();
+
+    let published = client.open("int count = 1\nint count = 2");
+    let diagnostic = &published["diagnostics"][0];
+
+    assert_eq!(diagnostic["code"], "E004");
+    assert_eq!(diagnostic["relatedInformation"], json!([{
+        "location": { "uri": URI, "range": range((0, 0), (0, 13)) },
+        "message": "first defined here",
+    }]));
+
+    client.shutdown();
+}
+
+#[test]
+fn lints_are_published_at_the_levels_of_the_project_config() {
+    let workspace = TempDir::new().unwrap();
+    let config = "[lints]\nunused-variable = \"deny\"\n\n[features]\nstatic-declarations = true\n";
+    std::fs::write(workspace.path().join("zxx.toml"), config).unwrap();
+    let mut client = Client::start_in(workspace.path());
+
+    let published = client.open("static int SIZE = 4\nint count = SIZE\n");
+    let diagnostics = published["diagnostics"].as_array().unwrap();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "W002");
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(diagnostics[0]["range"], range((1, 4), (1, 9)));
+
+    client.shutdown();
+}
+
+#[test]
+fn lex_errors_are_published_instead_of_exiting() {
+    let mut client = Client::start();
+
+    let published = client.open("int count = 1 #");
+    assert_eq!(published["diagnostics"][0]["code"], "E001");
+
+    let response = client.request("textDocument/unknown", json!({}));
+    assert_eq!(response["error"]["code"], -32601);
+
+    client.shutdown();
+}
+
+#[test]
+fn malformed_notifications_are_logged_instead_of_exiting() {
+    let mut client = Client::start();
+
+    client.notify("textDocument/didOpen", json!({ "textDocument": { "uri": URI } }));
+    let logged = client.notification("window/logMessage");
+    assert_eq!(logged["type"], 1);
+    assert!(logged["message"].as_str().unwrap().starts_with("Ignoring a malformed `textDocument/didOpen` notification"));
+
+    // the server still answers
+    let published = client.open("int count = missing");
+    assert_eq!(published["diagnostics"][0]["code"], "E002");
+
+    client.shutdown();
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use serde_json::{json, Value};
+use common::{range, Client, URI};
+
+fn formatting() -> Value {
+    json!({
+        "textDocument": { "uri": URI },
+        "options": { "tabSize": 2, "insertSpaces": true },
+    })
+}
+
+#[test]
+fn documents_are_formatted_with_a_single_edit() {
+    let mut client = Client::start();
+    client.open("func  twice(int a){\nint b=a*2 // doubled\n}\ntwice( 1 )");
+
+    let response = client.request("textDocument/formatting", formatting());
+    assert_eq!(response["result"], json!([{
+        "range": range((0, 0), (3, 10)),
+        "newText": "func twice(int a) {\n    int b = a * 2 // doubled\n}\ntwice(1)\n",
+    }]));
+
+    client.shutdown();
+}
+
+#[test]
+fn formatted_documents_and_syntax_errors_are_left_alone() {
+    let mut client = Client::start();
+    client.open("int x = 1\n");
+
+    let response = client.request("textDocument/formatting", formatting());
+    assert_eq!(response["result"], json!([]));
+
+    client.change(2, "int x = = 1\n");
+    let response = client.request("textDocument/formatting", formatting());
+    assert_eq!(response["result"], Value::Null);
+
+    client.shutdown();
+}
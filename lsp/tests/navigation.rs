@@ -0,0 +1,81 @@
+mod common;
+
+use serde_json::{json, Value};
+use common::{at, range, Client, URI};
+
+const SOURCE: &str = "\
+/// How many items fit.
+static int SIZE = 4
+int count = SIZE
+func grow(int step) {
+    count = count + step
+}
+grow(SIZE)
+";
+
+fn location(start: (u32, u32), end: (u32, u32)) -> Value {
+    json!({ "uri": URI, "range": range(start, end) })
+}
+
+#[test]
+fn definitions_resolve_variables_parameters_and_functions() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    // the parameter `step` in the body of `grow`
+    let response = client.request("textDocument/definition", at(4, 20));
+    assert_eq!(response["result"], location((3, 14), (3, 18)));
+
+    // a call before the cursor reaches the end of the name still counts
+    let response = client.request("textDocument/definition", at(6, 4));
+    assert_eq!(response["result"], location((3, 5), (3, 9)));
+
+    let response = client.request("textDocument/definition", at(2, 8));
+    assert_eq!(response["result"], location((2, 4), (2, 9)));
+
+    // keywords and literals have no definition
+    let response = client.request("textDocument/definition", at(3, 1));
+    assert_eq!(response["result"], Value::Null);
+
+    client.shutdown();
+}
+
+#[test]
+fn references_include_the_declaration_on_request() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    let mut params = at(1, 12);
+    params["context"] = json!({ "includeDeclaration": false });
+    let response = client.request("textDocument/references", params.clone());
+    assert_eq!(response["result"], json!([
+        location((2, 12), (2, 16)),
+        location((6, 5), (6, 9)),
+    ]));
+
+    params["context"] = json!({ "includeDeclaration": true });
+    let response = client.request("textDocument/references", params);
+    assert_eq!(response["result"].as_array().unwrap().len(), 3);
+    assert_eq!(response["result"][0], location((1, 11), (1, 15)));
+
+    client.shutdown();
+}
+
+#[test]
+fn hover_shows_the_signature_and_doc_comment() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    let response = client.request("textDocument/hover", at(6, 6));
+    assert_eq!(response["result"]["contents"]["kind"], "markdown");
+    assert_eq!(response["result"]["contents"]["value"], "```zpp\nstatic int SIZE\n```\n\nHow many items fit.");
+    assert_eq!(response["result"]["range"], range((6, 5), (6, 9)));
+
+    let response = client.request("textDocument/hover", at(6, 0));
+    assert_eq!(response["result"]["contents"]["value"], "```zpp\nfunc grow(int step)\n```");
+
+    let response = client.request("textDocument/hover", at(4, 20));
+    assert_eq!(response["result"]["contents"]["value"], "```zpp\nint step\n```");
+
+    client.shutdown();
+}
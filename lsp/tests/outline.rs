@@ -0,0 +1,97 @@
+mod common;
+
+use serde_json::{json, Value};
+use common::{range, Client, URI};
+
+const SOURCE: &str = "\
+static int LIMIT = 10
+mut int count = 0
+func bump(int step) {
+    count = count + step * LIMIT
+    print(\"bumped\")
+}
+";
+
+fn document() -> Value {
+    json!({ "textDocument": { "uri": URI } })
+}
+
+/// Decodes the relative encoding of semantic tokens into absolute
+/// `(line, column, length, type, modifiers)` tuples.
+fn decode(data: &Value) -> Vec<(u64, u64, u64, u64, u64)> {
+    let data: Vec<u64> = data.as_array().unwrap().iter().map(|value| value.as_u64().unwrap()).collect();
+    let (mut line, mut column) = (0, 0);
+
+    data.chunks(5)
+        .map(|token| {
+            line += token[0];
+            column = if token[0] == 0 { column + token[1] } else { token[1] };
+            (line, column, token[2], token[3], token[4])
+        })
+        .collect()
+}
+
+#[test]
+fn semantic_tokens_classify_names_by_their_declaration() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    let response = client.request("textDocument/semanticTokens/full", document());
+    let tokens = decode(&response["result"]["data"]);
+
+    // types: keyword 0, number 1, string 2, operator 3, function 4, parameter 5, variable 6,
+    // type 7. modifiers: declaration 1, readonly 2, static 4, mutable 8
+    assert_eq!(&tokens[..5], &[
+        (0, 0, 6, 0, 0),
+        (0, 7, 3, 7, 0),
+        (0, 11, 5, 6, 1 | 2 | 4),
+        (0, 17, 1, 3, 0),
+        (0, 19, 2, 1, 0),
+    ]);
+    assert!(tokens.contains(&(1, 0, 3, 0, 0)));
+    assert!(tokens.contains(&(1, 8, 5, 6, 1 | 8)));
+    assert!(tokens.contains(&(2, 5, 4, 4, 1)));
+    assert!(tokens.contains(&(2, 14, 4, 5, 1 | 2)));
+    assert!(tokens.contains(&(3, 4, 5, 6, 8)));
+    assert!(tokens.contains(&(3, 27, 5, 6, 2 | 4)));
+    assert!(tokens.contains(&(4, 10, 8, 2, 0)));
+    // `print` has no declaration
+    assert!(!tokens.iter().any(|token| token.0 == 4 && token.1 == 4));
+
+    client.shutdown();
+}
+
+#[test]
+fn outline_lists_functions_and_top_level_variables() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    let response = client.request("textDocument/documentSymbol", document());
+    let symbols: Vec<(&str, u64, &str)> = response["result"].as_array().unwrap().iter()
+        .map(|symbol| (
+            symbol["name"].as_str().unwrap(),
+            symbol["kind"].as_u64().unwrap(),
+            symbol["detail"].as_str().unwrap(),
+        ))
+        .collect();
+    assert_eq!(symbols, vec![
+        ("LIMIT", 14, "static int LIMIT"),
+        ("count", 13, "mut int count"),
+        ("bump", 12, "func bump(int step)"),
+    ]);
+    assert_eq!(response["result"][2]["range"], range((2, 0), (5, 1)));
+    assert_eq!(response["result"][2]["selectionRange"], range((2, 5), (2, 9)));
+
+    client.shutdown();
+}
+
+#[test]
+fn folding_ranges_cover_multi_line_blocks() {
+    let mut client = Client::start();
+    client.open(&format!("{}func empty() {{}}\n", SOURCE));
+
+    let response = client.request("textDocument/foldingRange", document());
+    assert_eq!(response["result"], json!([{ "startLine": 2, "endLine": 4, "kind": "region" }]));
+
+    client.shutdown();
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use serde_json::{json, Value};
+use common::{at, range, Client, URI};
+
+const SOURCE: &str = "\
+int count = 0
+int total = 0
+func bump(int step) {
+    int doubled = step * 2
+    count = count + doubled
+}
+bump(count)
+";
+
+fn rename(client: &mut Client, line: u32, character: u32, new_name: &str) -> Value {
+    let mut params = at(line, character);
+    params["newName"] = json!(new_name);
+    client.request("textDocument/rename", params)
+}
+
+fn edit(start: (u32, u32), end: (u32, u32), new_text: &str) -> Value {
+    json!({ "range": range(start, end), "newText": new_text })
+}
+
+#[test]
+fn rename_updates_the_declaration_and_every_use() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    let response = client.request("textDocument/prepareRename", at(4, 13));
+    assert_eq!(response["result"], range((4, 12), (4, 17)));
+
+    let response = rename(&mut client, 4, 13, "counter");
+    assert_eq!(response["result"]["changes"][URI], json!([
+        edit((0, 4), (0, 9), "counter"),
+        edit((4, 4), (4, 9), "counter"),
+        edit((4, 12), (4, 17), "counter"),
+        edit((6, 5), (6, 10), "counter"),
+    ]));
+
+    let response = rename(&mut client, 2, 6, "grow");
+    assert_eq!(response["result"]["changes"][URI], json!([
+        edit((2, 5), (2, 9), "grow"),
+        edit((6, 0), (6, 4), "grow"),
+    ]));
+
+    // a parameter may take the name of a global it does not shadow any use of
+    let response = rename(&mut client, 3, 18, "total");
+    assert_eq!(response["result"]["changes"][URI].as_array().unwrap().len(), 2);
+
+    client.shutdown();
+}
+
+#[test]
+fn rename_refuses_collisions() {
+    let mut client = Client::start();
+    client.open(SOURCE);
+
+    // declared in the same scope
+    let response = rename(&mut client, 0, 5, "total");
+    assert_eq!(response["error"]["code"], -32803);
+    assert_eq!(response["error"]["message"], "`total` is already declared in the scope of `count`");
+
+    let response = rename(&mut client, 3, 9, "step");
+    assert_eq!(response["error"]["code"], -32803);
+
+    // the parameter would shadow the global `count` that the body uses
+    let response = rename(&mut client, 2, 15, "count");
+    assert_eq!(response["error"]["message"], "Renaming `step` to `count` would change what other names refer to");
+
+    for invalid in ["func", "int", "two words", "9lives", "print"] {
+        let response = rename(&mut client, 2, 6, invalid);
+        assert_eq!(response["error"]["code"], -32803, "renaming to `{}` was accepted", invalid);
+    }
+
+    client.shutdown();
+}
+
+#[test]
+fn names_are_only_renamed_in_their_own_document() {
+    const OTHER: &str = "file:///project/other.zpp";
+
+    let mut client = Client::start();
+    client.open(SOURCE);
+    client.open_document(OTHER, "int count = 5\nprint(count)\n");
+
+    // without imports, the `count` of the other document is a different variable
+    let response = rename(&mut client, 0, 5, "counter");
+    let changes = response["result"]["changes"].as_object().unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[URI].as_array().unwrap().len(), 4);
+
+    client.shutdown();
+}
+
+#[test]
+fn code_actions_apply_the_suggestions_of_diagnostics() {
+    let mut client = Client::start();
+    let published = client.open("static int LIMIT = 1\nLIMIT = 2\nint ratio = 1.5\n");
+    // `static` declarations are not enabled, which is reported as well
+    assert_eq!(published["diagnostics"].as_array().unwrap().len(), 3);
+    assert_eq!(published["diagnostics"][0]["code"], "E018");
+
+    let mut params = json!({
+        "textDocument": { "uri": URI },
+        "range": range((1, 0), (1, 0)),
+        "context": { "diagnostics": [] },
+    });
+    let response = client.request("textDocument/codeAction", params.clone());
+    let actions = response["result"].as_array().unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0]["title"], "Replace `static` with `mut` so that `LIMIT` can be assigned to");
+    assert_eq!(actions[0]["kind"], "quickfix");
+    assert_eq!(actions[0]["diagnostics"][0]["code"], "E010");
+    assert_eq!(actions[0]["edit"]["changes"][URI], json!([edit((0, 0), (0, 6), "mut")]));
+
+    params["range"] = range((2, 4), (2, 9));
+    let response = client.request("textDocument/codeAction", params);
+    assert_eq!(response["result"][0]["title"], "Change the type of `ratio` to `float`");
+    assert_eq!(response["result"][0]["edit"]["changes"][URI], json!([edit((2, 0), (2, 3), "float")]));
+
+    client.shutdown();
+}
+
+#[test]
+fn assigned_variables_can_be_declared_mutable() {
+    let mut client = Client::start();
+    let published = client.open("int count = 1\ncount = 2\n");
+    assert_eq!(published["diagnostics"][0]["code"], "E016");
+
+    let response = client.request("textDocument/codeAction", json!({
+        "textDocument": { "uri": URI },
+        "range": range((1, 0), (1, 5)),
+        "context": { "diagnostics": [] },
+    }));
+    let actions = response["result"].as_array().unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0]["title"], "Declare `count` as mutable");
+    assert_eq!(actions[0]["edit"]["changes"][URI], json!([edit((0, 0), (0, 0), "mut ")]));
+
+    client.shutdown();
+}